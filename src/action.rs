@@ -1,6 +1,6 @@
 use traits::create_action;
 
-use crate::{component::Position, entity::EntityID, game::{Game, GameState}, ui::menu::{self, Menu, MenuType}};
+use crate::{component::Position, electricity, entity::EntityID, fire, fov, game::{Game, GameState}, item::{self, Item, ItemTagMap, ItemType}, lighting, map::{self, GameMap, Tile}, monster, pathfinding, registry, rng::RngStream, substance::{self, DrinkEffect, Liquid, Substance, SubstanceTagMap, Volume}, scheduler, tabletop::{self, AdvantageStatus, DamageType, Skill}, tag::Tag, temperature, trap, time::{self, Seconds, SECONDS_PER_HOUR, SECONDS_PER_MINUTE, SECONDS_PER_TURN}, travel::{self, AutoMove}, ui::menu::{self, Menu, MenuType}, weather};
 
 create_action!(CloseMenu);
 create_action!(NavigateMenu);
@@ -25,6 +25,7 @@ create_action!(Consult);
 create_action!(Crush);
 create_action!(Cut);
 create_action!(Dig);
+create_action!(Disarm);
 create_action!(Disrobe);
 create_action!(Drink);
 create_action!(Drop);
@@ -33,7 +34,9 @@ create_action!(Empty);
 create_action!(Enter);
 create_action!(Examine);
 create_action!(Exit);
+create_action!(Explore);
 create_action!(Fill);
+create_action!(Forage);
 create_action!(GetOff);
 create_action!(Give);
 create_action!(Go);
@@ -76,6 +79,7 @@ create_action!(ThrowAt);
 create_action!(ThrownAt);
 create_action!(Tie);
 create_action!(Touch);
+create_action!(Travel);
 create_action!(Turn);
 create_action!(Unlock);
 create_action!(Wait);
@@ -112,6 +116,7 @@ pub enum Action {
     Crush(Crush),
     Cut(Cut),
     Dig(Dig),
+    Disarm(Disarm),
     Disrobe(Disrobe),
     Drink(Drink),
     Drop(Drop),
@@ -120,7 +125,9 @@ pub enum Action {
     Enter(Enter),
     Examine(Examine),
     Exit(Exit),
+    Explore(Explore),
     Fill(Fill),
+    Forage(Forage),
     GetOff(GetOff),
     Give(Give),
     Go(Go),
@@ -163,6 +170,7 @@ pub enum Action {
     ThrownAt(ThrownAt),
     Tie(Tie),
     Touch(Touch),
+    Travel(Travel),
     Turn(Turn),
     Unlock(Unlock),
     Wait(Wait),
@@ -188,14 +196,21 @@ macro_rules! stub_action {
     ($class:ident) => {
         impl ActionRoutine for $class {
             fn execute(_game: &mut Game, _actor: EntityID, _noun: Noun, _second: Noun) -> bool {
-                return false;
+                return true;
             }
         }
     };
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemSelection {
+    pub index: usize,
+    pub quantity: u32,
+}
+
 pub enum Noun {
     Entity(EntityID),
+    Item(ItemSelection),
     Literal(String),
     Menu(MenuType),
     Nothing,
@@ -219,10 +234,29 @@ pub fn is_meta(action: Action) -> bool {
         Action::Restart(_) => true,
         Action::Restore(_) => true,
         Action::Save(_) => true,
+        // These only set the player off, and each step takes its own time
+        Action::Explore(_) => true,
+        Action::Travel(_) => true,
         _ => false
     };
 }
 
+// Eight hours of sleep
+pub const LONG_REST: Seconds = 8 * SECONDS_PER_HOUR;
+// Picking over a patch of herbs for anything worth having
+pub const FORAGE_TIME: Seconds = 10 * SECONDS_PER_MINUTE;
+
+// How long an action takes, which is a single round unless it's something
+// that can't be rushed
+pub fn get_action_cost(action: Action) -> Seconds {
+    return match action {
+        Action::Eat(_) => SECONDS_PER_MINUTE,
+        Action::Forage(_) => FORAGE_TIME,
+        Action::Sleep(_) => LONG_REST,
+        _ => SECONDS_PER_TURN,
+    };
+}
+
 pub enum RuleType {
     After,
     Before,
@@ -234,6 +268,429 @@ pub enum RuleType {
 
 pub type Rule = fn(RuleType, Action) -> bool;
 
+fn get_item_selection(noun: &Noun) -> Option<ItemSelection> {
+    return match noun {
+        Noun::Item(selection) => Some(*selection),
+        _ => None,
+    };
+}
+
+fn is_adjacent(game: &Game, first: EntityID, second: EntityID) -> bool {
+    let first_map = game.components.get_map_index(first);
+    let second_map = game.components.get_map_index(second);
+    let first_position = game.components.get_position(first);
+    let second_position = game.components.get_position(second);
+
+    if first_map.is_none() || second_map.is_none() || first_position.is_none() || second_position.is_none() {
+        return false;
+    }
+    if first_map.unwrap().map != second_map.unwrap().map {
+        return false;
+    }
+
+    let first_position = first_position.unwrap();
+    let second_position = second_position.unwrap();
+    return first_position.x.abs_diff(second_position.x) <= 1 && first_position.y.abs_diff(second_position.y) <= 1;
+}
+
+pub fn report(game: &mut Game, actor: EntityID, message: String) {
+    if actor == game.special_entities.player {
+        game.add_message(message);
+    }
+}
+
+// Reports the outcome of an action, returning true if it failed so that the
+// result can be handed straight back from an action routine
+fn report_outcome(game: &mut Game, actor: EntityID, outcome: Result<String, String>) -> bool {
+    return match outcome {
+        Ok(message) => {
+            report(game, actor, message);
+            false
+        },
+        Err(message) => {
+            report(game, actor, message);
+            true
+        },
+    };
+}
+
+// Looks for a position under or next to the actor that matches the predicate
+fn find_nearby(game: &Game, actor: EntityID, predicate: impl Fn(u16, u16) -> bool) -> Option<(u16, u16)> {
+    let position = game.components.get_position(actor)?;
+    let map = &game.current_map;
+
+    for offset_y in -1..=1 {
+        for offset_x in -1..=1 {
+            let x: i32 = position.x as i32 + offset_x;
+            let y: i32 = position.y as i32 + offset_y;
+            if x < 0 || y < 0 || x >= map.width as i32 || y >= map.height as i32 {
+                continue;
+            }
+            if predicate(x as u16, y as u16) {
+                return Some((x as u16, y as u16));
+            }
+        }
+    }
+
+    return None;
+}
+
+fn find_nearby_tile(game: &Game, actor: EntityID, predicate: fn(&Tile) -> bool) -> Option<(u16, u16)> {
+    return find_nearby(game, actor, |x, y| predicate(game.current_map.get_tile(x, y)));
+}
+
+// Moves one tile in the given direction, stopping at the edge of the map
+fn step(position: &mut Position, offset_x: i16, offset_y: i16, map: &GameMap) {
+    if position.x > 0 && offset_x < 0 || position.x < map.width - 1 && offset_x > 0 {
+        position.x = (position.x as i16 + offset_x) as u16;
+    }
+    if position.y > 0 && offset_y < 0 || position.y < map.height - 1 && offset_y > 0 {
+        position.y = (position.y as i16 + offset_y) as u16;
+    }
+}
+
+// Takes the player along whichever way out of the map they're standing on,
+// as long as it's one of the kinds asked for
+fn use_way_out(game: &mut Game, actor: EntityID, ways: &[Tile], refusal: &str) -> bool {
+    // Only the player moves between maps for now
+    if actor != game.special_entities.player {
+        return true;
+    }
+    let maybe_position = game.components.get_position(actor);
+    if maybe_position.is_none() {
+        return true;
+    }
+    let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+    let tile = *game.current_map.get_tile(x, y);
+    if !ways.contains(&tile) {
+        report(game, actor, String::from(refusal));
+        return true;
+    }
+
+    if let Err(message) = registry::follow_link(game, x, y) {
+        report(game, actor, message);
+        return true;
+    }
+    let message = match tile {
+        Tile::Entrance => "You make your way inside.",
+        Tile::Gate => "You pass through the gate.",
+        Tile::StairDown => "You go down the stairs.",
+        _ => "You climb the stairs.",
+    };
+    report(game, actor, String::from(message));
+    return false;
+}
+
+pub fn capitalize(text: String) -> String {
+    let mut characters = text.chars();
+    return match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => text,
+    };
+}
+
+// Whether a creature other than the actor is standing on a tile
+fn is_occupied(game: &Game, actor: EntityID, x: u16, y: u16) -> bool {
+    let occupant = game.components.get_creature_at(game.current_map.id, x, y);
+    return occupant.is_some() && occupant.unwrap() != actor;
+}
+
+// The direction entity for a step by the given offset
+pub fn get_direction(game: &Game, offset_x: i16, offset_y: i16) -> EntityID {
+    let directions = &game.special_entities;
+    return match (offset_x.signum(), offset_y.signum()) {
+        (0, -1) => directions.north,
+        (1, -1) => directions.north_east,
+        (1, 0) => directions.east,
+        (1, 1) => directions.south_east,
+        (0, 1) => directions.south,
+        (-1, 1) => directions.south_west,
+        (-1, 0) => directions.west,
+        (-1, -1) => directions.north_west,
+        _ => directions.world,
+    };
+}
+
+// A tinderbox strikes a flame, and anything already alight can pass it on
+fn is_fire_source(item: &Item) -> bool {
+    return item.item_type == ItemType::Tinderbox || item.state.contains(Tag::Burning);
+}
+
+// Whether the actor has a flame to hand, either the given item or anything
+// they're carrying
+fn has_flame(game: &Game, actor: EntityID, source: &Noun) -> bool {
+    let maybe_inventory = game.components.get_inventory(actor);
+    if maybe_inventory.is_none() {
+        return false;
+    }
+    let items = &maybe_inventory.unwrap().items;
+    return match get_item_selection(source) {
+        Some(selection) => items.get(selection.index).is_some_and(is_fire_source),
+        None => items.iter().any(is_fire_source),
+    };
+}
+
+fn light_item(game: &mut Game, actor: EntityID, selection: ItemSelection) -> bool {
+    let maybe_item = game.components.get_inventory(actor)
+        .and_then(|inventory| inventory.items.get(selection.index));
+    if maybe_item.is_none() {
+        report(game, actor, String::from("You don't have that."));
+        return true;
+    }
+    let target = maybe_item.unwrap();
+    let name = item::get_name(target.item_type);
+    if target.state.contains(Tag::Burning) {
+        report(game, actor, format!("The {} is already lit.", name));
+        return true;
+    }
+    let maybe_burn_time = item::get_burn_time(target.item_type);
+    if maybe_burn_time.is_none() {
+        report(game, actor, format!("You can't light the {}.", name));
+        return true;
+    }
+    if target.state.contains(Tag::Wet) {
+        report(game, actor, format!("The {} is too wet to light.", name));
+        return true;
+    }
+    let has_oil = target.liquid.is_some_and(|liquid| liquid.substance == Substance::Oil);
+    if item::burns_oil(target.item_type) && !has_oil {
+        report(game, actor, format!("The {} has no oil in it.", name));
+        return true;
+    }
+
+    // Light just one of a stack, carrying on from wherever it was put out
+    let items = &mut game.components.get_inventory_mut(actor).unwrap().items;
+    let mut lit = item::take_from_pile(items, selection.index, 1).unwrap();
+    lit.state.insert(Tag::Burning);
+    if lit.fuel == 0 {
+        lit.fuel = maybe_burn_time.unwrap();
+    }
+    item::add_to_pile(items, lit, &game.data_tables.item_tag_map);
+
+    report(game, actor, format!("You light the {}.", name));
+    return false;
+}
+
+fn insert_item(items: &mut Vec<Item>, selection: ItemSelection, container_index: usize, item_tag_map: &ItemTagMap) -> Result<String, String> {
+    if selection.index == container_index {
+        return Err(String::from("You can't put something inside itself."));
+    }
+    if selection.index >= items.len() || container_index >= items.len() {
+        return Err(String::from("You don't have that."));
+    }
+
+    let container = &items[container_index];
+    let container_name = item::get_name(container.item_type);
+    if !item::holds_items(container.item_type, item_tag_map) {
+        return Err(format!("You can't put things in the {}.", container_name));
+    }
+
+    let stack = &items[selection.index];
+    let quantity = u32::min(u32::max(1, selection.quantity), stack.quantity);
+    let weight = if quantity == stack.quantity {
+        item::get_total_weight(stack)
+    } else {
+        item::get_unit_weight(stack) * quantity
+    };
+    if weight > item::get_free_weight(container) {
+        return Err(format!("The {} is too full to hold that.", container_name));
+    }
+
+    let length_before = items.len();
+    let inserted = item::take_from_pile(items, selection.index, quantity).unwrap();
+    let container_index = if items.len() < length_before && selection.index < container_index {
+        container_index - 1
+    } else {
+        container_index
+    };
+
+    let message = format!("You put {} into the {}.", item::get_display_name(&inserted), container_name);
+    item::add_to_pile(&mut items[container_index].contents, inserted, item_tag_map);
+    return Ok(message);
+}
+
+// Pours as much of the liquid into the target as will fit, returning how
+// much was poured
+fn pour_into(liquid: Liquid, target: &mut Item, substance_tag_map: &SubstanceTagMap, item_tag_map: &ItemTagMap) -> Result<Volume, String> {
+    let target_name = item::get_name(target.item_type);
+
+    if !can_hold_substance(target, liquid.substance, substance_tag_map, item_tag_map) {
+        return Err(format!("The {} can't hold {}.", target_name, substance::get_apparent_name(liquid.substance, substance_tag_map)));
+    }
+    if target.liquid.is_some() && target.liquid.unwrap().substance != liquid.substance {
+        return Err(format!("The {} already has something else in it.", target_name));
+    }
+
+    let amount = u32::min(liquid.volume, item::get_free_volume(target));
+    if amount == 0 {
+        return Err(format!("The {} is already full.", target_name));
+    }
+
+    let existing: Volume = match target.liquid {
+        Some(existing) => existing.volume,
+        None => 0,
+    };
+    target.liquid = Some(Liquid::new(liquid.substance, existing + amount));
+
+    return Ok(amount);
+}
+
+// Liquids need a liquid container and gases need a gas container
+fn can_hold_substance(container: &Item, substance: substance::Substance, substance_tag_map: &SubstanceTagMap, item_tag_map: &ItemTagMap) -> bool {
+    let substance_tags = &substance_tag_map[substance];
+    let container_tags = &item_tag_map[container.item_type];
+
+    if substance_tags.contains(&Tag::Liquid) {
+        return item::holds_liquids(container.item_type, item_tag_map);
+    }
+    if substance_tags.contains(&Tag::Gas) {
+        return container_tags.contains(&Tag::GasContainer);
+    }
+    return container_tags.contains(&Tag::SolidContainer);
+}
+
+pub fn apply_damage(game: &mut Game, target: EntityID, amount: i32, damage_type: DamageType) {
+    let amount = if damage_type == DamageType::Lightning {
+        amount + electricity::get_extra_lightning_damage(game, target)
+    } else {
+        amount
+    };
+    let amount = monster::adjust_damage(game, target, amount, damage_type);
+    let maybe_health = game.components.get_health_mut(target);
+    if maybe_health.is_none() || amount <= 0 {
+        return;
+    }
+    let health = maybe_health.unwrap();
+    health.current -= amount;
+    let dead = health.current <= 0;
+
+    report(game, target, format!("You take {} {} damage.", amount, tabletop::get_damage_type_name(damage_type)));
+    if dead {
+        kill(game, target);
+    }
+}
+
+pub fn kill(game: &mut Game, target: EntityID) {
+    let maybe_alive = game.components.get_alive_mut(target);
+    if maybe_alive.is_none() {
+        return;
+    }
+    maybe_alive.unwrap().alive = false;
+    report(game, target, String::from("You die..."));
+}
+
+// Returns how many hit points were actually restored
+pub fn apply_healing(game: &mut Game, target: EntityID, amount: i32) -> i32 {
+    let exhaustion = match game.components.get_creature(target) {
+        Some(creature) => creature.exhaustion,
+        None => 0,
+    };
+    let maybe_health = game.components.get_health_mut(target);
+    if maybe_health.is_none() {
+        return 0;
+    }
+    let health = maybe_health.unwrap();
+    let maximum = tabletop::exhausted_max_hp(health.maximum, exhaustion);
+    let healed = i32::max(0, i32::min(amount, maximum - health.current));
+    health.current += healed;
+    return healed;
+}
+
+// The modifier a creature gets for a skill, and whether being tired or the
+// light gives it advantage or disadvantage
+fn get_skill_modifier(game: &Game, entity: EntityID, skill: Skill) -> Option<(i8, AdvantageStatus)> {
+    let creature = game.components.get_creature(entity)?;
+    let ability = tabletop::get_stat(&creature.stats, tabletop::skill_stat(skill));
+    let exhausted = if creature.exhaustion > 0 { AdvantageStatus::Disadvantage } else { AdvantageStatus::Normal };
+    let advantage = tabletop::combine_advantage(exhausted, lighting::get_light_advantage(game, entity, skill));
+    return Some((tabletop::modifier(ability), advantage));
+}
+
+pub fn get_passive_skill(game: &Game, entity: EntityID, skill: Skill) -> i8 {
+    return match get_skill_modifier(game, entity, skill) {
+        Some((modifier, advantage)) => tabletop::passive_score(modifier, advantage),
+        None => 0,
+    };
+}
+
+pub fn roll_skill_check(game: &mut Game, entity: EntityID, skill: Skill) -> i32 {
+    let (modifier, advantage) = get_skill_modifier(game, entity, skill).unwrap_or((0, AdvantageStatus::Normal));
+    return game.rng.roll_d20(RngStream::Skill, advantage) + modifier as i32;
+}
+
+fn quench_thirst(game: &mut Game, actor: EntityID, substance: Substance, volume: Volume) {
+    let hydration = substance::get_hydration(substance) * volume as Seconds;
+    let maybe_nutrition = game.components.get_nutrition_mut(actor);
+    if maybe_nutrition.is_none() || hydration == 0 {
+        return;
+    }
+    let nutrition = maybe_nutrition.unwrap();
+    nutrition.water = Seconds::min(nutrition.water + hydration, tabletop::MAX_WATER);
+    nutrition.parched = 0;
+}
+
+fn apply_drink_effect(game: &mut Game, actor: EntityID, substance: Substance) {
+    match substance::get_drink_effect(substance) {
+        DrinkEffect::Damage(dice, damage_type) => {
+            let damage = game.rng.roll(RngStream::Combat, dice);
+            apply_damage(game, actor, damage, damage_type);
+        },
+        DrinkEffect::Heal(dice) => {
+            let healing = game.rng.roll(RngStream::Combat, dice);
+            if apply_healing(game, actor, healing) > 0 {
+                report(game, actor, String::from("You feel better."));
+            }
+        },
+        DrinkEffect::Nothing => (),
+    };
+}
+
+// Finds the liquid an actor means to taste or smell, either in a held
+// container or, with no noun, spilled underfoot or in a nearby pool
+fn find_sensed_liquid(game: &Game, actor: EntityID, noun: &Noun) -> Result<Substance, String> {
+    let maybe_selection = get_item_selection(noun);
+    if maybe_selection.is_some() {
+        let maybe_item = game.components.get_inventory(actor)
+            .and_then(|inventory| inventory.items.get(maybe_selection.unwrap().index));
+        if maybe_item.is_none() {
+            return Err(String::from("You don't have that."));
+        }
+        return match maybe_item.unwrap().liquid {
+            Some(liquid) => Ok(liquid.substance),
+            None => Err(String::from("You notice nothing unusual.")),
+        };
+    }
+
+    if let Some(position) = game.components.get_position(actor) {
+        if let Some(spill) = game.current_map.get_spill(position.x, position.y) {
+            return Ok(spill);
+        }
+    }
+    return match find_nearby_tile(game, actor, |tile| map::get_liquid_source(tile).is_some()) {
+        Some((x, y)) => Ok(map::get_liquid_source(game.current_map.get_tile(x, y)).unwrap()),
+        None => Err(String::from("You notice nothing unusual.")),
+    };
+}
+
+// Poisons give themselves away only to a sharp enough sense
+fn sense_liquid(game: &mut Game, actor: EntityID, noun: &Noun, difficulty: i8, describe: fn(Substance) -> &'static str) -> bool {
+    let maybe_substance = find_sensed_liquid(game, actor, noun);
+    if maybe_substance.is_err() {
+        return report_outcome(game, actor, maybe_substance.map(|_| String::new()));
+    }
+    let substance = maybe_substance.unwrap();
+
+    let message = if !game.data_tables.tag_cache.substances[substance].contains(Tag::Poisonous) {
+        String::from(describe(substance))
+    } else if get_passive_skill(game, actor, Skill::Perception) >= difficulty {
+        format!("{} It must be poisoned!", describe(substance))
+    } else {
+        String::from("You notice nothing unusual.")
+    };
+    report(game, actor, message);
+    return false;
+}
+
 pub fn execute_action(game: &mut Game, action_request: ActionRequest) {
     
     let actor:EntityID = action_request.actor;
@@ -269,6 +726,7 @@ pub fn execute_action(game: &mut Game, action_request: ActionRequest) {
         Action::Crush(Crush) => Crush::execute(game, actor, noun, second),
         Action::Cut(Cut) => Cut::execute(game, actor, noun, second),
         Action::Dig(Dig) => Dig::execute(game, actor, noun, second),
+        Action::Disarm(Disarm) => Disarm::execute(game, actor, noun, second),
         Action::Disrobe(Disrobe) => Disrobe::execute(game, actor, noun, second),
         Action::Drink(Drink) => Drink::execute(game, actor, noun, second),
         Action::Drop(Drop) => Drop::execute(game, actor, noun, second),
@@ -277,7 +735,9 @@ pub fn execute_action(game: &mut Game, action_request: ActionRequest) {
         Action::Enter(Enter) => Enter::execute(game, actor, noun, second),
         Action::Examine(Examine) => Examine::execute(game, actor, noun, second),
         Action::Exit(Exit) => Exit::execute(game, actor, noun, second),
+        Action::Explore(Explore) => Explore::execute(game, actor, noun, second),
         Action::Fill(Fill) => Fill::execute(game, actor, noun, second),
+        Action::Forage(Forage) => Forage::execute(game, actor, noun, second),
         Action::GetOff(GetOff) => GetOff::execute(game, actor, noun, second),
         Action::Give(Give) => Give::execute(game, actor, noun, second),
         Action::Go(Go) => Go::execute(game, actor, noun, second),
@@ -320,6 +780,7 @@ pub fn execute_action(game: &mut Game, action_request: ActionRequest) {
         Action::ThrownAt(ThrownAt) => ThrownAt::execute(game, actor, noun, second),
         Action::Tie(Tie) => Tie::execute(game, actor, noun, second),
         Action::Touch(Touch) => Touch::execute(game, actor, noun, second),
+        Action::Travel(Travel) => Travel::execute(game, actor, noun, second),
         Action::Turn(Turn) => Turn::execute(game, actor, noun, second),
         Action::Unlock(Unlock) => Unlock::execute(game, actor, noun, second),
         Action::Wait(Wait) => Wait::execute(game, actor, noun, second),
@@ -337,6 +798,14 @@ pub fn execute_action(game: &mut Game, action_request: ActionRequest) {
         return;
     }
 
+    if !is_meta(action) {
+        let cost = scheduler::get_scaled_cost(game, actor, action);
+        scheduler::spend_time(game, actor, cost);
+        if actor == game.special_entities.player {
+            scheduler::advance_to_player_turn(game);
+        }
+    }
+
     if !is_meta(action) {
         //TODO(ches) if noun exists and after rule returns true, return
     }
@@ -357,6 +826,12 @@ impl ActionRoutine for CloseMenu {
         }
 
         match menu.unwrap() {
+            MenuType::Inventory(_) => {
+                game.menu_data.inventory.reset();
+                game.state = GameState::Running;
+            },
+            MenuType::Bestiary => game.state = GameState::Running,
+            MenuType::Travel => game.state = GameState::Running,
             MenuType::TestMenu => game.state = GameState::Menu(MenuType::Main),
             MenuType::NewCharacter => game.state = GameState::Menu(MenuType::Main),
             _ => ()
@@ -369,6 +844,7 @@ impl ActionRoutine for NavigateMenu {
     fn execute(game: &mut Game, _actor: EntityID, noun: Noun, _second: Noun) -> bool {
         let maybe_direction: Option<EntityID> = match noun {
             Noun::Entity(id) => Some(id),
+            Noun::Item(_) => None,
             Noun::Literal(_) => None,
             Noun::Menu(_) => None,
             Noun::Nothing => None,
@@ -468,9 +944,82 @@ stub_action!(Save);
 stub_action!(Answer);
 stub_action!(Ask);
 stub_action!(AskFor);
-stub_action!(Attack);
+impl ActionRoutine for Attack {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_target = match noun {
+            Noun::Entity(target) => Some(target),
+            _ => None,
+        };
+        if maybe_target.is_none() || game.components.get_health(maybe_target.unwrap()).is_none() {
+            report(game, actor, String::from("Attack what?"));
+            return true;
+        }
+        let target = maybe_target.unwrap();
+
+        let attacker_position = game.components.get_position(actor);
+        let target_position = game.components.get_position(target);
+        if attacker_position.is_none() || target_position.is_none() {
+            return true;
+        }
+        let (from_x, from_y) = (attacker_position.unwrap().x, attacker_position.unwrap().y);
+        let (to_x, to_y) = (target_position.unwrap().x, target_position.unwrap().y);
+        let distance = pathfinding::get_distance(from_x, from_y, to_x, to_y);
+        let maybe_attack = monster::choose_attack(game, actor, distance);
+        if maybe_attack.is_none() || (distance > 1 && !fov::has_line_of_sight(&game.current_map, from_x, from_y, to_x, to_y)) {
+            report(game, actor, String::from("You can't reach it from here."));
+            return true;
+        }
+        let attack = maybe_attack.unwrap();
+
+        let attacker_name = monster::get_creature_name(game, actor);
+        let target_name = monster::get_creature_name(game, target);
+        let roll = game.rng.roll_d20(RngStream::Combat, AdvantageStatus::Normal);
+        if !tabletop::is_hit(roll, attack.to_hit, monster::get_armor_class(game, target)) {
+            report(game, actor, format!("You miss {}.", target_name));
+            report(game, target, capitalize(format!("{} misses you.", attacker_name)));
+            return false;
+        }
+
+        let critical = roll == tabletop::CRITICAL_HIT;
+        let verb = if critical { "critically hit" } else { "hit" };
+        report(game, actor, format!("You {} {} with your {}.", verb, target_name, attack.name));
+        report(game, target, capitalize(format!("{} {}s you with its {}.", attacker_name, verb, attack.name)));
+        let damage_dice = if critical { tabletop::critical_damage(attack.damage) } else { attack.damage };
+        let damage = i32::max(1, game.rng.roll(RngStream::Combat, damage_dice));
+        apply_damage(game, target, damage, attack.damage_type);
+        let alive = game.components.get_alive(target);
+        if alive.is_some() && !alive.unwrap().alive {
+            report(game, actor, format!("You kill {}.", target_name));
+        }
+        return false;
+    }
+}
 stub_action!(Blow);
-stub_action!(Burn);
+impl ActionRoutine for Burn {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, second: Noun) -> bool {
+        if !has_flame(game, actor, &second) {
+            report(game, actor, String::from("You have nothing to light it with."));
+            return true;
+        }
+
+        if let Some(selection) = get_item_selection(&noun) {
+            return light_item(game, actor, selection);
+        }
+
+        // Without an item, set light to whatever will burn close by
+        let map = &game.current_map;
+        let tag_cache = &game.data_tables.tag_cache;
+        let maybe_target = find_nearby(game, actor, |x, y| !map.is_burning(x, y) && fire::get_fuel(map, x, y, tag_cache) > 0);
+        if maybe_target.is_none() {
+            report(game, actor, String::from("There is nothing here that will burn."));
+            return true;
+        }
+        let (x, y) = maybe_target.unwrap();
+        fire::ignite(game.current_map.as_mut(), x, y, &game.data_tables.tag_cache);
+        report(game, actor, String::from("The flames catch."));
+        return false;
+    }
+}
 stub_action!(Buy);
 stub_action!(Clean);
 stub_action!(Climb);
@@ -479,22 +1028,405 @@ stub_action!(Consult);
 stub_action!(Crush);
 stub_action!(Cut);
 stub_action!(Dig);
+impl ActionRoutine for Disarm {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        let maybe_trap = find_nearby_tile(game, actor, |tile| matches!(tile, Tile::TrapKnown));
+        if maybe_trap.is_none() {
+            report(game, actor, String::from("There's no trap here that you know of."));
+            return true;
+        }
+        let (x, y) = maybe_trap.unwrap();
+        return trap::disarm(game, actor, x, y);
+    }
+}
 stub_action!(Disrobe);
-stub_action!(Drink);
-stub_action!(Drop);
-stub_action!(Eat);
-stub_action!(Empty);
-stub_action!(Enter);
+impl ActionRoutine for Drink {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            // Without a container, drink straight from a pool or river
+            let maybe_source = find_nearby_tile(game, actor, |tile| map::get_liquid_source(tile).is_some());
+            if maybe_source.is_none() {
+                report(game, actor, String::from("Drink what?"));
+                return true;
+            }
+            let (source_x, source_y) = maybe_source.unwrap();
+            let substance = map::get_liquid_source(game.current_map.get_tile(source_x, source_y)).unwrap();
+
+            report(game, actor, format!("You drink some {}. {}", substance::get_name(substance), substance::get_taste(substance)));
+            quench_thirst(game, actor, substance, substance::DRINK_VOLUME);
+            apply_drink_effect(game, actor, substance);
+            return false;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+        let items = &mut maybe_inventory.unwrap().items;
+        if selection.index >= items.len() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+
+        let container_name = item::get_name(items[selection.index].item_type);
+        let maybe_liquid = items[selection.index].liquid;
+        if maybe_liquid.is_none() {
+            report(game, actor, format!("The {} is empty.", container_name));
+            return true;
+        }
+        let liquid = maybe_liquid.unwrap();
+        let substance_tag_map = &game.data_tables.substance_tag_map;
+        if temperature::is_frozen(&items[selection.index]) {
+            report(game, actor, format!("The {} is frozen solid.", substance::get_apparent_name(liquid.substance, substance_tag_map)));
+            return true;
+        }
+        if !game.data_tables.tag_cache.substances[liquid.substance].contains(Tag::Beverage) {
+            report(game, actor, format!("You can't drink {}.", substance::get_name(liquid.substance)));
+            return true;
+        }
+
+        let amount = Volume::min(substance::DRINK_VOLUME, liquid.volume);
+        let remaining = if amount < liquid.volume {
+            Some(Liquid::new(liquid.substance, liquid.volume - amount))
+        } else {
+            None
+        };
+
+        // Drink from just one container of a stack
+        if items[selection.index].quantity > 1 {
+            let mut container = items[selection.index].split(1);
+            container.liquid = remaining;
+            item::add_to_pile(items, container, &game.data_tables.item_tag_map);
+        } else {
+            items[selection.index].liquid = remaining;
+        }
+
+        let name = substance::get_apparent_name(liquid.substance, substance_tag_map);
+        report(game, actor, format!("You drink some {}. {}", name, substance::get_taste(liquid.substance)));
+        quench_thirst(game, actor, liquid.substance, amount);
+        apply_drink_effect(game, actor, liquid.substance);
+        return false;
+    }
+}
+impl ActionRoutine for Drop {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Drop what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_position = game.components.get_position(actor);
+        if maybe_position.is_none() {
+            return true;
+        }
+        let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+
+        let maybe_item = item::take_from_pile(&mut maybe_inventory.unwrap().items, selection.index, selection.quantity);
+        if maybe_item.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        let dropped: Item = maybe_item.unwrap();
+
+        report(game, actor, format!("You drop {}.", item::get_display_name(&dropped)));
+        item::add_to_pile(game.current_map.get_items_mut(x, y), dropped, &game.data_tables.item_tag_map);
+
+        return false;
+    }
+}
+impl ActionRoutine for Eat {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Eat what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_food = game.components.get_inventory(actor)
+            .and_then(|inventory| inventory.items.get(selection.index));
+        if maybe_food.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        let food = maybe_food.unwrap();
+        let item_type = food.item_type;
+        let spoiled = item::is_spoiled(food);
+        if !item::has_tag(food, Tag::Food, &game.data_tables.tag_cache) {
+            report(game, actor, format!("You can't eat the {}.", item::get_name(item_type)));
+            return true;
+        }
+
+        let full = match game.components.get_nutrition(actor) {
+            Some(nutrition) => nutrition.food >= tabletop::MAX_FOOD,
+            None => false,
+        };
+        if full {
+            report(game, actor, String::from("You couldn't eat another bite."));
+            return true;
+        }
+
+        item::take_from_pile(&mut game.components.get_inventory_mut(actor).unwrap().items, selection.index, 1);
+
+        // Spoiled food is only half as filling, and makes you sick
+        let nourishment = if spoiled { item::get_nutrition(item_type) / 2 } else { item::get_nutrition(item_type) };
+        if let Some(nutrition) = game.components.get_nutrition_mut(actor) {
+            nutrition.food = Seconds::min(nutrition.food + nourishment, tabletop::MAX_FOOD);
+            nutrition.starving = 0;
+        }
+
+        report(game, actor, format!("You eat the {}.", item::get_name(item_type)));
+        if spoiled {
+            report(game, actor, String::from("It tasted off."));
+            let damage = game.rng.roll(RngStream::Combat, item::SPOILED_FOOD_DAMAGE);
+            apply_damage(game, actor, damage, DamageType::Poison);
+        }
+        return false;
+    }
+}
+impl ActionRoutine for Empty {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Empty what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+        let maybe_target = get_item_selection(&second);
+
+        let maybe_position = game.components.get_position(actor);
+        if maybe_position.is_none() {
+            return true;
+        }
+        let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+        let items = &mut maybe_inventory.unwrap().items;
+        if selection.index >= items.len() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        if temperature::is_frozen(&items[selection.index]) && items[selection.index].liquid.is_some() {
+            report(game, actor, String::from("It's frozen solid and won't pour."));
+            return true;
+        }
+
+        if let Some(target) = maybe_target {
+            let target_index = target.index;
+            if target_index == selection.index || target_index >= items.len() {
+                report(game, actor, String::from("You can't empty it into that."));
+                return true;
+            }
+
+            // Split the borrow so both containers can be changed at once
+            let (source, target) = if selection.index < target_index {
+                let (left, right) = items.split_at_mut(target_index);
+                (&mut left[selection.index], &mut right[0])
+            } else {
+                let (left, right) = items.split_at_mut(selection.index);
+                (&mut right[0], &mut left[target_index])
+            };
+
+            if source.liquid.is_none() {
+                report(game, actor, String::from("There is nothing to pour."));
+                return true;
+            }
+            let liquid = source.liquid.unwrap();
+
+            let outcome = pour_into(liquid, target, &game.data_tables.substance_tag_map, &game.data_tables.item_tag_map)
+                .map(|amount| {
+                    source.liquid = if amount < liquid.volume {
+                        Some(Liquid::new(liquid.substance, liquid.volume - amount))
+                    } else {
+                        None
+                    };
+                    format!("You pour the {} into the {}.", substance::get_apparent_name(liquid.substance, &game.data_tables.substance_tag_map), item::get_name(target.item_type))
+                });
+            return report_outcome(game, actor, outcome);
+        }
+
+        let container = &mut items[selection.index];
+        let container_name = item::get_name(container.item_type);
+        if container.contents.is_empty() && container.liquid.is_none() {
+            let message = format!("The {} is already empty.", container_name);
+            report(game, actor, message);
+            return true;
+        }
+
+        let contents: Vec<Item> = std::mem::take(&mut container.contents);
+        let poured = container.liquid.take();
+
+        let floor = game.current_map.get_items_mut(x, y);
+        for content in contents {
+            item::add_to_pile(floor, content, &game.data_tables.item_tag_map);
+        }
+
+        let message = match poured {
+            Some(liquid) => {
+                game.current_map.set_spill(x, y, Some(liquid.substance));
+                // Anything that won't burn puts out the flames
+                if game.current_map.is_burning(x, y) && !game.data_tables.tag_cache.substances[liquid.substance].contains(Tag::Flammable) {
+                    fire::extinguish(game.current_map.as_mut(), x, y);
+                }
+                let name = substance::get_apparent_name(liquid.substance, &game.data_tables.substance_tag_map);
+                format!("You empty the {} and the {} splashes across the ground.", container_name, name)
+            },
+            None => format!("You empty the {} onto the ground.", container_name),
+        };
+        report(game, actor, message);
+
+        return false;
+    }
+}
+impl ActionRoutine for Enter {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        return use_way_out(game, actor, &[Tile::Entrance, Tile::Gate, Tile::StairDown], "There's no way down here.");
+    }
+}
 stub_action!(Examine);
-stub_action!(Exit);
-stub_action!(Fill);
+impl ActionRoutine for Exit {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        return use_way_out(game, actor, &[Tile::Gate, Tile::StairUp], "There's no way up here.");
+    }
+}
+impl ActionRoutine for Explore {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        if actor != game.special_entities.player {
+            return true;
+        }
+        game.auto_move = Some(AutoMove::Explore);
+        return false;
+    }
+}
+impl ActionRoutine for Fill {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Fill what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_source = find_nearby_tile(game, actor, |tile| map::get_liquid_source(tile).is_some());
+        if maybe_source.is_none() {
+            report(game, actor, String::from("There is nothing here to fill it with."));
+            return true;
+        }
+        let (source_x, source_y) = maybe_source.unwrap();
+        let substance = map::get_liquid_source(game.current_map.get_tile(source_x, source_y)).unwrap();
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+        let maybe_container = maybe_inventory.unwrap().items.get_mut(selection.index);
+        if maybe_container.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+
+        // A pool or river never runs dry, so pour in as much as will fit
+        let source = Liquid::new(substance, Volume::MAX);
+        let container = maybe_container.unwrap();
+
+        let outcome = pour_into(source, container, &game.data_tables.substance_tag_map, &game.data_tables.item_tag_map)
+            .map(|_| format!("You fill the {} with {}.", item::get_name(container.item_type), substance::get_name(substance)));
+        return report_outcome(game, actor, outcome);
+    }
+}
+// Picks a patch of herbs clean, finding something worth having more often
+// in the growing seasons than in the depths of winter
+impl ActionRoutine for Forage {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        let maybe_patch = find_nearby_tile(game, actor, |tile| matches!(tile, Tile::Herbs));
+        if maybe_patch.is_none() {
+            report(game, actor, String::from("There's nothing growing here worth foraging."));
+            return true;
+        }
+        let (x, y) = maybe_patch.unwrap();
+        game.current_map.set_tile(x, y, Tile::Plains);
+
+        let season = time::get_season(game.clock.get_date().month);
+        if !game.rng.chance(RngStream::Loot, weather::get_forage_chance(season)) {
+            report(game, actor, String::from("You find nothing worth picking."));
+            return false;
+        }
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return false;
+        }
+        item::add_to_pile(&mut maybe_inventory.unwrap().items, Item::new(ItemType::Herbs), &game.data_tables.item_tag_map);
+        report(game, actor, String::from("You gather some herbs."));
+        return false;
+    }
+}
 stub_action!(GetOff);
-stub_action!(Give);
+impl ActionRoutine for Give {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Give what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_recipient: Option<EntityID> = match second {
+            Noun::Entity(id) => Some(id),
+            _ => None,
+        };
+        if maybe_recipient.is_none() {
+            report(game, actor, String::from("There is no one here to give that to."));
+            return true;
+        }
+        let recipient = maybe_recipient.unwrap();
+
+        if recipient == actor || !is_adjacent(game, actor, recipient) {
+            report(game, actor, String::from("You can't reach them from here."));
+            return true;
+        }
+        if game.components.get_inventory(recipient).is_none() {
+            report(game, actor, String::from("They can't take that."));
+            return true;
+        }
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+
+        let maybe_item = item::take_from_pile(&mut maybe_inventory.unwrap().items, selection.index, selection.quantity);
+        if maybe_item.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        let given: Item = maybe_item.unwrap();
+
+        report(game, actor, format!("You give away {}.", item::get_display_name(&given)));
+
+        let recipient_inventory = game.components.get_inventory_mut(recipient).unwrap();
+        item::add_to_pile(&mut recipient_inventory.items, given, &game.data_tables.item_tag_map);
+
+        return false;
+    }
+}
 impl ActionRoutine for Go {
     fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
         
         let maybe_direction: Option<EntityID> = match noun {
             Noun::Entity(id) => Some(id),
+            Noun::Item(_) => None,
             Noun::Literal(_) => None,
             Noun::Menu(_) => None,
             Noun::Nothing => None,
@@ -505,7 +1437,12 @@ impl ActionRoutine for Go {
             return true;
         }
 
-        let maybe_position = game.components.get_position_mut(actor);
+        if scheduler::get_speed(game, actor) == 0 {
+            report(game, actor, String::from("You're too exhausted to move."));
+            return true;
+        }
+
+        let maybe_position = game.components.get_position(actor);
         if maybe_position.is_none() {
             //TODO(ches) report an error somehow
             return true;
@@ -548,18 +1485,68 @@ impl ActionRoutine for Go {
             //TODO(ches) report an error somehow
         }
 
-        let position: &mut Position = maybe_position.unwrap();
-        if position.x > 0 && offset_x < 0 || position.x < game.current_map.width - 1 && offset_x > 0 {
-            position.x = (position.x as i16 + offset_x) as u16;
+        let mut position = Position { x: maybe_position.unwrap().x, y: maybe_position.unwrap().y };
+        step(&mut position, offset_x, offset_y, &game.current_map);
+        if map::is_impassable(game.current_map.get_tile(position.x, position.y)) {
+            report(game, actor, String::from("The way is blocked."));
+            return true;
         }
-        if position.y > 0 && offset_y < 0 || position.y < game.current_map.height - 1 && offset_y > 0 {
-            position.y = (position.y as i16 + offset_y) as u16;
+        // Walking into a monster attacks it
+        if let Some(occupant) = game.components.get_creature_at(game.current_map.id, position.x, position.y).filter(|occupant| *occupant != actor) {
+            if game.components.get_monster(occupant).is_some() && game.components.get_monster(actor).is_none() {
+                return Attack::execute(game, actor, Noun::Entity(occupant), Noun::Nothing);
+            }
+            report(game, actor, String::from("There's something in the way."));
+            return true;
+        }
+        // Walking into a closed door opens it
+        if matches!(game.current_map.get_tile(position.x, position.y), Tile::DoorClosed) {
+            game.current_map.set_tile(position.x, position.y, Tile::DoorOpen);
+            report(game, actor, String::from("You open the door."));
+            return false;
+        }
+
+        // Ice is too slippery to stop on, so you slide on a tile further
+        if matches!(game.current_map.get_tile(position.x, position.y), Tile::Ice) {
+            let mut slide = Position { x: position.x, y: position.y };
+            step(&mut slide, offset_x, offset_y, &game.current_map);
+            let tile = game.current_map.get_tile(slide.x, slide.y);
+            if !map::is_impassable(tile) && !matches!(tile, Tile::DoorClosed) && !is_occupied(game, actor, slide.x, slide.y) {
+                position = slide;
+                report(game, actor, String::from("You slide across the ice."));
+            }
         }
 
+        let current = game.components.get_position_mut(actor).unwrap();
+        current.x = position.x;
+        current.y = position.y;
+        trap::enter_tile(game, actor, position.x, position.y);
         return false;
     }
 }
-stub_action!(Insert);
+impl ActionRoutine for Insert {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Put what?"));
+            return true;
+        }
+        let maybe_container = get_item_selection(&second);
+        if maybe_container.is_none() {
+            report(game, actor, String::from("Put it into what?"));
+            return true;
+        }
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+
+        let items = &mut maybe_inventory.unwrap().items;
+        let outcome = insert_item(items, maybe_selection.unwrap(), maybe_container.unwrap().index, &game.data_tables.item_tag_map);
+        return report_outcome(game, actor, outcome);
+    }
+}
 stub_action!(Inventory);
 stub_action!(Jump);
 stub_action!(JumpOver);
@@ -570,37 +1557,290 @@ stub_action!(Lock);
 stub_action!(Look);
 stub_action!(LookUnder);
 stub_action!(NotUnderstood);
-stub_action!(Open);
+impl ActionRoutine for Open {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Open what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+        let inventory = maybe_inventory.unwrap();
+
+        let maybe_stack = inventory.items.get(selection.index);
+        if maybe_stack.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+
+        let maybe_contents = item::get_bundle_contents(maybe_stack.unwrap().item_type);
+        if maybe_contents.is_none() {
+            report(game, actor, String::from("You can't open that."));
+            return true;
+        }
+        let (contents_type, contents_count) = maybe_contents.unwrap();
+
+        let bundles = item::take_from_pile(&mut inventory.items, selection.index, u32::max(1, selection.quantity)).unwrap();
+        let mut contents = Item::with_quantity(contents_type, contents_count * bundles.quantity);
+        contents.material = bundles.material;
+        let message = format!("You open {} and take out {}.", item::get_display_name(&bundles), item::get_display_name(&contents));
+
+        item::add_to_pile(&mut inventory.items, contents, &game.data_tables.item_tag_map);
+        report(game, actor, message);
+
+        return false;
+    }
+}
 stub_action!(Order);
 stub_action!(Pray);
-stub_action!(Pull);
+impl ActionRoutine for Pull {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        let maybe_lever = find_nearby_tile(game, actor, |tile| matches!(tile, Tile::Lever));
+        if maybe_lever.is_none() {
+            report(game, actor, String::from("There is nothing here to pull."));
+            return true;
+        }
+        let (x, y) = maybe_lever.unwrap();
+
+        report(game, actor, String::from("You pull the lever."));
+        if electricity::pull_lever(game, x, y) > 0 {
+            report(game, actor, String::from("You hear the grinding of a mechanism."));
+        } else {
+            report(game, actor, String::from("Nothing seems to happen."));
+        }
+        return false;
+    }
+}
 stub_action!(Push);
 stub_action!(PushDir);
-stub_action!(PutOn);
+impl ActionRoutine for PutOn {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Put what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_supporter = find_nearby_tile(game, actor, map::is_supporter);
+        if maybe_supporter.is_none() {
+            report(game, actor, String::from("There is nothing here to put it on."));
+            return true;
+        }
+        let (x, y) = maybe_supporter.unwrap();
+        let supporter_name = match game.current_map.get_tile(x, y) {
+            Tile::Altar => "altar",
+            Tile::Forge => "forge",
+            Tile::Statue => "statue",
+            Tile::Tombstone => "tombstone",
+            _ => "ground",
+        };
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+
+        let maybe_item = item::take_from_pile(&mut maybe_inventory.unwrap().items, selection.index, selection.quantity);
+        if maybe_item.is_none() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        let placed: Item = maybe_item.unwrap();
+
+        report(game, actor, format!("You put {} on the {}.", item::get_display_name(&placed), supporter_name));
+        item::add_to_pile(game.current_map.get_items_mut(x, y), placed, &game.data_tables.item_tag_map);
+
+        return false;
+    }
+}
 stub_action!(Receive);
 stub_action!(Remove);
-stub_action!(Search);
+impl ActionRoutine for Search {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        if trap::search(game, actor) == 0 {
+            report(game, actor, String::from("You search carefully but find nothing."));
+        }
+        return false;
+    }
+}
 stub_action!(Set);
 stub_action!(SetTo);
 stub_action!(Show);
 stub_action!(Sing);
-stub_action!(Sleep);
-stub_action!(Smell);
+impl ActionRoutine for Sleep {
+    fn execute(game: &mut Game, actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        // A long rest restores all hit points, and takes the edge off
+        // exhaustion as long as there was food and water to be had
+        let nourished = match game.components.get_nutrition(actor) {
+            Some(nutrition) => nutrition.food > 0 && nutrition.water > 0,
+            None => true,
+        };
+        let maybe_creature = game.components.get_creature_mut(actor);
+        if maybe_creature.is_none() {
+            return true;
+        }
+        let creature = maybe_creature.unwrap();
+        if nourished && creature.exhaustion > 0 {
+            creature.exhaustion -= 1;
+        }
+        let exhaustion = creature.exhaustion;
+
+        if let Some(health) = game.components.get_health_mut(actor) {
+            health.current = tabletop::exhausted_max_hp(health.maximum, exhaustion);
+        }
+
+        report(game, actor, String::from("You settle down and sleep for eight hours."));
+        return false;
+    }
+}
+impl ActionRoutine for Smell {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        return sense_liquid(game, actor, &noun, substance::POISON_SMELL_DC, substance::get_smell);
+    }
+}
 stub_action!(Swim);
 stub_action!(Swing);
-stub_action!(SwitchOff);
-stub_action!(SwitchOn);
-stub_action!(Take);
-stub_action!(Taste);
+impl ActionRoutine for SwitchOff {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Put out what?"));
+            return true;
+        }
+        let selection = maybe_selection.unwrap();
+
+        let maybe_inventory = game.components.get_inventory_mut(actor);
+        if maybe_inventory.is_none() {
+            return true;
+        }
+        let items = &mut maybe_inventory.unwrap().items;
+        if selection.index >= items.len() {
+            report(game, actor, String::from("You don't have that."));
+            return true;
+        }
+        let name = item::get_name(items[selection.index].item_type);
+        if !items[selection.index].state.contains(Tag::Burning) {
+            report(game, actor, format!("The {} isn't lit.", name));
+            return true;
+        }
+
+        // Whatever fuel is left is kept for next time
+        let mut unlit = item::take_from_pile(items, selection.index, 1).unwrap();
+        unlit.state.remove(Tag::Burning);
+        item::add_to_pile(items, unlit, &game.data_tables.item_tag_map);
+
+        report(game, actor, format!("You put out the {}.", name));
+        return false;
+    }
+}
+impl ActionRoutine for SwitchOn {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, second: Noun) -> bool {
+        let maybe_selection = get_item_selection(&noun);
+        if maybe_selection.is_none() {
+            report(game, actor, String::from("Light what?"));
+            return true;
+        }
+        if !has_flame(game, actor, &second) {
+            report(game, actor, String::from("You have nothing to light it with."));
+            return true;
+        }
+        return light_item(game, actor, maybe_selection.unwrap());
+    }
+}
+impl ActionRoutine for Take {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_position = game.components.get_position(actor);
+        if maybe_position.is_none() {
+            return true;
+        }
+        let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+
+        if game.components.get_inventory(actor).is_none() {
+            return true;
+        }
+
+        let floor: &mut Vec<Item> = game.current_map.get_items_mut(x, y);
+        let taken: Vec<Item> = match noun {
+            Noun::Item(selection) => item::take_from_pile(floor, selection.index, selection.quantity).into_iter().collect(),
+            Noun::Nothing => std::mem::take(floor),
+            _ => Vec::new(),
+        };
+
+        if taken.is_empty() {
+            report(game, actor, String::from("There is nothing here to take."));
+            return true;
+        }
+
+        let names: Vec<String> = taken.iter().map(item::get_display_name).collect();
+        report(game, actor, format!("You pick up {}.", names.join(", ")));
+
+        let tag_cache = &game.data_tables.tag_cache;
+        let burns = taken.iter().any(|taken_item| item::has_tag(taken_item, Tag::Hot, tag_cache));
+        let freezes = taken.iter().any(|taken_item| item::has_tag(taken_item, Tag::Frozen, tag_cache));
+
+        let inventory = game.components.get_inventory_mut(actor).unwrap();
+        for taken_item in taken {
+            item::add_to_pile(&mut inventory.items, taken_item, &game.data_tables.item_tag_map);
+        }
+
+        if burns {
+            report(game, actor, String::from("It burns your hands!"));
+            let damage = game.rng.roll(RngStream::Combat, temperature::HANDLING_DAMAGE);
+            apply_damage(game, actor, damage, DamageType::Fire);
+        }
+        if freezes {
+            report(game, actor, String::from("It's so cold it stings your hands!"));
+            let damage = game.rng.roll(RngStream::Combat, temperature::HANDLING_DAMAGE);
+            apply_damage(game, actor, damage, DamageType::Cold);
+        }
+
+        return false;
+    }
+}
+impl ActionRoutine for Taste {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        return sense_liquid(game, actor, &noun, substance::POISON_TASTE_DC, substance::get_taste);
+    }
+}
 stub_action!(Tell);
 stub_action!(Think);
 stub_action!(ThrowAt);
 stub_action!(ThrownAt);
 stub_action!(Tie);
 stub_action!(Touch);
+impl ActionRoutine for Travel {
+    fn execute(game: &mut Game, actor: EntityID, noun: Noun, _second: Noun) -> bool {
+        let maybe_index = match noun {
+            Noun::Number(index) => usize::try_from(index).ok().filter(|index| *index < game.current_map.tiles.len()),
+            _ => None,
+        };
+        if actor != game.special_entities.player || maybe_index.is_none() {
+            return true;
+        }
+        let (x, y) = game.current_map.index_to_coordinates(maybe_index.unwrap());
+        if let Err(message) = travel::start_travel(game, x, y) {
+            report(game, actor, message);
+            return true;
+        }
+        return false;
+    }
+}
 stub_action!(Turn);
 stub_action!(Unlock);
-stub_action!(Wait);
+
+impl ActionRoutine for Wait {
+    fn execute(_game: &mut Game, _actor: EntityID, _noun: Noun, _second: Noun) -> bool {
+        // Doing nothing still lets the turn go by
+        return false;
+    }
+}
+
 stub_action!(Wake);
 stub_action!(WakeOther);
 stub_action!(Wave);
@@ -0,0 +1,421 @@
+use crate::{action::{self, Action, ActionRequest, Attack, Go, Noun, Wait}, component::Position, dijkstra::DijkstraMap, entity::EntityID, fov, game::Game, lighting::{self, LightLevel}, map, monster, new_action, pathfinding, rng::RngStream, tabletop::Skill, trap};
+
+// How close something has to be for a creature to hear it, in tiles
+pub const HEARING_RANGE: u32 = 6;
+
+// Anything that moves on its own does one of these, trying each in order of
+// priority until one of them has something to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behavior {
+    // Runs from whatever it's fighting once it's badly hurt
+    Flee,
+    // Hangs back this many tiles from whatever it's fighting
+    KeepRange(u16),
+    // Chases down anything it notices
+    Hunt,
+    // Stays home, and only chases what comes within this many tiles of it
+    Guard(u16),
+    // Potters about, never straying more than this many tiles from home
+    Loiter(u16),
+    // Roams around at random
+    Wander,
+}
+
+fn get_coordinates(game: &Game, entity: EntityID) -> Option<(u16, u16)> {
+    return game.components.get_position(entity).map(|position| (position.x, position.y));
+}
+
+pub fn can_see(game: &Game, viewer: EntityID, x: u16, y: u16) -> bool {
+    let maybe_position = get_coordinates(game, viewer);
+    if maybe_position.is_none() || !game.current_map.is_in_bounds(x, y) {
+        return false;
+    }
+    let (viewer_x, viewer_y) = maybe_position.unwrap();
+    if !fov::is_within_radius(viewer_x, viewer_y, x, y, lighting::SIGHT_RADIUS) || !fov::has_line_of_sight(&game.current_map, viewer_x, viewer_y, x, y) {
+        return false;
+    }
+    let light = lighting::get_light_level(game, x, y);
+    return lighting::get_perceived_light(game, viewer, x, y, light) > LightLevel::Dark;
+}
+
+// Anything close enough can be heard unless it's sneaking well enough to
+// beat the listener's passive Perception
+pub fn can_hear(game: &Game, listener: EntityID, target: EntityID) -> bool {
+    let listener_position = get_coordinates(game, listener);
+    let target_position = get_coordinates(game, target);
+    if listener_position.is_none() || target_position.is_none() {
+        return false;
+    }
+    let (listener_x, listener_y) = listener_position.unwrap();
+    let (target_x, target_y) = target_position.unwrap();
+    if pathfinding::get_distance(listener_x, listener_y, target_x, target_y) > HEARING_RANGE {
+        return false;
+    }
+    return action::get_passive_skill(game, listener, Skill::Perception) >= action::get_passive_skill(game, target, Skill::Stealth);
+}
+
+pub fn can_perceive(game: &Game, actor: EntityID, target: EntityID) -> bool {
+    let maybe_position = get_coordinates(game, target);
+    if maybe_position.is_none() {
+        return false;
+    }
+    let (x, y) = maybe_position.unwrap();
+    return can_see(game, actor, x, y) || can_hear(game, actor, target);
+}
+
+fn make_request(actor: EntityID, action: Action, noun: Noun) -> ActionRequest {
+    return ActionRequest {
+        actor,
+        action,
+        noun,
+        second: Noun::Nothing,
+    };
+}
+
+fn go(game: &Game, actor: EntityID, from: (u16, u16), to: (u16, u16)) -> ActionRequest {
+    let direction = action::get_direction(game, to.0 as i16 - from.0 as i16, to.1 as i16 - from.1 as i16);
+    return make_request(actor, new_action!(Go), Noun::Entity(direction));
+}
+
+fn is_occupied(game: &Game, actor: EntityID, x: u16, y: u16) -> bool {
+    let occupant = game.components.get_creature_at(game.current_map.id, x, y);
+    return occupant.is_some() && occupant.unwrap() != actor;
+}
+
+// Whether a creature knows there's a trap on a tile, either because it's
+// been found or because the creature lives here and knows where they're laid
+fn knows_of_trap(game: &Game, actor: EntityID, x: u16, y: u16) -> bool {
+    if game.current_map.get_trap(x, y).is_none() {
+        return false;
+    }
+    let native = game.components.get_ai(actor).is_some_and(|ai| ai.native_map == Some(game.current_map.id));
+    return native || trap::is_known_trap(&game.current_map, x, y);
+}
+
+// Somebody's in the way, or there's a trap there that the creature knows about
+fn is_blocked(game: &Game, actor: EntityID, x: u16, y: u16) -> bool {
+    return is_occupied(game, actor, x, y) || knows_of_trap(game, actor, x, y);
+}
+
+// Takes the first step on the way to a tile, going around anyone in the way
+// and any traps it knows are there
+pub fn step_towards(game: &Game, actor: EntityID, x: u16, y: u16) -> Option<ActionRequest> {
+    let maybe_position = get_coordinates(game, actor);
+    if maybe_position.is_none() || maybe_position.unwrap() == (x, y) {
+        return None;
+    }
+    let from = maybe_position.unwrap();
+    // The goal itself is fine to head for, whatever's there
+    let path = pathfinding::find_path(&game.current_map, from, (x, y), |next_x, next_y| (next_x, next_y) != (x, y) && is_blocked(game, actor, next_x, next_y))?;
+    let next = *path.first()?;
+    if is_occupied(game, actor, next.0, next.1) {
+        return None;
+    }
+    return Some(go(game, actor, from, next));
+}
+
+// Takes whichever step gets furthest from a tile, if any step does
+pub fn step_away(game: &Game, actor: EntityID, x: u16, y: u16) -> Option<ActionRequest> {
+    let from = get_coordinates(game, actor)?;
+    let map = &game.current_map;
+    let mut best: Option<(u16, u16)> = None;
+    let mut best_distance = pathfinding::get_distance(from.0, from.1, x, y);
+    for offset in pathfinding::NEIGHBOR_OFFSETS {
+        let maybe_neighbor = pathfinding::get_neighbor(map, from.0, from.1, offset);
+        if maybe_neighbor.is_none() {
+            continue;
+        }
+        let (next_x, next_y) = maybe_neighbor.unwrap();
+        let tile = map.get_tile(next_x, next_y);
+        if map::is_impassable(tile) || map::is_hazardous(tile) || map.is_burning(next_x, next_y) || is_occupied(game, actor, next_x, next_y) {
+            continue;
+        }
+        let distance = pathfinding::get_distance(next_x, next_y, x, y);
+        if distance > best_distance {
+            best = Some((next_x, next_y));
+            best_distance = distance;
+        }
+    }
+    return best.map(|next| go(game, actor, from, next));
+}
+
+// Follows a distance map downhill when the quarry is right where the map
+// leads to or from, since that takes everything on the map into account
+fn step_on_player_map(game: &Game, actor: EntityID, quarry: EntityID, distances: &DijkstraMap) -> Option<Option<ActionRequest>> {
+    let from = get_coordinates(game, actor)?;
+    let last_known = game.components.get_ai(actor)?.last_known?;
+    if quarry != game.special_entities.player || get_coordinates(game, quarry) != Some(last_known) || !distances.is_current(&game.current_map) {
+        return None;
+    }
+    let maybe_step = distances.get_downhill_step(&game.current_map, from.0, from.1, |x, y| is_blocked(game, actor, x, y));
+    return Some(maybe_step.map(|next| go(game, actor, from, next)));
+}
+
+// Whether the quarry is where it was last noticed and close enough to hit
+// with something, in plain sight for anything thrown or shot
+fn can_attack(game: &Game, actor: EntityID, quarry: EntityID) -> bool {
+    let maybe_last_known = game.components.get_ai(actor).and_then(|ai| ai.last_known);
+    let from = get_coordinates(game, actor);
+    if maybe_last_known.is_none() || from.is_none() || get_coordinates(game, quarry) != maybe_last_known {
+        return false;
+    }
+    let (from_x, from_y) = from.unwrap();
+    let (x, y) = maybe_last_known.unwrap();
+    let distance = pathfinding::get_distance(from_x, from_y, x, y);
+    if distance > monster::get_attack_range(game, actor) as u32 {
+        return false;
+    }
+    return distance <= 1 || fov::has_line_of_sight(&game.current_map, from_x, from_y, x, y);
+}
+
+// Attacks the quarry if it's in reach, or heads for wherever it was last
+// noticed, forgetting about it on arriving and finding nothing there
+fn hunt(game: &mut Game, actor: EntityID) -> Option<ActionRequest> {
+    let (x, y) = game.components.get_ai(actor)?.last_known?;
+    let quarry = game.special_entities.player;
+    let from = get_coordinates(game, actor)?;
+    if can_attack(game, actor, quarry) {
+        return Some(make_request(actor, new_action!(Attack), Noun::Entity(quarry)));
+    }
+
+    let result = step_on_player_map(game, actor, quarry, &game.distance_maps.to_player)
+        .unwrap_or_else(|| step_towards(game, actor, x, y));
+    if result.is_none() && from == (x, y) {
+        game.components.get_ai_mut(actor).unwrap().last_known = None;
+    }
+    return result;
+}
+
+fn flee(game: &Game, actor: EntityID) -> Option<ActionRequest> {
+    let health = game.components.get_health(actor)?;
+    if health.current * 4 > health.maximum {
+        return None;
+    }
+    let (x, y) = game.components.get_ai(actor)?.last_known?;
+    let quarry = game.special_entities.player;
+    return step_on_player_map(game, actor, quarry, &game.distance_maps.from_player)
+        .unwrap_or_else(|| step_away(game, actor, x, y));
+}
+
+fn keep_range(game: &Game, actor: EntityID, range: u16) -> Option<ActionRequest> {
+    let (x, y) = game.components.get_ai(actor)?.last_known?;
+    let from = get_coordinates(game, actor)?;
+    let distance = pathfinding::get_distance(from.0, from.1, x, y);
+    if distance < range as u32 {
+        let quarry = game.special_entities.player;
+        return step_on_player_map(game, actor, quarry, &game.distance_maps.from_player)
+            .unwrap_or_else(|| step_away(game, actor, x, y));
+    }
+    if distance > range as u32 {
+        return step_towards(game, actor, x, y);
+    }
+    let quarry = game.special_entities.player;
+    if can_attack(game, actor, quarry) {
+        return Some(make_request(actor, new_action!(Attack), Noun::Entity(quarry)));
+    }
+    return Some(make_request(actor, new_action!(Wait), Noun::Nothing));
+}
+
+fn guard(game: &mut Game, actor: EntityID, radius: u16) -> Option<ActionRequest> {
+    let ai = game.components.get_ai(actor)?;
+    let home = (ai.home.x, ai.home.y);
+    let intruder = ai.last_known.filter(|(x, y)| pathfinding::get_distance(*x, *y, home.0, home.1) <= radius as u32);
+    if intruder.is_some() {
+        let result = hunt(game, actor);
+        if result.is_some() {
+            return result;
+        }
+    }
+    return step_towards(game, actor, home.0, home.1)
+        .or(Some(make_request(actor, new_action!(Wait), Noun::Nothing)));
+}
+
+// Heads off in a random direction, trying the others in turn if that way is
+// blocked
+fn wander(game: &mut Game, actor: EntityID) -> Option<ActionRequest> {
+    let from = get_coordinates(game, actor)?;
+    let start = game.rng.range(RngStream::Ai, 0, pathfinding::NEIGHBOR_OFFSETS.len() as i32 - 1) as usize;
+    for turn in 0..pathfinding::NEIGHBOR_OFFSETS.len() {
+        let offset = pathfinding::NEIGHBOR_OFFSETS[(start + turn) % pathfinding::NEIGHBOR_OFFSETS.len()];
+        let maybe_neighbor = pathfinding::get_neighbor(&game.current_map, from.0, from.1, offset);
+        if maybe_neighbor.is_none() {
+            continue;
+        }
+        let (x, y) = maybe_neighbor.unwrap();
+        let tile = game.current_map.get_tile(x, y);
+        if !map::is_impassable(tile) && !map::is_hazardous(tile) && !is_occupied(game, actor, x, y) {
+            return Some(go(game, actor, from, (x, y)));
+        }
+    }
+    return None;
+}
+
+// Mostly stands about, now and then taking a step, and heads home after
+// straying too far
+fn loiter(game: &mut Game, actor: EntityID, radius: u16) -> Option<ActionRequest> {
+    let home = game.components.get_ai(actor).map(|ai| (ai.home.x, ai.home.y))?;
+    let from = get_coordinates(game, actor)?;
+    if pathfinding::get_distance(from.0, from.1, home.0, home.1) > radius as u32 {
+        let result = step_towards(game, actor, home.0, home.1);
+        if result.is_some() {
+            return result;
+        }
+    }
+    if game.rng.chance(RngStream::Ai, 25) {
+        let result = wander(game, actor);
+        if result.is_some() {
+            return result;
+        }
+    }
+    return Some(make_request(actor, new_action!(Wait), Noun::Nothing));
+}
+
+fn run_behavior(game: &mut Game, actor: EntityID, behavior: Behavior) -> Option<ActionRequest> {
+    return match behavior {
+        Behavior::Flee => flee(game, actor),
+        Behavior::KeepRange(range) => keep_range(game, actor, range),
+        Behavior::Hunt => hunt(game, actor),
+        Behavior::Guard(radius) => guard(game, actor, radius),
+        Behavior::Loiter(radius) => loiter(game, actor, radius),
+        Behavior::Wander => wander(game, actor),
+    };
+}
+
+// Works out what a creature does with its turn, remembering where it last
+// noticed the player along the way
+pub fn choose_action(game: &mut Game, actor: EntityID) -> ActionRequest {
+    let player = game.special_entities.player;
+    if can_perceive(game, actor, player) {
+        let position = game.components.get_position(player).unwrap();
+        let last_known = Some((position.x, position.y));
+        if let Some(ai) = game.components.get_ai_mut(actor) {
+            ai.last_known = last_known;
+        }
+    }
+
+    let behaviors = match game.components.get_ai(actor) {
+        Some(ai) => ai.behaviors.clone(),
+        None => Vec::new(),
+    };
+    for behavior in behaviors {
+        if let Some(result) = run_behavior(game, actor, behavior) {
+            return result;
+        }
+    }
+    return make_request(actor, new_action!(Wait), Noun::Nothing);
+}
+
+// Somewhere for a guard to stand watch over, on the level the creature
+// belongs to
+pub fn set_home(game: &mut Game, actor: EntityID) {
+    let maybe_position = get_coordinates(game, actor);
+    let maybe_map = game.components.get_map_index(actor).map(|index| index.map);
+    if let (Some((x, y)), Some(ai)) = (maybe_position, game.components.get_ai_mut(actor)) {
+        ai.home = Position { x, y };
+        ai.native_map = maybe_map;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{component::EntityType, map::{GameMap, Tile}, scheduler};
+
+    fn make_test_game() -> (Game, EntityID) {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 14, 3));
+        for y in 0..3 {
+            for x in 0..14 {
+                game.current_map.set_tile(x, y, Tile::Floor);
+            }
+        }
+        let player = game.special_entities.player;
+        game.components.get_position_mut(player).unwrap().x = 0;
+        game.components.get_health_mut(player).unwrap().current = 10;
+
+        let monster = game.components.create_entity(EntityType::Monster);
+        let position = game.components.get_position_mut(monster).unwrap();
+        position.x = 6;
+        position.y = 1;
+        let health = game.components.get_health_mut(monster).unwrap();
+        health.current = 10;
+        health.maximum = 10;
+        game.components.get_ai_mut(monster).unwrap().behaviors = vec![Behavior::Flee, Behavior::Hunt, Behavior::Wander];
+        return (game, monster);
+    }
+
+    #[test]
+    fn test_hunt_and_flee() {
+        let (mut game, monster) = make_test_game();
+        let player = game.special_entities.player;
+        for _ in 0..5 {
+            let request = choose_action(&mut game, monster);
+            action::execute_action(&mut game, request);
+        }
+        assert_eq!(game.components.get_position(monster).unwrap().x, 1);
+        assert!(matches!(choose_action(&mut game, monster).action, Action::Attack(_)));
+
+        // Badly hurt, it runs
+        game.components.get_health_mut(monster).unwrap().current = 2;
+        let request = choose_action(&mut game, monster);
+        action::execute_action(&mut game, request);
+        assert_eq!(game.components.get_position(monster).unwrap().x, 2);
+        assert!(can_perceive(&game, monster, player));
+    }
+
+    #[test]
+    fn test_monsters_act_on_their_turn() {
+        let (mut game, monster) = make_test_game();
+        let player = game.special_entities.player;
+        game.components.get_turn_mut(monster).unwrap().next = game.clock.time;
+        action::execute_action(&mut game, ActionRequest {
+            actor: player,
+            action: new_action!(Wait),
+            noun: Noun::Nothing,
+            second: Noun::Nothing,
+        });
+        assert_eq!(game.components.get_position(monster).unwrap().x, 5);
+        assert_eq!(scheduler::get_next_turn(&game, monster), game.clock.time);
+
+        // Walls keep it from seeing or hearing the player, so it wanders off
+        // once it gets to where the player was last seen
+        game.components.get_position_mut(player).unwrap().x = 13;
+        game.components.get_position_mut(monster).unwrap().y = 1;
+        game.components.get_ai_mut(monster).unwrap().last_known = Some((5, 1));
+        game.current_map.set_tile(7, 0, Tile::Wall);
+        game.current_map.set_tile(7, 1, Tile::Wall);
+        game.current_map.set_tile(7, 2, Tile::Wall);
+        assert!(!can_perceive(&game, monster, player));
+        choose_action(&mut game, monster);
+        assert!(game.components.get_ai(monster).unwrap().last_known.is_none());
+    }
+
+    #[test]
+    fn test_monsters_go_around_known_traps() {
+        let (mut game, monster) = make_test_game();
+        game.current_map.set_trap(5, 1, Some(trap::TrapKind::Pit));
+        game.current_map.set_tile(5, 1, Tile::TrapKnown);
+        game.components.get_ai_mut(monster).unwrap().last_known = Some((0, 1));
+        let request = step_towards(&game, monster, 0, 1).unwrap();
+        action::execute_action(&mut game, request);
+        let position = game.components.get_position(monster).unwrap();
+        assert_eq!(position.x, 5);
+        assert_ne!(position.y, 1);
+    }
+
+    #[test]
+    fn test_monsters_know_their_own_traps() {
+        let (mut game, monster) = make_test_game();
+        game.current_map.set_tile(5, 0, Tile::Wall);
+        game.current_map.set_tile(5, 2, Tile::Wall);
+        game.current_map.set_trap(5, 1, Some(trap::TrapKind::Pit));
+
+        // A stranger walks straight into a hidden trap in the only way
+        // through, but one that lives here won't go that way
+        let request = step_towards(&game, monster, 0, 1).unwrap();
+        assert!(matches!(request.noun, Noun::Entity(direction) if direction == game.special_entities.west));
+        set_home(&mut game, monster);
+        assert!(knows_of_trap(&game, monster, 5, 1));
+        assert!(step_towards(&game, monster, 0, 1).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::{entity::EntityID, map::MapID, tabletop::{Alignment, Class, Race, Size, Stats}};
+use crate::{ai::Behavior, entity::EntityID, monster::MonsterKind, item::Item, map::MapID, npc::NpcRole, tabletop::{self, Alignment, Class, Race, Size, Stats}, tag::TagSet, temperature::{Temperature, AMBIENT_TEMPERATURE}, time::Seconds};
 
 pub struct Alive {
     pub alive: bool
@@ -25,8 +25,91 @@ macro_rules! impl_get_alive {
     };
 }
 
+// How a creature decides what to do, and what it remembers while doing it
+pub struct Ai {
+    // Tried in order until one of them has something to do
+    pub behaviors: Vec<Behavior>,
+    // Where the creature guards, or started out
+    pub home: Position,
+    // Where the creature last saw or heard the player
+    pub last_known: Option<(u16, u16)>,
+    // The level the creature lives on, whose traps it knows about
+    pub native_map: Option<MapID>,
+}
+
+impl Ai {
+    pub fn new() -> Self {
+        Self {
+            behaviors: vec![Behavior::Hunt, Behavior::Wander],
+            home: Position::new(),
+            last_known: None,
+            native_map: None,
+        }
+    }
+}
+macro_rules! impl_get_ai {
+    () => {
+        fn get_ai(&self, entity: EntityID) -> Option<&Ai> {
+            return self.ai.get(to_index(entity));
+        }
+
+        fn get_ai_mut(&mut self, entity: EntityID) -> Option<&mut Ai> {
+            return self.ai.get_mut(to_index(entity));
+        }
+    };
+}
+
+// What kind of monster an entity is, which everything else about it comes from
+pub struct Monster {
+    pub kind: MonsterKind,
+}
+
+impl Monster {
+    pub fn new() -> Self {
+        Self {
+            kind: MonsterKind::GiantRat
+        }
+    }
+}
+macro_rules! impl_get_monster {
+    () => {
+        fn get_monster(&self, entity: EntityID) -> Option<&Monster> {
+            return self.monster.get(to_index(entity));
+        }
+
+        fn get_monster_mut(&mut self, entity: EntityID) -> Option<&mut Monster> {
+            return self.monster.get_mut(to_index(entity));
+        }
+    };
+}
+
+// What a character who isn't the player does for a living, if anything
+pub struct Npc {
+    pub role: Option<NpcRole>,
+}
+
+impl Npc {
+    pub fn new() -> Self {
+        Self {
+            role: None
+        }
+    }
+}
+macro_rules! impl_get_npc {
+    () => {
+        fn get_npc(&self, entity: EntityID) -> Option<&Npc> {
+            return self.npc.get(to_index(entity));
+        }
+
+        fn get_npc_mut(&mut self, entity: EntityID) -> Option<&mut Npc> {
+            return self.npc.get_mut(to_index(entity));
+        }
+    };
+}
+
 pub struct Creature {
     pub alignment: Alignment,
+    pub exhaustion: u8,
     pub size: Size,
     pub race: Race,
     pub stats: Stats,
@@ -36,6 +119,7 @@ impl Creature {
     pub fn new() -> Self {
         Self {
             alignment: Alignment::Neutral,
+            exhaustion: 0,
             size: Size::Medium,
             race: Race::Human,
             stats: Stats {
@@ -84,6 +168,85 @@ macro_rules! impl_get_character {
     };
 }
 
+pub struct Health {
+    pub current: i32,
+    pub maximum: i32,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self {
+            current: 1,
+            maximum: 1
+        }
+    }
+}
+macro_rules! impl_get_health {
+    () => {
+        fn get_health(&self, entity: EntityID) -> Option<&Health> {
+            return self.health.get(to_index(entity));
+        }
+
+        fn get_health_mut(&mut self, entity: EntityID) -> Option<&mut Health> {
+            return self.health.get_mut(to_index(entity));
+        }
+    };
+}
+
+pub struct Inventory {
+    pub items: Vec<Item>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new()
+        }
+    }
+}
+macro_rules! impl_get_inventory {
+    () => {
+        fn get_inventory(&self, entity: EntityID) -> Option<&Inventory> {
+            return self.inventory.get(to_index(entity));
+        }
+
+        fn get_inventory_mut(&mut self, entity: EntityID) -> Option<&mut Inventory> {
+            return self.inventory.get_mut(to_index(entity));
+        }
+    };
+}
+
+pub struct Nutrition {
+    // How long until food and water run out
+    pub food: Seconds,
+    pub water: Seconds,
+    // How long the character has gone with nothing left
+    pub starving: Seconds,
+    pub parched: Seconds,
+}
+
+impl Nutrition {
+    pub fn new() -> Self {
+        Self {
+            food: tabletop::MAX_FOOD,
+            water: tabletop::MAX_WATER,
+            starving: 0,
+            parched: 0
+        }
+    }
+}
+macro_rules! impl_get_nutrition {
+    () => {
+        fn get_nutrition(&self, entity: EntityID) -> Option<&Nutrition> {
+            return self.nutrition.get(to_index(entity));
+        }
+
+        fn get_nutrition_mut(&mut self, entity: EntityID) -> Option<&mut Nutrition> {
+            return self.nutrition.get_mut(to_index(entity));
+        }
+    };
+}
+
 pub struct MapIndex {
     pub map: MapID,
 }
@@ -132,6 +295,57 @@ macro_rules! impl_get_position {
     };
 }
 
+// Tags an entity has for now, like Burning or Wet
+pub struct Status {
+    pub tags: TagSet,
+    // How warm the entity has become from its surroundings
+    pub temperature: Temperature,
+}
+
+impl Status {
+    pub fn new() -> Self {
+        Self {
+            tags: TagSet::new(),
+            temperature: AMBIENT_TEMPERATURE
+        }
+    }
+}
+macro_rules! impl_get_status {
+    () => {
+        fn get_status(&self, entity: EntityID) -> Option<&Status> {
+            return self.status.get(to_index(entity));
+        }
+
+        fn get_status_mut(&mut self, entity: EntityID) -> Option<&mut Status> {
+            return self.status.get_mut(to_index(entity));
+        }
+    };
+}
+
+// When a creature next gets to act, by the game clock
+pub struct Turn {
+    pub next: Seconds,
+}
+
+impl Turn {
+    pub fn new() -> Self {
+        Self {
+            next: 0
+        }
+    }
+}
+macro_rules! impl_get_turn {
+    () => {
+        fn get_turn(&self, entity: EntityID) -> Option<&Turn> {
+            return self.turn.get(to_index(entity));
+        }
+
+        fn get_turn_mut(&mut self, entity: EntityID) -> Option<&mut Turn> {
+            return self.turn.get_mut(to_index(entity));
+        }
+    };
+}
+
 #[derive(PartialEq)]
 pub enum EntityType {
     Character,
@@ -174,22 +388,36 @@ fn to_index(entity: EntityID) -> usize {
 
 pub struct CharacterComponents {
     next_id: AtomicUsize,
+    pub ai: Vec<Ai>,
     pub alive: Vec<Alive>,
     pub character: Vec<Character>,
     pub creature: Vec<Creature>,
+    pub health: Vec<Health>,
+    pub inventory: Vec<Inventory>,
     pub map_index: Vec<MapIndex>,
+    pub npc: Vec<Npc>,
+    pub nutrition: Vec<Nutrition>,
     pub position: Vec<Position>,
+    pub status: Vec<Status>,
+    pub turn: Vec<Turn>,
 }
 
 impl CharacterComponents {
     pub fn new() -> Self {
         Self {
             next_id: AtomicUsize::new(0),
+            ai: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
             alive: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
             character: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
             creature: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            health: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            inventory: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
             map_index: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            npc: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            nutrition: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
             position: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            status: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
+            turn: Vec::with_capacity(DEFAULT_CHARACTER_COMPONENT_COUNT),
         }
     }
 
@@ -199,11 +427,18 @@ impl CharacterComponents {
         }
         let id: EntityID = self.next_id.fetch_add(1, Ordering::Relaxed);
         
+        self.ai.push(Ai::new());
         self.alive.push(Alive::new());
         self.character.push(Character::new());
         self.creature.push(Creature::new());
+        self.health.push(Health::new());
+        self.inventory.push(Inventory::new());
         self.map_index.push(MapIndex::new());
+        self.npc.push(Npc::new());
+        self.nutrition.push(Nutrition::new());
         self.position.push(Position::new());
+        self.status.push(Status::new());
+        self.turn.push(Turn::new());
 
         return id | TYPE_BITMASK_CHARACTER;
     }
@@ -212,11 +447,18 @@ impl CharacterComponents {
         return self.next_id.load(Ordering::Relaxed);
     }
 
+    impl_get_ai!();
     impl_get_alive!();
     impl_get_creature!();
     impl_get_character!();
+    impl_get_health!();
+    impl_get_inventory!();
     impl_get_map_index!();
+    impl_get_npc!();
+    impl_get_nutrition!();
     impl_get_position!();
+    impl_get_status!();
+    impl_get_turn!();
 }
 
 pub struct MetaComponents {
@@ -252,20 +494,32 @@ impl MetaComponents {
 
 pub struct MonsterComponents {
     next_id: AtomicUsize,
+    pub ai: Vec<Ai>,
     pub alive: Vec<Alive>,
     pub creature: Vec<Creature>,
+    pub health: Vec<Health>,
+    pub inventory: Vec<Inventory>,
     pub map_index: Vec<MapIndex>,
+    pub monster: Vec<Monster>,
     pub position: Vec<Position>,
+    pub status: Vec<Status>,
+    pub turn: Vec<Turn>,
 }
 
 impl MonsterComponents {
     pub fn new() -> Self {
         Self {
             next_id: AtomicUsize::new(0),
+            ai: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
             alive: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
             creature: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
+            health: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
+            inventory: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
             map_index: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
+            monster: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
             position: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
+            status: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
+            turn: Vec::with_capacity(DEFAULT_MONSTER_COMPONENT_COUNT),
         }
     }
 
@@ -275,10 +529,16 @@ impl MonsterComponents {
         }
         let id: EntityID = self.next_id.fetch_add(1, Ordering::Relaxed);
 
+        self.ai.push(Ai::new());
         self.alive.push(Alive::new());
         self.creature.push(Creature::new());
+        self.health.push(Health::new());
+        self.inventory.push(Inventory::new());
         self.map_index.push(MapIndex::new());
+        self.monster.push(Monster::new());
         self.position.push(Position::new());
+        self.status.push(Status::new());
+        self.turn.push(Turn::new());
 
         return id | TYPE_BITMASK_MONSTER;
     }
@@ -287,10 +547,16 @@ impl MonsterComponents {
         return self.next_id.load(Ordering::Relaxed);
     }
 
+    impl_get_ai!();
     impl_get_alive!();
     impl_get_creature!();
+    impl_get_health!();
+    impl_get_inventory!();
     impl_get_map_index!();
+    impl_get_monster!();
     impl_get_position!();
+    impl_get_status!();
+    impl_get_turn!();
 }
 
 pub struct ObjectComponents {
@@ -299,6 +565,7 @@ pub struct ObjectComponents {
     pub map_index: Vec<MapIndex>,
     pub parent: Vec<EntityID>,
     pub position: Vec<Position>,
+    pub status: Vec<Status>,
 }
 
 impl ObjectComponents {
@@ -309,6 +576,7 @@ impl ObjectComponents {
             map_index: Vec::with_capacity(DEFAULT_OBJECT_COMPONENT_COUNT),
             parent: Vec::with_capacity(DEFAULT_OBJECT_COMPONENT_COUNT),
             position: Vec::with_capacity(DEFAULT_OBJECT_COMPONENT_COUNT),
+            status: Vec::with_capacity(DEFAULT_OBJECT_COMPONENT_COUNT),
         }
     }
 
@@ -321,6 +589,7 @@ impl ObjectComponents {
         self.alive.push(Alive::new());
         self.map_index.push(MapIndex::new());
         self.position.push(Position::new());
+        self.status.push(Status::new());
 
         return id | TYPE_BITMASK_OBJECT;
     }
@@ -332,6 +601,7 @@ impl ObjectComponents {
     impl_get_alive!();
     impl_get_map_index!();
     impl_get_position!();
+    impl_get_status!();
 }
 
 pub struct Components {
@@ -376,7 +646,23 @@ impl Components {
         return &self.object_components;
     }
     
-    fn get_alive(&self, entity: EntityID) -> Option<&Alive> {
+    pub fn get_ai(&self, entity: EntityID) -> Option<&Ai> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_ai(entity),
+            EntityType::Monster => self.monster_components.get_ai(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_ai_mut(&mut self, entity: EntityID) -> Option<&mut Ai> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_ai_mut(entity),
+            EntityType::Monster => self.monster_components.get_ai_mut(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_alive(&self, entity: EntityID) -> Option<&Alive> {
         return match get_entity_type(entity) {
             EntityType::Character => self.character_components.get_alive(entity),
             EntityType::Monster => self.monster_components.get_alive(entity),
@@ -385,7 +671,7 @@ impl Components {
         };
     }
 
-    fn get_alive_mut(&mut self, entity: EntityID) -> Option<&mut Alive> {
+    pub fn get_alive_mut(&mut self, entity: EntityID) -> Option<&mut Alive> {
         return match get_entity_type(entity) {
             EntityType::Character => self.character_components.get_alive_mut(entity),
             EntityType::Monster => self.monster_components.get_alive_mut(entity),
@@ -410,6 +696,46 @@ impl Components {
         };
     }
 
+    pub fn get_creature_at(&self, map: MapID, x: u16, y: u16) -> Option<EntityID> {
+        let characters = &self.character_components;
+        for i in 0..characters.get_size() {
+            let position = &characters.position[i];
+            if characters.alive[i].alive && characters.map_index[i].map == map && position.x == x && position.y == y {
+                return Some(i | TYPE_BITMASK_CHARACTER);
+            }
+        }
+
+        let monsters = &self.monster_components;
+        for i in 0..monsters.get_size() {
+            let position = &monsters.position[i];
+            if monsters.alive[i].alive && monsters.map_index[i].map == map && position.x == x && position.y == y {
+                return Some(i | TYPE_BITMASK_MONSTER);
+            }
+        }
+
+        return None;
+    }
+
+    pub fn get_living_creatures(&self) -> Vec<EntityID> {
+        let mut result: Vec<EntityID> = Vec::new();
+
+        let characters = &self.character_components;
+        for i in 0..characters.get_size() {
+            if characters.alive[i].alive {
+                result.push(i | TYPE_BITMASK_CHARACTER);
+            }
+        }
+
+        let monsters = &self.monster_components;
+        for i in 0..monsters.get_size() {
+            if monsters.alive[i].alive {
+                result.push(i | TYPE_BITMASK_MONSTER);
+            }
+        }
+
+        return result;
+    }
+
     pub fn get_character(&self, entity: EntityID) -> Option<&Character> {
         return match get_entity_type(entity) {
             EntityType::Character => self.character_components.get_character(entity),
@@ -424,6 +750,38 @@ impl Components {
         };
     }
 
+    pub fn get_health(&self, entity: EntityID) -> Option<&Health> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_health(entity),
+            EntityType::Monster => self.monster_components.get_health(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_health_mut(&mut self, entity: EntityID) -> Option<&mut Health> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_health_mut(entity),
+            EntityType::Monster => self.monster_components.get_health_mut(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_inventory(&self, entity: EntityID) -> Option<&Inventory> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_inventory(entity),
+            EntityType::Monster => self.monster_components.get_inventory(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_inventory_mut(&mut self, entity: EntityID) -> Option<&mut Inventory> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_inventory_mut(entity),
+            EntityType::Monster => self.monster_components.get_inventory_mut(entity),
+            _ => None,
+        };
+    }
+
     pub fn get_map_index(&self, entity: EntityID) -> Option<&MapIndex> {
         return match get_entity_type(entity) {
             EntityType::Character => self.character_components.get_map_index(entity),
@@ -442,6 +800,48 @@ impl Components {
         };
     }
 
+    pub fn get_monster(&self, entity: EntityID) -> Option<&Monster> {
+        return match get_entity_type(entity) {
+            EntityType::Monster => self.monster_components.get_monster(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_monster_mut(&mut self, entity: EntityID) -> Option<&mut Monster> {
+        return match get_entity_type(entity) {
+            EntityType::Monster => self.monster_components.get_monster_mut(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_npc(&self, entity: EntityID) -> Option<&Npc> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_npc(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_npc_mut(&mut self, entity: EntityID) -> Option<&mut Npc> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_npc_mut(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_nutrition(&self, entity: EntityID) -> Option<&Nutrition> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_nutrition(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_nutrition_mut(&mut self, entity: EntityID) -> Option<&mut Nutrition> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_nutrition_mut(entity),
+            _ => None,
+        };
+    }
+
     pub fn get_position(&self, entity: EntityID) -> Option<&Position> {
         return match get_entity_type(entity) {
             EntityType::Character => self.character_components.get_position(entity),
@@ -460,4 +860,38 @@ impl Components {
         };
     }
 
-}
\ No newline at end of file
+    pub fn get_status(&self, entity: EntityID) -> Option<&Status> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_status(entity),
+            EntityType::Monster => self.monster_components.get_status(entity),
+            EntityType::Object => self.object_components.get_status(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_status_mut(&mut self, entity: EntityID) -> Option<&mut Status> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_status_mut(entity),
+            EntityType::Monster => self.monster_components.get_status_mut(entity),
+            EntityType::Object => self.object_components.get_status_mut(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_turn(&self, entity: EntityID) -> Option<&Turn> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_turn(entity),
+            EntityType::Monster => self.monster_components.get_turn(entity),
+            _ => None,
+        };
+    }
+
+    pub fn get_turn_mut(&mut self, entity: EntityID) -> Option<&mut Turn> {
+        return match get_entity_type(entity) {
+            EntityType::Character => self.character_components.get_turn_mut(entity),
+            EntityType::Monster => self.monster_components.get_turn_mut(entity),
+            _ => None,
+        };
+    }
+
+}
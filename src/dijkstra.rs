@@ -0,0 +1,345 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{game::Game, map::{GameMap, MapID}, pathfinding::{self, NEIGHBOR_OFFSETS}};
+
+// Distance from a tile that can't reach any goal
+pub const UNREACHABLE: i32 = i32::MAX;
+
+// Fleeing creatures will go this much further, as a fraction of how far they
+// already are, to reach somewhere with more room to run instead of a corner
+const FLEE_NUMERATOR: i32 = 6;
+const FLEE_DENOMINATOR: i32 = 5;
+
+// How far every tile on a map is from the nearest of a set of goals, found
+// by Dijkstra's algorithm. Walking downhill from anywhere leads to a goal.
+pub struct DijkstraMap {
+    pub map_id: MapID,
+    pub distances: Vec<i32>,
+    // What each goal tile starts out at, which isn't always zero
+    goals: Vec<Option<i32>>,
+    // The neighbor each tile's distance came from, so that everything that
+    // depended on a changed tile can be found again
+    parents: Vec<Option<usize>>,
+}
+
+impl DijkstraMap {
+    pub fn new(map: &GameMap, goals: &[(u16, u16, i32)]) -> Self {
+        let mut result = Self {
+            map_id: map.id,
+            distances: vec![UNREACHABLE; map.tiles.len()],
+            goals: vec![None; map.tiles.len()],
+            parents: vec![None; map.tiles.len()],
+        };
+        for (x, y, value) in goals {
+            result.goals[map.coordinates_to_index(*x, *y)] = Some(*value);
+        }
+        let everything: Vec<usize> = (0..map.tiles.len()).collect();
+        result.repair(map, &everything);
+        return result;
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            map_id: 0,
+            distances: Vec::new(),
+            goals: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    // Whether the distances were worked out for this map
+    pub fn is_current(&self, map: &GameMap) -> bool {
+        return self.map_id == map.id && self.distances.len() == map.tiles.len();
+    }
+
+    // Nothing is reachable on an empty map, which has no goals to reach
+    pub fn get_distance(&self, map: &GameMap, x: u16, y: u16) -> Option<i32> {
+        let distance = *self.distances.get(map.coordinates_to_index(x, y))?;
+        if distance == UNREACHABLE {
+            return None;
+        }
+        return Some(distance);
+    }
+
+    // The neighboring tile that gets closest to a goal, if any gets closer
+    pub fn get_downhill_step(&self, map: &GameMap, x: u16, y: u16, is_blocked: impl Fn(u16, u16) -> bool) -> Option<(u16, u16)> {
+        let mut best: Option<(u16, u16)> = None;
+        let mut best_distance = self.distances[map.coordinates_to_index(x, y)];
+        for offset in NEIGHBOR_OFFSETS {
+            let maybe_neighbor = pathfinding::get_neighbor(map, x, y, offset);
+            if maybe_neighbor.is_none() {
+                continue;
+            }
+            let (next_x, next_y) = maybe_neighbor.unwrap();
+            let distance = self.distances[map.coordinates_to_index(next_x, next_y)];
+            if distance < best_distance && !is_blocked(next_x, next_y) {
+                best = Some((next_x, next_y));
+                best_distance = distance;
+            }
+        }
+        return best;
+    }
+
+    // Moves the goals, only working out again the distances that depended on
+    // the goals that changed. Returns whether any did.
+    pub fn set_goals(&mut self, map: &GameMap, goals: &[(u16, u16, i32)]) -> bool {
+        let mut new_goals: Vec<Option<i32>> = vec![None; map.tiles.len()];
+        for (x, y, value) in goals {
+            new_goals[map.coordinates_to_index(*x, *y)] = Some(*value);
+        }
+        let changed: Vec<usize> = (0..new_goals.len()).filter(|index| new_goals[*index] != self.goals[*index]).collect();
+        self.goals = new_goals;
+        self.repair(map, &changed);
+        return !changed.is_empty();
+    }
+
+    // Brings the distances up to date after some tiles have changed, such as
+    // a door opening or a wall being dug out. Returns whether any had.
+    pub fn update_tiles(&mut self, map: &GameMap, changed: &[usize]) -> bool {
+        self.repair(map, changed);
+        return !changed.is_empty();
+    }
+
+    // Forgets the distance of every changed tile and of everything whose
+    // distance was reached through one, then lets the distances flow back in
+    // from the tiles around them that are still right
+    fn repair(&mut self, map: &GameMap, changed: &[usize]) {
+        let mut invalid: Vec<bool> = vec![false; self.distances.len()];
+        let mut pending: Vec<usize> = Vec::new();
+        for index in changed {
+            if !invalid[*index] {
+                invalid[*index] = true;
+                pending.push(*index);
+            }
+        }
+        let mut reset: Vec<usize> = Vec::new();
+        while let Some(index) = pending.pop() {
+            reset.push(index);
+            let (x, y) = map.index_to_coordinates(index);
+            for offset in NEIGHBOR_OFFSETS {
+                let maybe_neighbor = pathfinding::get_neighbor(map, x, y, offset);
+                if maybe_neighbor.is_none() {
+                    continue;
+                }
+                let (next_x, next_y) = maybe_neighbor.unwrap();
+                let next_index = map.coordinates_to_index(next_x, next_y);
+                if !invalid[next_index] && self.parents[next_index] == Some(index) {
+                    invalid[next_index] = true;
+                    pending.push(next_index);
+                }
+            }
+        }
+
+        let mut frontier: BinaryHeap<Reverse<(i32, usize)>> = BinaryHeap::new();
+        for index in reset {
+            let (x, y) = map.index_to_coordinates(index);
+            self.parents[index] = None;
+            self.distances[index] = match self.goals[index] {
+                Some(value) if pathfinding::get_step_cost(map, x, y).is_some() => value,
+                _ => UNREACHABLE,
+            };
+            if self.distances[index] != UNREACHABLE {
+                frontier.push(Reverse((self.distances[index], index)));
+            }
+
+            for offset in NEIGHBOR_OFFSETS {
+                let maybe_neighbor = pathfinding::get_neighbor(map, x, y, offset);
+                if maybe_neighbor.is_none() {
+                    continue;
+                }
+                let (next_x, next_y) = maybe_neighbor.unwrap();
+                let next_index = map.coordinates_to_index(next_x, next_y);
+                if !invalid[next_index] && self.distances[next_index] != UNREACHABLE {
+                    frontier.push(Reverse((self.distances[next_index], next_index)));
+                }
+            }
+        }
+
+        while let Some(Reverse((distance, index))) = frontier.pop() {
+            if distance > self.distances[index] {
+                continue;
+            }
+            let (x, y) = map.index_to_coordinates(index);
+            let maybe_step = pathfinding::get_step_cost(map, x, y);
+            if maybe_step.is_none() {
+                continue;
+            }
+            // Getting here from a neighbor means stepping onto this tile
+            let next_distance = distance + maybe_step.unwrap() as i32;
+
+            for offset in NEIGHBOR_OFFSETS {
+                let maybe_neighbor = pathfinding::get_neighbor(map, x, y, offset);
+                if maybe_neighbor.is_none() {
+                    continue;
+                }
+                let (next_x, next_y) = maybe_neighbor.unwrap();
+                let next_index = map.coordinates_to_index(next_x, next_y);
+                if pathfinding::get_step_cost(map, next_x, next_y).is_none() || next_distance >= self.distances[next_index] {
+                    continue;
+                }
+                self.distances[next_index] = next_distance;
+                self.parents[next_index] = Some(index);
+                frontier.push(Reverse((next_distance, next_index)));
+            }
+        }
+    }
+}
+
+// Turns a map leading towards something into one leading away from it.
+// Running straight away ends up in corners, so every tile is made a goal
+// worth more the further it is, and the map is worked out again so that
+// walking downhill heads for wherever there's the most room to run.
+pub fn get_flee_map(map: &GameMap, towards: &DijkstraMap) -> DijkstraMap {
+    let mut goals: Vec<(u16, u16, i32)> = Vec::new();
+    for (index, distance) in towards.distances.iter().enumerate() {
+        if *distance == UNREACHABLE {
+            continue;
+        }
+        let (x, y) = map.index_to_coordinates(index);
+        goals.push((x, y, -distance * FLEE_NUMERATOR / FLEE_DENOMINATOR));
+    }
+    return DijkstraMap::new(map, &goals);
+}
+
+fn get_player_goals(game: &Game) -> Vec<(u16, u16, i32)> {
+    return match game.components.get_position(game.special_entities.player) {
+        Some(position) => vec![(position.x, position.y, 0)],
+        None => Vec::new(),
+    };
+}
+
+// Anywhere the player hasn't seen yet that could be walked to
+pub fn get_unexplored_goals(map: &GameMap) -> Vec<(u16, u16, i32)> {
+    let mut result: Vec<(u16, u16, i32)> = Vec::new();
+    for (index, explored) in map.explored.iter().enumerate() {
+        let (x, y) = map.index_to_coordinates(index);
+        if !*explored && pathfinding::get_step_cost(map, x, y).is_some() {
+            result.push((x, y, 0));
+        }
+    }
+    return result;
+}
+
+// Anywhere there's something lying on the ground
+pub fn get_item_goals(map: &GameMap) -> Vec<(u16, u16, i32)> {
+    let mut result: Vec<(u16, u16, i32)> = Vec::new();
+    for (index, items) in map.items.iter().enumerate() {
+        if !items.is_empty() {
+            let (x, y) = map.index_to_coordinates(index);
+            result.push((x, y, 0));
+        }
+    }
+    return result;
+}
+
+// The distance maps kept up to date for the current map
+pub struct DistanceMaps {
+    pub to_player: DijkstraMap,
+    pub from_player: DijkstraMap,
+    pub to_items: DijkstraMap,
+    pub to_unexplored: DijkstraMap,
+    // Leads to wherever the player asked to travel to
+    pub travel: Option<DijkstraMap>,
+}
+
+impl DistanceMaps {
+    pub fn new() -> Self {
+        Self {
+            to_player: DijkstraMap::empty(),
+            from_player: DijkstraMap::empty(),
+            to_items: DijkstraMap::empty(),
+            to_unexplored: DijkstraMap::empty(),
+            travel: None,
+        }
+    }
+}
+
+// Catches the distance maps up with any tiles that have changed and with
+// wherever their goals are now, working them out from scratch on a new map
+pub fn update_distance_maps(game: &mut Game) {
+    let changed: Vec<usize> = std::mem::take(&mut game.current_map.changed_tiles);
+    let player_goals = get_player_goals(game);
+    let map = &game.current_map;
+    let maps = &mut game.distance_maps;
+
+    if !maps.to_player.is_current(map) {
+        maps.to_player = DijkstraMap::new(map, &player_goals);
+        maps.from_player = get_flee_map(map, &maps.to_player);
+        maps.to_items = DijkstraMap::new(map, &get_item_goals(map));
+        maps.to_unexplored = DijkstraMap::new(map, &get_unexplored_goals(map));
+        maps.travel = None;
+        return;
+    }
+
+    let tiles_changed = maps.to_player.update_tiles(map, &changed);
+    let player_moved = maps.to_player.set_goals(map, &player_goals);
+    if tiles_changed || player_moved {
+        maps.from_player = get_flee_map(map, &maps.to_player);
+    }
+    maps.to_items.update_tiles(map, &changed);
+    maps.to_items.set_goals(map, &get_item_goals(map));
+    maps.to_unexplored.update_tiles(map, &changed);
+    maps.to_unexplored.set_goals(map, &get_unexplored_goals(map));
+    if let Some(travel) = maps.travel.as_mut() {
+        travel.update_tiles(map, &changed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Tile;
+
+    fn make_test_map() -> GameMap {
+        let mut map = GameMap::new(0, 7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                map.set_tile(x, y, Tile::Floor);
+            }
+        }
+        for y in 0..5 {
+            map.set_tile(3, y, Tile::Wall);
+        }
+        map.set_tile(3, 2, Tile::DoorClosed);
+        map.changed_tiles.clear();
+        return map;
+    }
+
+    #[test]
+    fn test_distances_and_downhill() {
+        let map = make_test_map();
+        let towards = DijkstraMap::new(&map, &[(6, 2, 0)]);
+        assert_eq!(towards.get_distance(&map, 6, 2), Some(0));
+        assert_eq!(towards.get_distance(&map, 5, 2), Some(1));
+        // Stepping into the doorway costs two, and then there are three more tiles
+        assert_eq!(towards.get_distance(&map, 2, 2), Some(5));
+        assert_eq!(towards.get_distance(&map, 3, 0), None);
+        assert_eq!(towards.get_downhill_step(&map, 2, 1, |_, _| false), Some((3, 2)));
+        assert_eq!(towards.get_downhill_step(&map, 2, 1, |x, _| x == 3), None);
+
+        // Running away heads through the door for the far side of the room
+        let away = get_flee_map(&map, &towards);
+        assert!(away.get_distance(&map, 0, 0).unwrap() < away.get_distance(&map, 5, 2).unwrap());
+        assert_eq!(away.get_downhill_step(&map, 5, 2, |_, _| false).map(|(x, _)| x < 5), Some(true));
+    }
+
+    #[test]
+    fn test_incremental_updates_match_recomputing() {
+        let mut map = make_test_map();
+        let mut incremental = DijkstraMap::new(&map, &[(0, 0, 0)]);
+
+        map.set_tile(3, 2, Tile::DoorOpen);
+        map.set_tile(3, 4, Tile::Floor);
+        let changed = std::mem::take(&mut map.changed_tiles);
+        assert_eq!(changed.len(), 2);
+        incremental.update_tiles(&map, &changed);
+        assert_eq!(incremental.distances, DijkstraMap::new(&map, &[(0, 0, 0)]).distances);
+
+        map.set_tile(3, 2, Tile::Wall);
+        map.set_tile(1, 1, Tile::Wall);
+        let changed = std::mem::take(&mut map.changed_tiles);
+        incremental.update_tiles(&map, &changed);
+        incremental.set_goals(&map, &[(0, 4, 0), (6, 0, 0)]);
+        assert_eq!(incremental.distances, DijkstraMap::new(&map, &[(0, 4, 0), (6, 0, 0)]).distances);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+use crate::{action, entity::EntityID, game::Game, item, map::{self, GameMap, Tile}, material::{self, Material}, rng::RngStream, tabletop::{DamageType, Dice}, tag::{Tag, TagCache}};
+
+// How far a discharge arcs along anything that conducts, in tiles
+pub const ARC_RANGE: u16 = 6;
+
+// Metal armor draws the current in
+pub const METAL_ARMOR_DAMAGE: Dice = Dice::new(1, 6, 0);
+
+// Until there's equipment, metal armor counts as worn as long as it's carried
+pub fn is_wearing_metal(game: &Game, entity: EntityID) -> bool {
+    let maybe_inventory = game.components.get_inventory(entity);
+    if maybe_inventory.is_none() {
+        return false;
+    }
+    let tag_cache = &game.data_tables.tag_cache;
+    let material_map = &game.data_tables.material_map;
+    return maybe_inventory.unwrap().items.iter()
+        .any(|armor| item::has_tag(armor, Tag::Armor, tag_cache) && material::is_a(armor.material, Material::Metal, material_map));
+}
+
+// Metal armor and a soaking both carry the current on to whoever's nearby
+pub fn is_conductive_creature(game: &Game, entity: EntityID) -> bool {
+    return is_wearing_metal(game, entity) || game.get_entity_tags(entity).contains(Tag::Wet);
+}
+
+pub fn get_extra_lightning_damage(game: &mut Game, entity: EntityID) -> i32 {
+    if is_wearing_metal(game, entity) {
+        return game.rng.roll(RngStream::Combat, METAL_ARMOR_DAMAGE);
+    }
+    return 0;
+}
+
+// Levers, wet ground and anything metal lying around carry a current
+pub fn is_conductive_tile(map: &GameMap, x: u16, y: u16, tag_cache: &TagCache) -> bool {
+    if matches!(map.get_tile(x, y), Tile::Lever) || map.is_wet(x, y, tag_cache) {
+        return true;
+    }
+    return map.get_items(x, y).iter().any(|conductor| item::has_tag(conductor, Tag::Conductive, tag_cache));
+}
+
+// Every creature on the current map and where it stands
+fn get_creature_positions(game: &Game) -> Vec<(EntityID, u16, u16)> {
+    let mut result: Vec<(EntityID, u16, u16)> = Vec::new();
+    for creature in game.components.get_living_creatures() {
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        if map_index.is_none() || position.is_none() || map_index.unwrap().map != game.current_map.id {
+            continue;
+        }
+        result.push((creature, position.unwrap().x, position.unwrap().y));
+    }
+    return result;
+}
+
+// Finds every tile connected to the start through conductive tiles and
+// creatures, going no further than the range
+pub fn trace_circuit(game: &Game, start_x: u16, start_y: u16, range: u16) -> Vec<(u16, u16)> {
+    let map = &game.current_map;
+    let tag_cache = &game.data_tables.tag_cache;
+    let conductive_creatures: Vec<(u16, u16)> = get_creature_positions(game).into_iter()
+        .filter(|(creature, _, _)| is_conductive_creature(game, *creature))
+        .map(|(_, x, y)| (x, y))
+        .collect();
+    let conducts = |x: u16, y: u16| is_conductive_tile(map, x, y, tag_cache) || conductive_creatures.contains(&(x, y));
+
+    let mut distances: Vec<Option<u16>> = vec![None; map.tiles.len()];
+    let mut result: Vec<(u16, u16)> = Vec::new();
+    let mut frontier: VecDeque<(u16, u16)> = VecDeque::new();
+    distances[map.coordinates_to_index(start_x, start_y)] = Some(0);
+    frontier.push_back((start_x, start_y));
+
+    while let Some((x, y)) = frontier.pop_front() {
+        result.push((x, y));
+        let distance = distances[map.coordinates_to_index(x, y)].unwrap();
+        if distance >= range || !conducts(x, y) {
+            continue;
+        }
+
+        for offset_y in -1..=1 {
+            for offset_x in -1..=1 {
+                let next_x: i32 = x as i32 + offset_x;
+                let next_y: i32 = y as i32 + offset_y;
+                if next_x < 0 || next_y < 0 || !map.is_in_bounds(next_x as u16, next_y as u16) {
+                    continue;
+                }
+                let next_index = map.coordinates_to_index(next_x as u16, next_y as u16);
+                if distances[next_index].is_some() {
+                    continue;
+                }
+                // The current reaches whatever touches a conductor, but only
+                // keeps going through other conductors
+                distances[next_index] = Some(distance + 1);
+                frontier.push_back((next_x as u16, next_y as u16));
+            }
+        }
+    }
+
+    return result;
+}
+
+// Sends a bolt of lightning into a tile, which arcs out to hit everything
+// connected to it
+pub fn discharge(game: &mut Game, x: u16, y: u16, amount: i32) {
+    let circuit = trace_circuit(game, x, y, ARC_RANGE);
+    for (creature, creature_x, creature_y) in get_creature_positions(game) {
+        if circuit.contains(&(creature_x, creature_y)) {
+            action::report(game, creature, String::from("Lightning courses through you!"));
+            action::apply_damage(game, creature, amount, DamageType::Lightning);
+        }
+    }
+}
+
+// Powers every mechanism wired up to a lever, returning how many moved
+pub fn pull_lever(game: &mut Game, x: u16, y: u16) -> u32 {
+    let range = game.current_map.width + game.current_map.height;
+    let circuit = trace_circuit(game, x, y, range);
+
+    let mut result: u32 = 0;
+    let map = game.current_map.as_mut();
+    for (circuit_x, circuit_y) in circuit {
+        if let Some(toggled) = map::get_toggled_mechanism(map.get_tile(circuit_x, circuit_y)) {
+            map.set_tile(circuit_x, circuit_y, toggled);
+            result += 1;
+        }
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{Item, ItemType};
+
+    fn make_test_game() -> Game {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 8, 1));
+        for x in 0..8 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+
+        let player = game.special_entities.player;
+        let health = game.components.get_health_mut(player).unwrap();
+        health.current = 50;
+        health.maximum = 50;
+        let position = game.components.get_position_mut(player).unwrap();
+        position.x = 4;
+        position.y = 0;
+        return game;
+    }
+
+    #[test]
+    fn test_lightning_arcs_along_chain() {
+        let mut game = make_test_game();
+        let player = game.special_entities.player;
+
+        discharge(&mut game, 0, 0, 10);
+        assert_eq!(game.components.get_health(player).unwrap().current, 50);
+
+        for x in 0..4 {
+            game.current_map.get_items_mut(x, 0).push(Item::new(ItemType::Chain));
+        }
+        discharge(&mut game, 0, 0, 10);
+        assert_eq!(game.components.get_health(player).unwrap().current, 40);
+
+        // Metal armor takes more of the current
+        game.components.get_inventory_mut(player).unwrap().items.push(Item::new(ItemType::ChainMailArmor));
+        discharge(&mut game, 0, 0, 10);
+        let health = game.components.get_health(player).unwrap().current;
+        assert!((30 - 6..30).contains(&health));
+    }
+
+    #[test]
+    fn test_soaked_creatures_pass_the_current_on() {
+        let mut game = make_test_game();
+        let player = game.special_entities.player;
+        let monster = game.components.create_entity(crate::component::EntityType::Monster);
+        *game.components.get_position_mut(monster).unwrap() = crate::component::Position { x: 3, y: 0 };
+        game.components.get_health_mut(monster).unwrap().current = 50;
+
+        discharge(&mut game, 3, 0, 10);
+        assert_eq!(game.components.get_health(player).unwrap().current, 50);
+
+        // A puddle soaks the monster, which then carries the current on even
+        // once the puddle's been mopped up
+        game.current_map.set_spill(3, 0, Some(crate::substance::Substance::Water));
+        crate::temperature::update_creatures(&mut game);
+        game.current_map.set_spill(3, 0, None);
+        assert!(game.get_entity_tags(monster).contains(Tag::Wet));
+        discharge(&mut game, 3, 0, 10);
+        assert_eq!(game.components.get_health(player).unwrap().current, 40);
+    }
+
+    #[test]
+    fn test_lever_opens_door() {
+        let mut game = make_test_game();
+        game.current_map.set_tile(0, 0, Tile::Lever);
+        game.current_map.set_tile(3, 0, Tile::DoorClosed);
+        assert_eq!(pull_lever(&mut game, 0, 0), 0);
+
+        game.current_map.get_items_mut(1, 0).push(Item::new(ItemType::Chain));
+        game.current_map.set_spill(2, 0, Some(crate::substance::Substance::Water));
+        assert_eq!(pull_lever(&mut game, 0, 0), 1);
+        assert!(matches!(game.current_map.get_tile(3, 0), Tile::DoorOpen));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{action, game::Game, item::{self, Item}, map::{self, GameMap}, rng::RngStream, substance::{Liquid, Substance}, tabletop::{DamageType, Dice}, tag::{Tag, TagCache}, time::SECONDS_PER_TURN};
+
+// Standing in flames, as per the 5e rules for environmental fire
+pub const FIRE_DAMAGE: Dice = Dice::new(1, 10, 0);
+pub const FIRE_LIGHT_RADIUS: u16 = 4;
+
+const SMOKE_DURATION: u8 = 10;
+
+// Extra turns of burning from a pool of oil or anything flammable lying around
+const SPILL_FUEL: u16 = 5;
+const ITEM_FUEL: u16 = 2;
+
+// How long a tile would burn for, counting what's lying on it. Anything
+// soaked with a liquid that doesn't burn won't catch.
+pub fn get_fuel(map: &GameMap, x: u16, y: u16, tag_cache: &TagCache) -> u16 {
+    let mut fuel = map::get_tile_fuel(map.get_tile(x, y));
+
+    if let Some(spill) = map.get_spill(x, y) {
+        if !tag_cache.substances[spill].contains(Tag::Flammable) {
+            return 0;
+        }
+        fuel += SPILL_FUEL;
+    }
+
+    for item in map.get_items(x, y) {
+        if is_combustible(item, tag_cache) {
+            fuel += ITEM_FUEL;
+        }
+    }
+
+    return fuel;
+}
+
+// Sets a tile alight, returning whether it caught
+pub fn ignite(map: &mut GameMap, x: u16, y: u16, tag_cache: &TagCache) -> bool {
+    if map.is_burning(x, y) {
+        return false;
+    }
+    let fuel = get_fuel(map, x, y, tag_cache);
+    if fuel == 0 {
+        return false;
+    }
+    let index = map.coordinates_to_index(x, y);
+    map.fires[index] = fuel;
+    map.changed_tiles.push(index);
+    return true;
+}
+
+pub fn extinguish(map: &mut GameMap, x: u16, y: u16) {
+    let index = map.coordinates_to_index(x, y);
+    if map.fires[index] > 0 {
+        map.changed_tiles.push(index);
+    }
+    map.fires[index] = 0;
+}
+
+// Things that would burn if they weren't soaked through
+pub fn is_combustible(item: &Item, tag_cache: &TagCache) -> bool {
+    return item::has_tag(item, Tag::Flammable, tag_cache) && !item.state.contains(Tag::Wet);
+}
+
+// Advances every fire on the current map by one turn
+pub fn update_fires(game: &mut Game) {
+    let tag_cache = &game.data_tables.tag_cache;
+    let map = game.current_map.as_mut();
+
+    for smoke in map.smoke.iter_mut() {
+        *smoke = smoke.saturating_sub(1);
+    }
+
+    // Spread from the fires as they were at the start of the turn, so they
+    // creep outwards one tile at a time
+    let burning: Vec<usize> = (0..map.fires.len()).filter(|index| map.fires[*index] > 0).collect();
+    for index in burning {
+        let (x, y) = map.index_to_coordinates(index);
+        burn_tile(map, x, y, tag_cache);
+
+        for (neighbor_x, neighbor_y) in map.get_orthogonal_neighbors(x, y) {
+            ignite(map, neighbor_x, neighbor_y, tag_cache);
+        }
+    }
+
+    // Lit torches and the like set fire to whatever they're dropped on
+    for index in 0..map.items.len() {
+        if map.items[index].iter().any(|item| item.state.contains(Tag::Burning)) {
+            let (x, y) = map.index_to_coordinates(index);
+            ignite(map, x, y, tag_cache);
+        }
+    }
+
+    burn_down_items(game);
+
+    let map_id = game.current_map.id;
+    for creature in game.components.get_living_creatures() {
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        if map_index.is_none() || position.is_none() || map_index.unwrap().map != map_id {
+            continue;
+        }
+        let position = position.unwrap();
+        if game.current_map.is_in_bounds(position.x, position.y) && game.current_map.is_burning(position.x, position.y) {
+            action::report(game, creature, String::from("You are burned by the flames!"));
+            let damage = game.rng.roll(RngStream::Combat, FIRE_DAMAGE);
+            action::apply_damage(game, creature, damage, DamageType::Fire);
+        }
+    }
+}
+
+// Uses up a turn of fuel, consuming anything flammable on the tile and
+// leaving it burnt out once nothing is left
+fn burn_tile(map: &mut GameMap, x: u16, y: u16, tag_cache: &TagCache) {
+    let index = map.coordinates_to_index(x, y);
+    map.fires[index] -= 1;
+
+    map.items[index].retain(|item| !is_combustible(item, tag_cache) || item.state.contains(Tag::Burning));
+    if map.spills[index].is_some() {
+        map.spills[index] = None;
+    }
+
+    map.smoke[index] = SMOKE_DURATION;
+    for (neighbor_x, neighbor_y) in map.get_orthogonal_neighbors(x, y) {
+        let neighbor = map.coordinates_to_index(neighbor_x, neighbor_y);
+        map.smoke[neighbor] = u8::max(map.smoke[neighbor], SMOKE_DURATION / 2);
+    }
+
+    if map.fires[index] == 0 {
+        map.changed_tiles.push(index);
+        let burnt = map::get_burnt_tile(map.get_tile(x, y));
+        map.set_tile(x, y, burnt);
+    }
+}
+
+// Lit items use up their fuel wherever they are. Torches and candles are
+// gone once it runs out, lamps just go out once their oil does.
+fn burn_down_items(game: &mut Game) {
+    for pile in game.current_map.items.iter_mut() {
+        burn_down_pile(pile);
+    }
+
+    for creature in game.components.get_living_creatures() {
+        let maybe_inventory = game.components.get_inventory_mut(creature);
+        if maybe_inventory.is_none() {
+            continue;
+        }
+        for message in burn_down_pile(&mut maybe_inventory.unwrap().items) {
+            action::report(game, creature, message);
+        }
+    }
+}
+
+fn burn_down_pile(items: &mut Vec<Item>) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    for item in items.iter_mut() {
+        if !item.state.contains(Tag::Burning) {
+            continue;
+        }
+        item.fuel = item.fuel.saturating_sub(SECONDS_PER_TURN);
+        if item.fuel > 0 || !item::burns_oil(item.item_type) {
+            continue;
+        }
+
+        let name = item::get_name(item.item_type);
+        match item.liquid {
+            Some(oil) if oil.substance == Substance::Oil => {
+                item.liquid = if oil.volume > 1 { Some(Liquid::new(oil.substance, oil.volume - 1)) } else { None };
+                item.fuel = item::OIL_BURN_TIME;
+            },
+            _ => {
+                item.state.remove(Tag::Burning);
+                messages.push(format!("Your {} goes out.", name));
+            },
+        }
+    }
+
+    let mut index = 0;
+    while index < items.len() {
+        if items[index].state.contains(Tag::Burning) && items[index].fuel == 0 {
+            let burnt_out = items.remove(index);
+            messages.push(format!("Your {} burns out.", item::get_name(burnt_out.item_type)));
+        } else {
+            index += 1;
+        }
+    }
+    return messages;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{item::ItemType, map::Tile};
+
+    fn make_test_game() -> Game {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 5, 1));
+        for x in 0..5 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+        return game;
+    }
+
+    #[test]
+    fn test_fire_spreads_and_burns_out() {
+        let mut game = make_test_game();
+        game.current_map.set_tile(0, 0, Tile::Web);
+        game.current_map.set_tile(1, 0, Tile::Web);
+        game.current_map.set_tile(3, 0, Tile::Web);
+
+        assert!(ignite(&mut game.current_map, 0, 0, &game.data_tables.tag_cache));
+        update_fires(&mut game);
+        assert!(game.current_map.is_burning(1, 0));
+        assert!(matches!(game.current_map.get_tile(0, 0), Tile::Floor));
+
+        update_fires(&mut game);
+        update_fires(&mut game);
+        assert!(!game.current_map.is_burning(3, 0));
+        assert!(game.current_map.smoke[1] > 0);
+    }
+
+    #[test]
+    fn test_wet_tiles_and_oil() {
+        let mut game = make_test_game();
+        let tag_cache = &game.data_tables.tag_cache;
+        let map = game.current_map.as_mut();
+
+        map.get_items_mut(0, 0).push(Item::new(ItemType::Book));
+        assert!(get_fuel(map, 0, 0, tag_cache) > 0);
+        map.set_spill(0, 0, Some(Substance::Water));
+        assert_eq!(get_fuel(map, 0, 0, tag_cache), 0);
+
+        map.set_spill(1, 0, Some(Substance::Oil));
+        assert!(ignite(map, 1, 0, tag_cache));
+    }
+
+    #[test]
+    fn test_lamp_burns_oil() {
+        let mut lamp = Item::with_liquid(ItemType::Lamp, Liquid::new(Substance::Oil, 2));
+        lamp.state.insert(Tag::Burning);
+        lamp.fuel = SECONDS_PER_TURN;
+        let mut items = vec!(lamp);
+
+        assert!(burn_down_pile(&mut items).is_empty());
+        assert_eq!(items[0].liquid.unwrap().volume, 1);
+
+        items[0].fuel = SECONDS_PER_TURN;
+        burn_down_pile(&mut items);
+        items[0].fuel = SECONDS_PER_TURN;
+        assert_eq!(burn_down_pile(&mut items).len(), 1);
+        assert!(!items[0].state.contains(Tag::Burning));
+    }
+}
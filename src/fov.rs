@@ -0,0 +1,83 @@
+use crate::map::GameMap;
+
+// Whether anything between the two tiles blocks the view, walking a
+// Bresenham line between them. The tiles at either end don't count, so
+// walls can be seen even though they can't be seen past.
+pub fn has_line_of_sight(map: &GameMap, from_x: u16, from_y: u16, to_x: u16, to_y: u16) -> bool {
+    let (mut x, mut y) = (from_x as i32, from_y as i32);
+    let (end_x, end_y) = (to_x as i32, to_y as i32);
+    let delta_x = (end_x - x).abs();
+    let delta_y = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = delta_x + delta_y;
+
+    loop {
+        if x == end_x && y == end_y {
+            return true;
+        }
+        let doubled = 2 * error;
+        if doubled >= delta_y {
+            error += delta_y;
+            x += step_x;
+        }
+        if doubled <= delta_x {
+            error += delta_x;
+            y += step_y;
+        }
+        if (x != end_x || y != end_y) && map.blocks_sight(x as u16, y as u16) {
+            return false;
+        }
+    }
+}
+
+// Every tile within the radius that can be seen from the origin
+pub fn compute_fov(map: &GameMap, origin_x: u16, origin_y: u16, radius: u16) -> Vec<bool> {
+    let mut result: Vec<bool> = vec![false; map.tiles.len()];
+    let min_x = origin_x.saturating_sub(radius);
+    let min_y = origin_y.saturating_sub(radius);
+    let max_x = u16::min(origin_x.saturating_add(radius), map.width.saturating_sub(1));
+    let max_y = u16::min(origin_y.saturating_add(radius), map.height.saturating_sub(1));
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if is_within_radius(origin_x, origin_y, x, y, radius) && has_line_of_sight(map, origin_x, origin_y, x, y) {
+                result[map.coordinates_to_index(x, y)] = true;
+            }
+        }
+    }
+
+    return result;
+}
+
+pub fn is_within_radius(from_x: u16, from_y: u16, to_x: u16, to_y: u16, radius: u16) -> bool {
+    let delta_x = from_x.abs_diff(to_x) as u32;
+    let delta_y = from_y.abs_diff(to_y) as u32;
+    return delta_x * delta_x + delta_y * delta_y <= radius as u32 * radius as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Tile;
+
+    #[test]
+    fn test_walls_block_sight() {
+        let mut map = GameMap::new(0, 7, 7);
+        for y in 0..7 {
+            for x in 0..7 {
+                map.set_tile(x, y, Tile::Floor);
+            }
+        }
+        map.set_tile(3, 3, Tile::Wall);
+
+        assert!(has_line_of_sight(&map, 0, 3, 3, 3));
+        assert!(!has_line_of_sight(&map, 0, 3, 6, 3));
+        assert!(has_line_of_sight(&map, 0, 0, 6, 0));
+
+        let visible = compute_fov(&map, 0, 3, 10);
+        assert!(visible[map.coordinates_to_index(3, 3)]);
+        assert!(!visible[map.coordinates_to_index(5, 3)]);
+        assert!(visible[map.coordinates_to_index(5, 0)]);
+    }
+}
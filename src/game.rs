@@ -1,8 +1,10 @@
 use std::collections::VecDeque;
 
-use ringbuffer::AllocRingBuffer;
+use enum_map::EnumMap;
 
-use crate::{action::ActionRequest, component::{Components, EntityType}, entity::EntityID, item::{self, ItemTagMap}, map::GameMap, material::{self, MaterialMap}, tag::{self, TagMap}, ui::menu::{self, MenuData, MenuType}};
+use ringbuffer::{AllocRingBuffer, RingBuffer};
+
+use crate::{action::ActionRequest, component::{Components, EntityType}, dijkstra::DistanceMaps, entity::EntityID, item::{self, ItemTagMap}, map::GameMap, material::{self, MaterialMap}, monster::MonsterKind, registry::MapRegistry, rng::Rng, substance::{self, SubstanceTagMap}, tag::{self, Tag, TagCache, TagSet}, temperature, time::Clock, travel::AutoMove, ui::menu::{self, MenuData, MenuType}, weather::Weather};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameState {
@@ -16,10 +18,13 @@ pub struct DebugInfo {
     pub fps_history: AllocRingBuffer<u32>
 }
 
+const MESSAGE_HISTORY_SIZE: usize = 100;
+
 pub struct DataTables {
     pub item_tag_map: ItemTagMap,
     pub material_map: MaterialMap,
-    pub tag_map: TagMap,
+    pub substance_tag_map: SubstanceTagMap,
+    pub tag_cache: TagCache,
 }
 
 pub struct SpecialEntities {
@@ -41,11 +46,22 @@ pub struct SpecialEntities {
 
 pub struct Game {
     pub action_queue: VecDeque<ActionRequest>,
+    // Where the player is exploring or travelling to on their own, if anywhere
+    pub auto_move: Option<AutoMove>,
+    // The kinds of monster the player has seen
+    pub bestiary: EnumMap<MonsterKind, bool>,
+    pub clock: Clock,
+    pub weather: Weather,
     pub components: Components,
     pub current_map: Box<GameMap>,
     pub data_tables: DataTables,
     pub debug_info: DebugInfo,
+    pub distance_maps: DistanceMaps,
+    // Every level in the world and the ways between them
+    pub maps: MapRegistry,
     pub menu_data: MenuData,
+    pub messages: AllocRingBuffer<String>,
+    pub rng: Rng,
     pub special_entities: SpecialEntities,
     pub state: GameState,
 }
@@ -75,21 +91,57 @@ impl Game {
     pub fn new() -> Self {
         let mut result = Self {
             action_queue: VecDeque::with_capacity(1000),
+            auto_move: None,
+            bestiary: EnumMap::default(),
+            clock: Clock::new(),
+            weather: Weather::Clear,
             components: Components::new(),
             current_map: Box::new(GameMap::empty_map()),
-            data_tables: DataTables {
-                item_tag_map: item::generate_item_tag_map(),
-                tag_map: tag::generate_tag_map(),
-                material_map: material::generate_material_map(),
-            },
+            data_tables: generate_data_tables(),
             debug_info: DebugInfo{fps_history: AllocRingBuffer::new(100)},
+            distance_maps: DistanceMaps::new(),
+            maps: MapRegistry::new(),
             menu_data: MenuData::new(),
+            messages: AllocRingBuffer::new(MESSAGE_HISTORY_SIZE),
+            rng: Rng::new(0),
             special_entities: SpecialEntities::new(),
             state: GameState::Menu(MenuType::Main),
         };
         set_up_special_entities(&mut result.special_entities, &mut result.components);
         return result;
     }
+
+    pub fn add_message(&mut self, message: String) {
+        self.messages.push(message);
+    }
+
+    // The tags a creature has right now, from what it is and what's happening to it
+    pub fn get_entity_tags(&self, entity: EntityID) -> TagSet {
+        let mut result = TagSet::new();
+        if self.components.get_creature(entity).is_some() {
+            result.insert(Tag::Animate);
+        }
+        if let Some(status) = self.components.get_status(entity) {
+            result = result.union(status.tags);
+            result = result.union(temperature::get_temperature_tags(status.temperature));
+        }
+        return self.data_tables.tag_cache.expand(result);
+    }
+}
+
+fn generate_data_tables() -> DataTables {
+    let item_tag_map = item::generate_item_tag_map();
+    let material_map = material::generate_material_map();
+    let substance_tag_map = substance::generate_substance_tag_map();
+    let tag_map = tag::generate_tag_map();
+    let tag_cache = TagCache::new(&tag_map, &item_tag_map, &material_map, &substance_tag_map);
+
+    return DataTables {
+        item_tag_map,
+        material_map,
+        substance_tag_map,
+        tag_cache,
+    };
 }
 
 fn set_up_special_entities(special_entities: &mut SpecialEntities, components: &mut Components) {
@@ -1 +1,7 @@
+pub mod bsp;
+pub mod caves;
+pub mod dump;
 pub mod map_gen;
+pub mod overworld;
+pub mod prefab;
+pub mod town;
@@ -0,0 +1,328 @@
+use crate::{map::{GameMap, MapID, Tile}, pathfinding, rng::{Rng, RngStream}};
+
+use super::map_gen::{self, GeneratedMap, Room};
+
+// How corridors get from one room to another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CorridorStyle {
+    // Straight along one axis, then straight along the other
+    Bent,
+    // Wanders on its way, drifting towards where it's going
+    Winding,
+}
+
+// Levels this deep were dug out less carefully, with tunnels that wander
+const WINDING_DEPTH: u16 = 3;
+
+pub struct BspSettings {
+    // Smallest and largest a room can be along either side, not counting walls
+    pub min_room_size: u16,
+    pub max_room_size: u16,
+    pub max_rooms: usize,
+    pub corridor_style: CorridorStyle,
+    // Percent chance for a doorway to get a door, and for a door to be left open
+    pub door_chance: u32,
+    pub open_door_chance: u32,
+}
+
+impl BspSettings {
+    pub fn new() -> Self {
+        Self {
+            min_room_size: 4,
+            max_room_size: 12,
+            max_rooms: 12,
+            corridor_style: CorridorStyle::Bent,
+            door_chance: 70,
+            open_door_chance: 30,
+        }
+    }
+
+    pub fn for_depth(depth: u16) -> Self {
+        let mut result = Self::new();
+        if depth >= WINDING_DEPTH {
+            result.corridor_style = CorridorStyle::Winding;
+        }
+        return result;
+    }
+}
+
+// One step of the partition. Leaves get a room each, and every split gets a
+// corridor joining its two halves, so every room can be reached.
+struct Node {
+    area: Room,
+    children: Option<(usize, usize)>,
+    room: Option<Room>,
+}
+
+// Whether an area can be split along one axis and leave room for a room and
+// its walls on both sides
+fn can_split(length: u16, settings: &BspSettings) -> bool {
+    return length >= 2 * (settings.min_room_size + 2);
+}
+
+fn split_area(area: &Room, settings: &BspSettings, rng: &mut Rng) -> Option<(Room, Room)> {
+    let can_split_across = can_split(area.width, settings);
+    let can_split_down = can_split(area.height, settings);
+    if !can_split_across && !can_split_down {
+        return None;
+    }
+    // Long thin areas get split the short way so rooms don't end up as corridors
+    let across = if can_split_across && can_split_down {
+        if area.width * 4 > area.height * 5 { true } else if area.height * 4 > area.width * 5 { false } else { rng.chance(RngStream::MapGen, 50) }
+    } else {
+        can_split_across
+    };
+
+    let smallest = (settings.min_room_size + 2) as i32;
+    if across {
+        let split = rng.range(RngStream::MapGen, smallest, area.width as i32 - smallest) as u16;
+        return Some((Room::new(area.x, area.y, split, area.height), Room::new(area.x + split, area.y, area.width - split, area.height)));
+    }
+    let split = rng.range(RngStream::MapGen, smallest, area.height as i32 - smallest) as u16;
+    return Some((Room::new(area.x, area.y, area.width, split), Room::new(area.x, area.y + split, area.width, area.height - split)));
+}
+
+// Fits a room of a random size somewhere inside an area, keeping a wall
+// between it and the area's edges
+fn make_room(area: &Room, settings: &BspSettings, rng: &mut Rng) -> Room {
+    let max_width = u16::min(settings.max_room_size, area.width - 2);
+    let max_height = u16::min(settings.max_room_size, area.height - 2);
+    let width = rng.range(RngStream::MapGen, settings.min_room_size as i32, max_width as i32) as u16;
+    let height = rng.range(RngStream::MapGen, settings.min_room_size as i32, max_height as i32) as u16;
+    let x = area.x + 1 + rng.range(RngStream::MapGen, 0, (area.width - 2 - width) as i32) as u16;
+    let y = area.y + 1 + rng.range(RngStream::MapGen, 0, (area.height - 2 - height) as i32) as u16;
+    return Room::new(x, y, width, height);
+}
+
+fn get_rooms_under(nodes: &[Node], index: usize) -> Vec<Room> {
+    return match nodes[index].children {
+        Some((left, right)) => {
+            let mut result = get_rooms_under(nodes, left);
+            result.extend(get_rooms_under(nodes, right));
+            result
+        },
+        None => nodes[index].room.into_iter().collect(),
+    };
+}
+
+// The pair of rooms, one from each side, that are closest together
+fn get_closest_rooms(first: &[Room], second: &[Room]) -> (Room, Room) {
+    let mut result = (first[0], second[0]);
+    let mut best = u32::MAX;
+    for a in first {
+        for b in second {
+            let (a_x, a_y) = a.center();
+            let (b_x, b_y) = b.center();
+            let distance = pathfinding::get_distance(a_x, a_y, b_x, b_y);
+            if distance < best {
+                best = distance;
+                result = (*a, *b);
+            }
+        }
+    }
+    return result;
+}
+
+fn carve(map: &mut GameMap, x: u16, y: u16) {
+    if *map.get_tile(x, y) == Tile::Wall {
+        map.set_tile(x, y, Tile::Floor);
+    }
+}
+
+fn carve_corridor(map: &mut GameMap, from: (u16, u16), to: (u16, u16), style: CorridorStyle, rng: &mut Rng) {
+    let (mut x, mut y) = from;
+    carve(map, x, y);
+    match style {
+        CorridorStyle::Bent => {
+            let horizontal_first = rng.chance(RngStream::MapGen, 50);
+            while (x, y) != to {
+                if (horizontal_first && x != to.0) || y == to.1 {
+                    x = if x < to.0 { x + 1 } else { x - 1 };
+                } else {
+                    y = if y < to.1 { y + 1 } else { y - 1 };
+                }
+                carve(map, x, y);
+            }
+        },
+        CorridorStyle::Winding => {
+            while (x, y) != to {
+                // Mostly heads the right way, but a third of the time goes
+                // sideways instead as long as that stays off the map's edge
+                let towards_x = x != to.0 && (y == to.1 || rng.chance(RngStream::MapGen, 50));
+                let wander = rng.chance(RngStream::MapGen, 33);
+                if towards_x && !wander {
+                    x = if x < to.0 { x + 1 } else { x - 1 };
+                } else if !towards_x && !wander && y != to.1 {
+                    y = if y < to.1 { y + 1 } else { y - 1 };
+                } else if towards_x && y > 1 && y < map.height - 2 {
+                    y = if rng.chance(RngStream::MapGen, 50) { y + 1 } else { y - 1 };
+                } else if x > 1 && x < map.width - 2 {
+                    x = if rng.chance(RngStream::MapGen, 50) { x + 1 } else { x - 1 };
+                }
+                carve(map, x, y);
+            }
+        },
+    }
+}
+
+// Wherever a corridor passes through the wall around a room between two
+// pieces of wall, there's a doorway
+fn place_doors(map: &mut GameMap, rooms: &[Room], settings: &BspSettings, rng: &mut Rng) {
+    for room in rooms {
+        let left = room.x - 1;
+        let right = room.x + room.width;
+        let top = room.y - 1;
+        let bottom = room.y + room.height;
+        let mut ring: Vec<(u16, u16, bool)> = Vec::new();
+        for x in room.x..right {
+            ring.push((x, top, true));
+            ring.push((x, bottom, true));
+        }
+        for y in room.y..bottom {
+            ring.push((left, y, false));
+            ring.push((right, y, false));
+        }
+
+        for (x, y, horizontal) in ring {
+            if *map.get_tile(x, y) != Tile::Floor {
+                continue;
+            }
+            let walled = if horizontal {
+                *map.get_tile(x - 1, y) == Tile::Wall && *map.get_tile(x + 1, y) == Tile::Wall
+            } else {
+                *map.get_tile(x, y - 1) == Tile::Wall && *map.get_tile(x, y + 1) == Tile::Wall
+            };
+            if !walled || !rng.chance(RngStream::MapGen, settings.door_chance) {
+                continue;
+            }
+            let door = if rng.chance(RngStream::MapGen, settings.open_door_chance) { Tile::DoorOpen } else { Tile::DoorClosed };
+            map.set_tile(x, y, door);
+        }
+    }
+}
+
+pub fn generate(id: MapID, width: u16, height: u16, settings: &BspSettings, rng: &mut Rng) -> GeneratedMap {
+    let mut map = GameMap::new(id, width, height);
+    map_gen::fill(&mut map, Tile::Wall);
+
+    // Keeps splitting the biggest area that can be split until there are
+    // enough to hold all the rooms
+    let mut nodes: Vec<Node> = vec![Node { area: Room::new(0, 0, width, height), children: None, room: None }];
+    let mut leaves: Vec<usize> = vec![0];
+    while leaves.len() < settings.max_rooms {
+        leaves.sort_by_key(|index| nodes[*index].area.width as u32 * nodes[*index].area.height as u32);
+        let mut split = false;
+        for position in (0..leaves.len()).rev() {
+            let index = leaves[position];
+            let maybe_halves = split_area(&nodes[index].area, settings, rng);
+            if maybe_halves.is_none() {
+                continue;
+            }
+            let (first, second) = maybe_halves.unwrap();
+            nodes.push(Node { area: first, children: None, room: None });
+            nodes.push(Node { area: second, children: None, room: None });
+            nodes[index].children = Some((nodes.len() - 2, nodes.len() - 1));
+            leaves.remove(position);
+            leaves.push(nodes.len() - 2);
+            leaves.push(nodes.len() - 1);
+            split = true;
+            break;
+        }
+        if !split {
+            break;
+        }
+    }
+
+    let mut rooms: Vec<Room> = Vec::new();
+    for node in nodes.iter_mut() {
+        let area = node.area;
+        if node.children.is_some() || area.width < settings.min_room_size + 2 || area.height < settings.min_room_size + 2 {
+            continue;
+        }
+        let room = make_room(&area, settings, rng);
+        map_gen::carve_room(&mut map, &room);
+        node.room = Some(room);
+        rooms.push(room);
+    }
+
+    for index in 0..nodes.len() {
+        if nodes[index].children.is_none() {
+            continue;
+        }
+        let (left, right) = nodes[index].children.unwrap();
+        let left_rooms = get_rooms_under(&nodes, left);
+        let right_rooms = get_rooms_under(&nodes, right);
+        if left_rooms.is_empty() || right_rooms.is_empty() {
+            continue;
+        }
+        let (from, to) = get_closest_rooms(&left_rooms, &right_rooms);
+        carve_corridor(&mut map, from.center(), to.center(), settings.corridor_style, rng);
+    }
+    place_doors(&mut map, &rooms, settings, rng);
+
+    // Up in the first room and down in whichever is furthest from it
+    let mut stairs_up: Option<(u16, u16)> = None;
+    let mut stairs_down: Option<(u16, u16)> = None;
+    if !rooms.is_empty() {
+        let up = rooms[0].center();
+        let down = rooms.iter().map(|room| room.center())
+            .max_by_key(|(x, y)| pathfinding::get_distance(up.0, up.1, *x, *y))
+            .unwrap();
+        map.set_tile(up.0, up.1, Tile::StairUp);
+        stairs_up = Some(up);
+        if down != up {
+            map.set_tile(down.0, down.1, Tile::StairDown);
+            stairs_down = Some(down);
+        }
+    }
+
+    return GeneratedMap {
+        map,
+        rooms,
+        stairs_up,
+        stairs_down,
+        spawns: Vec::new(),
+        vaults: Vec::new(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dijkstra::DijkstraMap;
+
+    #[test]
+    fn test_rooms_are_connected() {
+        for (seed, depth, style) in [(1, 1, CorridorStyle::Bent), (2, WINDING_DEPTH, CorridorStyle::Winding), (3, 2, CorridorStyle::Bent)] {
+            let mut rng = Rng::new(seed);
+            let settings = BspSettings::for_depth(depth);
+            assert_eq!(settings.corridor_style, style);
+            let generated = generate(0, 80, 40, &settings, &mut rng);
+            let map = &generated.map;
+            assert!(generated.rooms.len() >= 4);
+            assert!(generated.rooms.len() <= settings.max_rooms);
+            for room in &generated.rooms {
+                assert!(room.width >= settings.min_room_size && room.width <= settings.max_room_size);
+                assert!(room.x > 0 && room.x + room.width < map.width);
+            }
+
+            let (up_x, up_y) = generated.stairs_up.unwrap();
+            let (down_x, down_y) = generated.stairs_down.unwrap();
+            assert_eq!(*map.get_tile(up_x, up_y), Tile::StairUp);
+            assert_eq!(*map.get_tile(down_x, down_y), Tile::StairDown);
+
+            // Every open tile can be reached from the stairs up
+            let distances = DijkstraMap::new(map, &[(up_x, up_y, 0)]);
+            for index in 0..map.tiles.len() {
+                let (x, y) = map.index_to_coordinates(index);
+                if *map.get_tile(x, y) != Tile::Wall {
+                    assert!(distances.get_distance(map, x, y).is_some());
+                }
+            }
+        }
+
+        let first = generate(0, 80, 40, &BspSettings::new(), &mut Rng::new(7));
+        let second = generate(0, 80, 40, &BspSettings::new(), &mut Rng::new(7));
+        assert_eq!(first.map.tiles, second.map.tiles);
+    }
+}
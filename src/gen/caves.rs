@@ -0,0 +1,376 @@
+use std::collections::VecDeque;
+
+use crate::{dijkstra::{DijkstraMap, UNREACHABLE}, lighting::LightLevel, map::{GameMap, MapID, Tile}, pathfinding::{self, NEIGHBOR_OFFSETS}, rng::{Rng, RngStream}};
+
+use super::map_gen::{self, GeneratedMap};
+
+const MAX_ATTEMPTS: usize = 10;
+// Lakes and magma only go into a cave with this much of the map open, as a
+// fraction, so there's still room to walk around them
+const OPEN_NUMERATOR: usize = 1;
+const OPEN_DENOMINATOR: usize = 3;
+// A lake never floods more than this fraction of the floor that's left
+const LAKE_DENOMINATOR: usize = 4;
+// Half-size of the chamber dug out of a cave that didn't open up at all
+const CHAMBER_RADIUS: u16 = 2;
+
+pub struct CaveSettings {
+    // Percent of the map that starts out as rock before smoothing
+    pub fill_percent: u32,
+    pub smoothing_passes: u8,
+    pub lake_count: u8,
+    // The most tiles a lake floods
+    pub lake_size: usize,
+    // Percent chance of a river of magma running across the cave
+    pub magma_chance: u32,
+    pub web_pockets: u8,
+}
+
+impl CaveSettings {
+    pub fn new() -> Self {
+        Self {
+            fill_percent: 45,
+            smoothing_passes: 5,
+            lake_count: 2,
+            lake_size: 40,
+            magma_chance: 30,
+            web_pockets: 3,
+        }
+    }
+}
+
+// How many of the tiles around one are rock, with anything off the map
+// counting as rock
+fn count_walls(walls: &[bool], map: &GameMap, x: u16, y: u16) -> usize {
+    let mut result = 0;
+    for offset in NEIGHBOR_OFFSETS {
+        let is_wall = match pathfinding::get_neighbor(map, x, y, offset) {
+            Some((next_x, next_y)) => walls[map.coordinates_to_index(next_x, next_y)],
+            None => true,
+        };
+        if is_wall {
+            result += 1;
+        }
+    }
+    return result;
+}
+
+// Fills the map with random rock, then lets each tile take after its
+// neighbors until the noise settles into caverns
+fn grow_caverns(map: &mut GameMap, settings: &CaveSettings, rng: &mut Rng) {
+    let mut walls: Vec<bool> = (0..map.tiles.len()).map(|index| {
+        let (x, y) = map.index_to_coordinates(index);
+        x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1 || rng.chance(RngStream::MapGen, settings.fill_percent)
+    }).collect();
+
+    for _ in 0..settings.smoothing_passes {
+        let mut next = walls.clone();
+        for (index, wall) in next.iter_mut().enumerate() {
+            let (x, y) = map.index_to_coordinates(index);
+            if x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1 {
+                continue;
+            }
+            let neighbors = count_walls(&walls, map, x, y);
+            if neighbors >= 5 {
+                *wall = true;
+            } else if neighbors < 4 {
+                *wall = false;
+            }
+        }
+        walls = next;
+    }
+
+    for (index, wall) in walls.iter().enumerate() {
+        let (x, y) = map.index_to_coordinates(index);
+        map.set_tile(x, y, if *wall { Tile::Wall } else { Tile::Floor });
+    }
+}
+
+// Every tile that can be walked to from a tile without crossing anything
+// the caller rules out
+fn flood(map: &GameMap, start: usize, can_enter: impl Fn(Tile) -> bool) -> Vec<usize> {
+    let mut seen: Vec<bool> = vec![false; map.tiles.len()];
+    let mut result: Vec<usize> = Vec::new();
+    let mut pending: VecDeque<usize> = VecDeque::from([start]);
+    seen[start] = true;
+    while let Some(index) = pending.pop_front() {
+        result.push(index);
+        let (x, y) = map.index_to_coordinates(index);
+        for offset in NEIGHBOR_OFFSETS {
+            let maybe_neighbor = pathfinding::get_neighbor(map, x, y, offset);
+            if maybe_neighbor.is_none() {
+                continue;
+            }
+            let (next_x, next_y) = maybe_neighbor.unwrap();
+            let next_index = map.coordinates_to_index(next_x, next_y);
+            if !seen[next_index] && can_enter(map.tiles[next_index]) {
+                seen[next_index] = true;
+                pending.push_back(next_index);
+            }
+        }
+    }
+    return result;
+}
+
+// Fills in every cavern but the biggest, so the whole cave is connected
+fn remove_disconnected(map: &mut GameMap) {
+    let mut region_of: Vec<Option<usize>> = vec![None; map.tiles.len()];
+    let mut regions: Vec<Vec<usize>> = Vec::new();
+    for index in 0..map.tiles.len() {
+        if map.tiles[index] != Tile::Floor || region_of[index].is_some() {
+            continue;
+        }
+        let region = flood(map, index, |tile| tile == Tile::Floor);
+        for tile in &region {
+            region_of[*tile] = Some(regions.len());
+        }
+        regions.push(region);
+    }
+
+    let maybe_biggest = (0..regions.len()).max_by_key(|region| regions[*region].len());
+    for (region, tiles) in regions.iter().enumerate() {
+        if Some(region) == maybe_biggest {
+            continue;
+        }
+        for index in tiles {
+            let (x, y) = map.index_to_coordinates(*index);
+            map.set_tile(x, y, Tile::Wall);
+        }
+    }
+}
+
+fn count_floor(map: &GameMap) -> usize {
+    return map.tiles.iter().filter(|tile| **tile == Tile::Floor).count();
+}
+
+fn is_roomy(map: &GameMap) -> bool {
+    return count_floor(map) * OPEN_DENOMINATOR >= map.tiles.len() * OPEN_NUMERATOR;
+}
+
+// Opens up a small chamber in the middle of the map, for when the noise
+// never made room enough for both sets of stairs
+fn dig_chamber(map: &mut GameMap) {
+    let (middle_x, middle_y) = (map.width / 2, map.height / 2);
+    for y in middle_y.saturating_sub(CHAMBER_RADIUS)..=middle_y + CHAMBER_RADIUS {
+        for x in middle_x.saturating_sub(CHAMBER_RADIUS)..=middle_x + CHAMBER_RADIUS {
+            if x > 0 && y > 0 && x < map.width - 1 && y < map.height - 1 {
+                map.set_tile(x, y, Tile::Floor);
+            }
+        }
+    }
+}
+
+fn get_random_floor(map: &GameMap, rng: &mut Rng) -> Option<(u16, u16)> {
+    let floors: Vec<usize> = (0..map.tiles.len()).filter(|index| map.tiles[*index] == Tile::Floor).collect();
+    if floors.is_empty() {
+        return None;
+    }
+    let index = floors[rng.range(RngStream::MapGen, 0, floors.len() as i32 - 1) as usize];
+    return Some(map.index_to_coordinates(index));
+}
+
+// Floods outwards from a random spot, deep in the middle and shallow around
+// the shore
+fn flood_lake(map: &mut GameMap, settings: &CaveSettings, rng: &mut Rng) {
+    let maybe_start = get_random_floor(map, rng);
+    if maybe_start.is_none() {
+        return;
+    }
+    let (start_x, start_y) = maybe_start.unwrap();
+    let mut lake = flood(map, map.coordinates_to_index(start_x, start_y), |tile| tile == Tile::Floor);
+    lake.truncate(usize::min(settings.lake_size, count_floor(map) / LAKE_DENOMINATOR));
+
+    let mut in_lake: Vec<bool> = vec![false; map.tiles.len()];
+    for index in &lake {
+        in_lake[*index] = true;
+    }
+    for index in lake {
+        let (x, y) = map.index_to_coordinates(index);
+        let shore = NEIGHBOR_OFFSETS.iter().any(|offset| match pathfinding::get_neighbor(map, x, y, *offset) {
+            Some((next_x, next_y)) => !in_lake[map.coordinates_to_index(next_x, next_y)],
+            None => true,
+        });
+        map.set_tile(x, y, if shore { Tile::Pool } else { Tile::Water });
+    }
+}
+
+// Runs a river of magma from one side of the cave to the other, wandering
+// up and down as it goes and eating through rock where it meets it. Wherever
+// it cuts part of the cave off, some of it cools to leave a crossing.
+fn run_magma_river(map: &mut GameMap, rng: &mut Rng) {
+    let mut y = rng.range(RngStream::MapGen, 1, map.height as i32 - 2) as u16;
+    let mut river: Vec<(u16, u16)> = Vec::new();
+    for x in 1..map.width - 1 {
+        let tile = *map.get_tile(x, y);
+        if tile == Tile::Floor || tile == Tile::Wall {
+            map.set_tile(x, y, Tile::Magma);
+            river.push((x, y));
+        }
+        if rng.chance(RngStream::MapGen, 40) {
+            let drift = if rng.chance(RngStream::MapGen, 50) { 1 } else { -1 };
+            y = (y as i32 + drift).clamp(1, map.height as i32 - 2) as u16;
+        }
+    }
+
+    let is_open = |tile: Tile| tile != Tile::Wall && tile != Tile::Magma;
+    loop {
+        let maybe_start = (0..map.tiles.len()).find(|index| is_open(map.tiles[*index]));
+        if maybe_start.is_none() {
+            return;
+        }
+        let mut reached: Vec<bool> = vec![false; map.tiles.len()];
+        for index in flood(map, maybe_start.unwrap(), is_open) {
+            reached[index] = true;
+        }
+        let cut_off = (0..map.tiles.len()).any(|index| is_open(map.tiles[index]) && !reached[index]);
+        if !cut_off {
+            return;
+        }
+
+        // Best is a spot with the reachable side on one bank and the cut off
+        // side on the other, but any spot on the reachable bank will do
+        let touches = |x: u16, y: u16, want_reached: bool| NEIGHBOR_OFFSETS.iter().any(|offset| {
+            pathfinding::get_neighbor(map, x, y, *offset).is_some_and(|(next_x, next_y)| {
+                let index = map.coordinates_to_index(next_x, next_y);
+                is_open(map.tiles[index]) && reached[index] == want_reached
+            })
+        });
+        let maybe_crossing = river.iter().position(|(x, y)| touches(*x, *y, true) && touches(*x, *y, false))
+            .or_else(|| river.iter().position(|(x, y)| touches(*x, *y, true)));
+        if maybe_crossing.is_none() {
+            return;
+        }
+        let (x, y) = river.remove(maybe_crossing.unwrap());
+        map.set_tile(x, y, Tile::Floor);
+    }
+}
+
+// Fills a few nooks, tiles mostly surrounded by rock, with webs
+fn spin_webs(map: &mut GameMap, settings: &CaveSettings, rng: &mut Rng) {
+    let walls: Vec<bool> = map.tiles.iter().map(|tile| *tile == Tile::Wall).collect();
+    let mut nooks: Vec<(u16, u16)> = Vec::new();
+    for index in 0..map.tiles.len() {
+        let (x, y) = map.index_to_coordinates(index);
+        if map.tiles[index] == Tile::Floor && count_walls(&walls, map, x, y) >= 5 {
+            nooks.push((x, y));
+        }
+    }
+
+    for _ in 0..settings.web_pockets {
+        if nooks.is_empty() {
+            return;
+        }
+        let (x, y) = nooks.swap_remove(rng.range(RngStream::MapGen, 0, nooks.len() as i32 - 1) as usize);
+        let mut pocket = flood(map, map.coordinates_to_index(x, y), |tile| tile == Tile::Floor);
+        pocket.truncate(4);
+        for index in pocket {
+            let (web_x, web_y) = map.index_to_coordinates(index);
+            map.set_tile(web_x, web_y, Tile::Web);
+        }
+    }
+}
+
+pub fn generate(id: MapID, width: u16, height: u16, settings: &CaveSettings, rng: &mut Rng) -> GeneratedMap {
+    let mut map = GameMap::new(id, width, height);
+    // Unlucky noise can leave only a poky little cavern, so tries again
+    // until the cave takes up a fair share of the map, keeping the best try
+    let mut best: Vec<Tile> = Vec::new();
+    for _ in 0..MAX_ATTEMPTS {
+        map_gen::fill(&mut map, Tile::Wall);
+        grow_caverns(&mut map, settings, rng);
+        remove_disconnected(&mut map);
+        if best.is_empty() || count_floor(&map) > best.iter().filter(|tile| **tile == Tile::Floor).count() {
+            best = map.tiles.clone();
+        }
+        if is_roomy(&map) {
+            break;
+        }
+    }
+    map.tiles = best;
+    if count_floor(&map) < 2 {
+        dig_chamber(&mut map);
+    }
+
+    // A cramped cave is left as it is, since flooding it or cutting it in
+    // two could leave nowhere to put the stairs
+    if is_roomy(&map) {
+        for _ in 0..settings.lake_count {
+            flood_lake(&mut map, settings, rng);
+        }
+        if rng.chance(RngStream::MapGen, settings.magma_chance) {
+            let before = map.tiles.clone();
+            run_magma_river(&mut map, rng);
+            if count_floor(&map) < 2 {
+                map.tiles = before;
+            }
+        }
+    }
+    spin_webs(&mut map, settings, rng);
+
+    // Up anywhere, and down as far from it as the cave allows
+    let mut stairs_up: Option<(u16, u16)> = None;
+    let mut stairs_down: Option<(u16, u16)> = None;
+    if let Some((up_x, up_y)) = get_random_floor(&map, rng) {
+        let distances = DijkstraMap::new(&map, &[(up_x, up_y, 0)]);
+        let maybe_down = (0..map.tiles.len())
+            .filter(|index| map.tiles[*index] == Tile::Floor && distances.distances[*index] != UNREACHABLE)
+            .max_by_key(|index| distances.distances[*index]);
+        map.set_tile(up_x, up_y, Tile::StairUp);
+        stairs_up = Some((up_x, up_y));
+        if let Some(down) = maybe_down.filter(|down| *down != map.coordinates_to_index(up_x, up_y)) {
+            let (down_x, down_y) = map.index_to_coordinates(down);
+            map.set_tile(down_x, down_y, Tile::StairDown);
+            stairs_down = Some((down_x, down_y));
+        }
+    }
+
+    // Caves don't get lit from above
+    map.ambient_light = LightLevel::Dark;
+
+    return GeneratedMap {
+        map,
+        rooms: Vec::new(),
+        stairs_up,
+        stairs_down,
+        spawns: Vec::new(),
+        vaults: Vec::new(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caves_are_connected() {
+        let mut settings = CaveSettings::new();
+        settings.magma_chance = 100;
+        for seed in 0..4 {
+            let generated = generate(0, 60, 30, &settings, &mut Rng::new(seed));
+            let map = &generated.map;
+            let (up_x, up_y) = generated.stairs_up.unwrap();
+            assert!(generated.stairs_down.is_some());
+
+            // Everything open can be reached without wading through magma
+            let reached = flood(map, map.coordinates_to_index(up_x, up_y), |tile| tile != Tile::Wall && tile != Tile::Magma);
+            let open = map.tiles.iter().filter(|tile| **tile != Tile::Wall && **tile != Tile::Magma).count();
+            assert_eq!(reached.len(), open);
+            assert!(open > map.tiles.len() / 4);
+            assert!(map.tiles.contains(&Tile::Water) && map.tiles.contains(&Tile::Magma) && map.tiles.contains(&Tile::Web));
+        }
+
+        let first = generate(0, 60, 30, &CaveSettings::new(), &mut Rng::new(7));
+        let second = generate(0, 60, 30, &CaveSettings::new(), &mut Rng::new(7));
+        assert_eq!(first.map.tiles, second.map.tiles);
+    }
+
+    #[test]
+    fn test_small_caves_always_get_stairs() {
+        let mut settings = CaveSettings::new();
+        settings.magma_chance = 100;
+        for seed in 0..100 {
+            let generated = generate(0, 20, 10, &settings, &mut Rng::new(seed));
+            assert!(generated.stairs_up.is_some() && generated.stairs_down.is_some(), "seed {} has no stairs", seed);
+        }
+    }
+}
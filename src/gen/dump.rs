@@ -0,0 +1,306 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use enum_map::EnumMap;
+
+use crate::{dijkstra::DijkstraMap, game::Game, map::{self, GameMap, Tile}, registry::{self, LevelKind, MapRegistry}, rng::{Rng, Seed}, ui::terminal::icons, weather};
+
+use super::{map_gen::{self, GeneratedMap}, overworld::{self, OverworldSettings}, prefab::Prefab};
+
+// Running with --mapgen generates levels and prints them instead of
+// starting the game, for tuning the generators:
+//
+//   voguelike --mapgen dungeon --seed 42 --size 80x40 --depth 2
+//   voguelike --mapgen cave --seed 1 --count 1000
+//
+// One level gets printed along with its statistics. Any more than one are
+// only checked, with a line for each that comes out disconnected.
+pub struct DumpOptions {
+    pub kind: LevelKind,
+    pub width: u16,
+    pub height: u16,
+    pub depth: u16,
+    // How many seeds to go through, counting up from the first
+    pub count: u32,
+}
+
+// Smallest a level can be and still have room for the generators to work.
+// Any smaller and there's hardly anywhere inland on the overworld to clear
+// for a settlement and an entrance beside it.
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 15;
+
+pub struct LevelStats {
+    pub rooms: usize,
+    // Tiles that can be walked on, and how many of them can be got to from
+    // where the player comes in
+    pub open_tiles: usize,
+    pub reachable_tiles: usize,
+    // Stairs, gates and entrances, and how many of them can be got to
+    pub ways: usize,
+    pub reachable_ways: usize,
+    pub items: usize,
+    pub monsters: usize,
+    pub traps: usize,
+    pub vaults: Vec<String>,
+    pub histogram: EnumMap<Tile, usize>,
+}
+
+impl LevelStats {
+    pub fn is_connected(&self) -> bool {
+        return self.ways > 0 && self.reachable_ways == self.ways;
+    }
+}
+
+pub fn get_level_kind_name(kind: LevelKind) -> &'static str {
+    return match kind {
+        LevelKind::Cave => "cave",
+        LevelKind::Dungeon => "dungeon",
+        LevelKind::Overworld => "overworld",
+        LevelKind::Town => "town",
+    };
+}
+
+fn parse_level_kind(name: &str) -> Option<LevelKind> {
+    return match name {
+        "cave" => Some(LevelKind::Cave),
+        "dungeon" => Some(LevelKind::Dungeon),
+        "overworld" => Some(LevelKind::Overworld),
+        "town" => Some(LevelKind::Town),
+        _ => None,
+    };
+}
+
+// The value after a flag, if the flag is there at all
+fn get_argument<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a String>, String> {
+    let maybe_index = args.iter().position(|arg| arg == flag);
+    if maybe_index.is_none() {
+        return Ok(None);
+    }
+    let maybe_value = args.get(maybe_index.unwrap() + 1);
+    if maybe_value.is_none() {
+        return Err(format!("{} needs a value after it", flag));
+    }
+    return Ok(maybe_value);
+}
+
+fn parse_number<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> Result<T, String> {
+    return match get_argument(args, flag)? {
+        Some(value) => value.parse::<T>().map_err(|_| format!("Not a valid number for {}: {}", flag, value)),
+        None => Ok(default),
+    };
+}
+
+pub fn parse_dump_arguments(args: &[String]) -> Result<Option<DumpOptions>, String> {
+    let maybe_generator = get_argument(args, "--mapgen")?;
+    if maybe_generator.is_none() {
+        return Ok(None);
+    }
+    let maybe_kind = parse_level_kind(maybe_generator.unwrap());
+    if maybe_kind.is_none() {
+        return Err(format!("Not a generator: {} (try cave, dungeon, overworld or town)", maybe_generator.unwrap()));
+    }
+
+    let (mut width, mut height) = (80, 40);
+    if let Some(size) = get_argument(args, "--size")? {
+        let maybe_parsed = size.split_once('x').and_then(|(w, h)| Some((w.parse::<u16>().ok()?, h.parse::<u16>().ok()?)));
+        if maybe_parsed.is_none() {
+            return Err(format!("Not a valid size: {} (it should look like 80x40)", size));
+        }
+        (width, height) = maybe_parsed.unwrap();
+    }
+    if width < MIN_WIDTH || height < MIN_HEIGHT {
+        return Err(format!("Levels need to be at least {}x{}", MIN_WIDTH, MIN_HEIGHT));
+    }
+
+    return Ok(Some(DumpOptions {
+        kind: maybe_kind.unwrap(),
+        width,
+        height,
+        depth: parse_number(args, "--depth", 1)?,
+        count: parse_number(args, "--count", 1)?,
+    }));
+}
+
+// Generates a level the same way the game would, on a game that's only
+// there to hold the seed and the settings
+pub fn generate(game: &mut Game, kind: LevelKind, depth: u16) -> Option<GeneratedMap> {
+    if kind != LevelKind::Overworld {
+        return registry::generate_level(game, kind, depth).map(|level| level.generated);
+    }
+    let (width, height) = (game.maps.level_width, game.maps.level_height);
+    let mut generated = overworld::generate(0, width, height, &OverworldSettings::new(), &mut game.rng);
+    let spawn_rate = weather::get_spawn_rate(game.clock.get_time_of_day());
+    let spawns = map_gen::populate_map(&mut generated.map, &[], kind, 0, spawn_rate, &mut game.rng);
+    return Some(GeneratedMap {
+        map: generated.map,
+        rooms: Vec::new(),
+        stairs_up: generated.settlements.first().cloned(),
+        stairs_down: None,
+        spawns,
+        vaults: Vec::new(),
+    });
+}
+
+pub fn get_stats(generated: &GeneratedMap) -> LevelStats {
+    let map = &generated.map;
+    let mut histogram: EnumMap<Tile, usize> = EnumMap::default();
+    for tile in &map.tiles {
+        histogram[*tile] += 1;
+    }
+
+    let maybe_distances = generated.stairs_up.map(|(x, y)| DijkstraMap::new(map, &[(x, y, 0)]));
+    let is_reachable = |index: usize| {
+        let (x, y) = map.index_to_coordinates(index);
+        return maybe_distances.as_ref().is_some_and(|distances| distances.get_distance(map, x, y).is_some());
+    };
+    let open: Vec<usize> = (0..map.tiles.len()).filter(|index| !map::is_impassable(&map.tiles[*index])).collect();
+    let ways: Vec<usize> = (0..map.tiles.len()).filter(|index| map::is_way_out(&map.tiles[*index])).collect();
+
+    return LevelStats {
+        rooms: generated.rooms.len(),
+        open_tiles: open.len(),
+        reachable_tiles: open.iter().filter(|index| is_reachable(**index)).count(),
+        ways: ways.len(),
+        reachable_ways: ways.iter().filter(|index| is_reachable(**index)).count(),
+        items: map.items.iter().map(|pile| pile.len()).sum(),
+        monsters: generated.spawns.len(),
+        traps: map.traps.iter().filter(|trap| trap.is_some()).count(),
+        vaults: generated.vaults.clone(),
+        histogram,
+    };
+}
+
+// The map drawn with the same characters as on screen, a line for each row
+pub fn render(map: &GameMap) -> String {
+    let mut result = String::with_capacity((map.width as usize + 1) * map.height as usize);
+    for y in 0..map.height {
+        for x in 0..map.width {
+            result.push(icons::tile_icon(map.get_tile(x, y)));
+        }
+        result.push('\n');
+    }
+    return result;
+}
+
+pub fn format_stats(stats: &LevelStats) -> String {
+    let connected = if stats.is_connected() { "yes" } else { "NO" };
+    let tiles: Vec<String> = stats.histogram.iter().filter(|(_, count)| **count > 0).map(|(tile, count)| format!("{:?} {}", tile, count)).collect();
+    let vaults = if stats.vaults.is_empty() { String::from("none") } else { stats.vaults.join(", ") };
+    return format!(
+        "rooms: {}\nconnected: {} ({} of {} ways, {} of {} open tiles reachable)\nitems: {}, monsters: {}, traps: {}\nvaults: {}\ntiles: {}\n",
+        stats.rooms, connected, stats.reachable_ways, stats.ways, stats.reachable_tiles, stats.open_tiles,
+        stats.items, stats.monsters, stats.traps, vaults, tiles.join(", "),
+    );
+}
+
+// Generates a level for each seed and reports on them, returning whether
+// they all came out connected
+pub fn run_dump(options: &DumpOptions, first_seed: Seed, prefabs: Vec<Prefab>) -> bool {
+    let mut game = Game::new();
+    let mut prefabs = prefabs;
+    let name = get_level_kind_name(options.kind);
+    let mut failures: u32 = 0;
+    let (mut rooms, mut items, mut monsters) = (0, 0, 0);
+    // How often each prefab got used, in the order they were loaded
+    let mut vaults: Vec<(String, usize)> = prefabs.iter().map(|prefab| (prefab.name.clone(), 0)).collect();
+
+    for offset in 0..options.count {
+        let seed = first_seed.wrapping_add(offset as Seed);
+        game.rng = Rng::new(seed);
+        game.maps = MapRegistry::new();
+        game.maps.level_width = options.width;
+        game.maps.level_height = options.height;
+        game.maps.prefabs = std::mem::take(&mut prefabs);
+        // A generator that falls over only counts against its own seed
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| generate(&mut game, options.kind, options.depth)));
+        prefabs = std::mem::take(&mut game.maps.prefabs);
+
+        if outcome.is_err() {
+            println!("seed {}: {} panicked", seed, name);
+            failures += 1;
+            continue;
+        }
+        let maybe_generated = outcome.unwrap();
+        if maybe_generated.is_none() {
+            println!("seed {}: {} didn't generate", seed, name);
+            failures += 1;
+            continue;
+        }
+        let generated = maybe_generated.unwrap();
+        let stats = get_stats(&generated);
+        if options.count == 1 {
+            println!("seed {}: {} {}x{} at depth {}", seed, name, options.width, options.height, options.depth);
+            print!("{}", render(&generated.map));
+            print!("{}", format_stats(&stats));
+        } else if !stats.is_connected() {
+            println!("seed {}: disconnected, {} of {} ways reachable", seed, stats.reachable_ways, stats.ways);
+        }
+        if !stats.is_connected() {
+            failures += 1;
+        }
+        rooms += stats.rooms;
+        items += stats.items;
+        monsters += stats.monsters;
+        for name in &stats.vaults {
+            if let Some((_, uses)) = vaults.iter_mut().find(|(vault, _)| vault == name) {
+                *uses += 1;
+            }
+        }
+    }
+
+    if options.count > 1 {
+        let count = options.count as usize;
+        println!(
+            "{} seeds of {} from {}: {} failed, averaging {} rooms, {} items and {} monsters",
+            options.count, name, first_seed, failures, rooms / count, items / count, monsters / count,
+        );
+        let used: Vec<String> = vaults.iter().filter(|(_, uses)| *uses > 0).map(|(vault, uses)| format!("{} {}", vault, uses)).collect();
+        if !used.is_empty() {
+            println!("vaults: {}", used.join(", "));
+        }
+    }
+    return failures == 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_args(text: &str) -> Vec<String> {
+        return text.split_whitespace().map(String::from).collect();
+    }
+
+    #[test]
+    fn test_dump_arguments() {
+        assert!(parse_dump_arguments(&make_args("voguelike --seed 3")).unwrap().is_none());
+        let options = parse_dump_arguments(&make_args("voguelike --mapgen cave --size 60x30 --count 5")).unwrap().unwrap();
+        assert_eq!(options.kind, LevelKind::Cave);
+        assert_eq!((options.width, options.height, options.depth, options.count), (60, 30, 1, 5));
+        assert!(parse_dump_arguments(&make_args("voguelike --mapgen castle")).is_err());
+        assert!(parse_dump_arguments(&make_args("voguelike --mapgen town --size 80by40")).is_err());
+        assert!(parse_dump_arguments(&make_args("voguelike --mapgen town --size 20x10")).is_err());
+        assert!(parse_dump_arguments(&make_args("voguelike --mapgen dungeon --depth")).is_err());
+    }
+
+    #[test]
+    fn test_every_generator_comes_out_connected() {
+        // At the smallest size allowed as well as a roomy one
+        for (width, height) in [(MIN_WIDTH, MIN_HEIGHT), (60, 30)] {
+            for kind in [LevelKind::Cave, LevelKind::Dungeon, LevelKind::Overworld, LevelKind::Town] {
+                let mut game = Game::new();
+                game.maps.level_width = width;
+                game.maps.level_height = height;
+                for seed in 0..10 {
+                    game.rng = Rng::new(seed);
+                    let generated = generate(&mut game, kind, 2).unwrap();
+                    let stats = get_stats(&generated);
+                    assert!(stats.is_connected(), "{} seed {} at {}x{} is disconnected", get_level_kind_name(kind), seed, width, height);
+                    assert_eq!(stats.histogram.values().sum::<usize>(), width as usize * height as usize);
+                    let rendered = render(&generated.map);
+                    assert_eq!(rendered.lines().count(), height as usize);
+                    assert!(rendered.lines().all(|line| line.chars().count() == width as usize));
+                }
+            }
+        }
+    }
+}
@@ -1,32 +1,451 @@
-use crate::{item::{Item, ItemType}, map::{self, GameMap}};
-
-pub fn populate_map(map: &mut GameMap) {
-    let map_height = map.height;
-    let map_width = map.width;
-
-    let y_max: u16 = map_height - 1;
-    let x_max: u16 = map_width - 1;
-    for y in 0..map_height {
-        for x in 0..map_width {
-            if y == 0 || y == y_max || x == 0 || x == x_max {
-                map.set_tile(x, y, map::Tile::Wall);
+use enum_map::{enum_map, EnumMap};
+
+use crate::{item::{self, Item, ItemType}, map::{self, GameMap, Tile}, material::Material, monster::MonsterKind, pathfinding, registry::LevelKind, rng::{Rng, RngStream}, substance::{Liquid, Substance}, trap::TrapKind};
+
+// A rectangle of floor carved out by a generator, not counting its walls
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Room {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Room {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn center(&self) -> (u16, u16) {
+        return (self.x + self.width / 2, self.y + self.height / 2);
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        return x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height;
+    }
+}
+
+// A generated map, along with what later passes need to know about it to
+// fill it in
+pub struct GeneratedMap {
+    pub map: GameMap,
+    pub rooms: Vec<Room>,
+    pub stairs_up: Option<(u16, u16)>,
+    pub stairs_down: Option<(u16, u16)>,
+    // Monsters that have to go in particular places, like a vault's guards
+    pub spawns: Vec<(MonsterKind, u16, u16)>,
+    // The prefabs stamped into the level, by name
+    pub vaults: Vec<String>,
+}
+
+pub fn fill(map: &mut GameMap, tile: Tile) {
+    for y in 0..map.height {
+        for x in 0..map.width {
+            map.set_tile(x, y, tile);
+        }
+    }
+}
+
+pub fn carve_room(map: &mut GameMap, room: &Room) {
+    for y in room.y..room.y + room.height {
+        for x in room.x..room.x + room.width {
+            map.set_tile(x, y, Tile::Floor);
+        }
+    }
+}
+
+// How much treasure a level gets for each level down and each item it has
+// room for, in copper pieces
+const ITEM_BUDGET_PER_DEPTH: u32 = 75;
+// How much open ground there is for each item a level tries to place
+const OPEN_TILES_PER_ITEM: usize = 16;
+const MAX_TRAPS: usize = 6;
+// Percent chance for a room to have monsters in it during the day
+const MONSTER_ROOM_CHANCE: u32 = 50;
+// How much open ground there is for each monster on maps without rooms
+const OPEN_TILES_PER_MONSTER: usize = 300;
+// How close monsters and traps can be to the ways in and out of a level
+const CLEAR_RADIUS: u32 = 3;
+
+// How likely each kind of monster is to turn up, by where and how deep
+pub fn get_monster_weights(kind: LevelKind, depth: u16) -> EnumMap<MonsterKind, u32> {
+    return match kind {
+        LevelKind::Cave => enum_map! {
+            MonsterKind::Bat => 30,
+            MonsterKind::GiantRat => 20,
+            MonsterKind::GiantSpider => if depth >= 2 { 20 } else { 5 },
+            MonsterKind::Goblin => 10,
+            MonsterKind::Kobold => 20,
+            MonsterKind::Ogre => if depth >= 3 { 10 } else { 0 },
+            MonsterKind::Orc => if depth >= 3 { 5 } else { 0 },
+            MonsterKind::Skeleton => 0,
+            MonsterKind::Wolf => 5,
+            MonsterKind::Zombie => 0,
+        },
+        LevelKind::Dungeon => enum_map! {
+            MonsterKind::Bat => 5,
+            MonsterKind::GiantRat => if depth <= 2 { 30 } else { 10 },
+            MonsterKind::GiantSpider => if depth >= 3 { 10 } else { 0 },
+            MonsterKind::Goblin => 20,
+            MonsterKind::Kobold => if depth <= 3 { 25 } else { 10 },
+            MonsterKind::Ogre => if depth >= 4 { 10 } else { 0 },
+            MonsterKind::Orc => if depth >= 2 { 15 } else { 0 },
+            MonsterKind::Skeleton => 15,
+            MonsterKind::Wolf => 0,
+            MonsterKind::Zombie => if depth >= 2 { 15 } else { 0 },
+        },
+        LevelKind::Overworld => enum_map! {
+            MonsterKind::Bat => 10,
+            MonsterKind::GiantRat => 5,
+            MonsterKind::GiantSpider => 5,
+            MonsterKind::Goblin => 20,
+            MonsterKind::Kobold => 10,
+            MonsterKind::Ogre => 5,
+            MonsterKind::Orc => 10,
+            MonsterKind::Skeleton => 0,
+            MonsterKind::Wolf => 30,
+            MonsterKind::Zombie => 0,
+        },
+        // The town watch keeps the streets clear
+        LevelKind::Town => EnumMap::default(),
+    };
+}
+
+// How likely each kind of item is to be found lying around. The better
+// things only turn up further down.
+pub fn get_item_weights(kind: LevelKind, depth: u16) -> Vec<(ItemType, u32)> {
+    let mut result = match kind {
+        LevelKind::Cave => vec![
+            (ItemType::Coin, 15),
+            (ItemType::Crystal, 15),
+            (ItemType::Dagger, 6),
+            (ItemType::Pickaxe, 4),
+            (ItemType::Rations, 10),
+            (ItemType::Rope, 8),
+            (ItemType::Spear, 4),
+            (ItemType::Torch, 15),
+            (ItemType::Vial, 8),
+        ],
+        LevelKind::Dungeon => vec![
+            (ItemType::Book, 4),
+            (ItemType::Candle, 10),
+            (ItemType::Coin, 25),
+            (ItemType::Crystal, 4),
+            (ItemType::Dagger, 10),
+            (ItemType::Flask, 6),
+            (ItemType::LeatherArmor, 6),
+            (ItemType::Mace, 6),
+            (ItemType::Rations, 15),
+            (ItemType::Rope, 5),
+            (ItemType::Shield, 5),
+            (ItemType::Shortsword, 8),
+            (ItemType::ThievesTools, 3),
+            (ItemType::Torch, 20),
+            (ItemType::Vial, 10),
+        ],
+        // Nothing worth having gets left lying about in the open
+        LevelKind::Overworld => Vec::new(),
+        LevelKind::Town => Vec::new(),
+    };
+    if kind == LevelKind::Dungeon && depth >= 2 {
+        result.extend([(ItemType::ChainShirtArmor, 4), (ItemType::Longsword, 6)]);
+    }
+    if kind != LevelKind::Overworld && kind != LevelKind::Town && depth >= 3 {
+        result.extend([(ItemType::Amulet, 3), (ItemType::Ring, 3), (ItemType::Spellbook, 2)]);
+    }
+    return result;
+}
+
+pub fn get_trap_weights(kind: LevelKind, depth: u16) -> EnumMap<TrapKind, u32> {
+    return match kind {
+        // Caves only have whatever the kobolds dug
+        LevelKind::Cave => enum_map! {
+            TrapKind::Alarm => 0,
+            TrapKind::Dart => 0,
+            TrapKind::Fire => 0,
+            TrapKind::Net => 20,
+            TrapKind::Pit => 40,
+        },
+        LevelKind::Dungeon => enum_map! {
+            TrapKind::Alarm => 20,
+            TrapKind::Dart => 25,
+            TrapKind::Fire => if depth >= 2 { 15 } else { 0 },
+            TrapKind::Net => 20,
+            TrapKind::Pit => 25,
+        },
+        LevelKind::Overworld => EnumMap::default(),
+        LevelKind::Town => EnumMap::default(),
+    };
+}
+
+// Rolls on a weighted table, or gives nothing if everything has no chance
+pub fn pick_weighted<T: Copy>(weights: &[(T, u32)], stream: RngStream, rng: &mut Rng) -> Option<T> {
+    let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let roll = rng.range(stream, 0, total as i32 - 1) as u32;
+    let mut running: u32 = 0;
+    for (thing, weight) in weights {
+        running += weight;
+        if roll < running {
+            return Some(*thing);
+        }
+    }
+    return None;
+}
+
+// Steel gear turns up in whatever the smith had to hand, with silver only
+// showing up further down
+fn pick_material(item_type: ItemType, depth: u16, rng: &mut Rng) -> Material {
+    let default = item::get_default_material(item_type);
+    if default != Material::Steel {
+        return default;
+    }
+    let weights = [
+        (Material::Steel, 70),
+        (Material::Iron, 20),
+        (Material::Bronze, 10),
+        (Material::Silver, if depth >= 3 { 5 } else { 0 }),
+    ];
+    return pick_weighted(&weights, RngStream::Loot, rng).unwrap();
+}
+
+// Fills in the details of a found item, like how many there are or what's
+// in it
+fn make_item(item_type: ItemType, depth: u16, rng: &mut Rng) -> Item {
+    return match item_type {
+        ItemType::Candle => Item::with_quantity(item_type, rng.range(RngStream::Loot, 1, 3) as u32),
+        ItemType::Coin => Item::with_quantity(item_type, rng.range(RngStream::Loot, 5, 25 * u16::max(depth, 1) as i32) as u32),
+        ItemType::Flask => Item::with_liquid(item_type, Liquid::new(Substance::Oil, 16)),
+        ItemType::Torch => Item::with_quantity(item_type, rng.range(RngStream::Loot, 1, 3) as u32),
+        ItemType::Vial => {
+            let greater = depth >= 4 && rng.chance(RngStream::Loot, 30);
+            let substance = if greater { Substance::PotionOfGreaterHealing } else { Substance::PotionOfHealing };
+            Item::with_liquid(item_type, Liquid::new(substance, 4))
+        },
+        _ => Item::with_material(item_type, pick_material(item_type, depth, rng)),
+    };
+}
+
+// What an item counts for against a level's treasure, in copper pieces
+fn get_value(item: &Item) -> u32 {
+    if item.item_type == ItemType::Coin {
+        return item.quantity;
+    }
+    return u32::max(1, item::get_price(item).in_copper());
+}
+
+// Ground that things can be left on and monsters can stand on
+pub fn is_open_ground(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Floor | Tile::Forest | Tile::Hills | Tile::Plains | Tile::Road);
+}
+
+// Picks somewhere from the open tiles, in a random room if there are any
+fn pick_spot(open: &[(u16, u16)], rooms: &[Room], rng: &mut Rng) -> Option<usize> {
+    if rooms.is_empty() {
+        if open.is_empty() {
+            return None;
+        }
+        return Some(rng.range(RngStream::MapGen, 0, open.len() as i32 - 1) as usize);
+    }
+    let room = rooms[rng.range(RngStream::MapGen, 0, rooms.len() as i32 - 1) as usize];
+    return pick_spot_in(open, &room, rng);
+}
+
+fn pick_spot_in(open: &[(u16, u16)], room: &Room, rng: &mut Rng) -> Option<usize> {
+    let inside: Vec<usize> = (0..open.len()).filter(|index| room.contains(open[*index].0, open[*index].1)).collect();
+    if inside.is_empty() {
+        return None;
+    }
+    return Some(inside[rng.range(RngStream::MapGen, 0, inside.len() as i32 - 1) as usize]);
+}
+
+// How many items a level tries to place, so that bigger maps have more to find
+fn get_item_count(open_tiles: usize) -> usize {
+    return open_tiles / OPEN_TILES_PER_ITEM;
+}
+
+// Leaves treasure about the rooms until the level's budget is spent
+fn place_items(map: &mut GameMap, open: &[(u16, u16)], rooms: &[Room], kind: LevelKind, depth: u16, rng: &mut Rng) {
+    let weights = get_item_weights(kind, depth);
+    let count = get_item_count(open.len());
+    let mut budget = ITEM_BUDGET_PER_DEPTH * u16::max(depth, 1) as u32 * count as u32;
+    for _ in 0..count {
+        let maybe_item_type = pick_weighted(&weights, RngStream::Loot, rng);
+        if maybe_item_type.is_none() || budget == 0 {
+            return;
+        }
+        let item = make_item(maybe_item_type.unwrap(), depth, rng);
+        let value = get_value(&item);
+        let maybe_spot = pick_spot(open, rooms, rng);
+        if value > budget || maybe_spot.is_none() {
+            continue;
+        }
+        budget -= value;
+        let (x, y) = open[maybe_spot.unwrap()];
+        map.get_items_mut(x, y).push(item);
+    }
+}
+
+fn place_traps(map: &mut GameMap, open: &mut Vec<(u16, u16)>, kind: LevelKind, depth: u16, rng: &mut Rng) {
+    let weights: Vec<(TrapKind, u32)> = get_trap_weights(kind, depth).into_iter().collect();
+    let count = usize::min(depth as usize + 1, MAX_TRAPS);
+    for _ in 0..count {
+        let maybe_trap = pick_weighted(&weights, RngStream::MapGen, rng);
+        let floors: Vec<usize> = (0..open.len()).filter(|index| *map.get_tile(open[*index].0, open[*index].1) == Tile::Floor).collect();
+        if maybe_trap.is_none() || floors.is_empty() {
+            return;
+        }
+        let index = floors[rng.range(RngStream::MapGen, 0, floors.len() as i32 - 1) as usize];
+        let (x, y) = open.swap_remove(index);
+        map.set_trap(x, y, maybe_trap);
+    }
+}
+
+// Groups of monsters in some of the rooms, or scattered about if there are
+// no rooms. More turn up at night.
+// How many monsters roam a map with no rooms to keep them in, like the
+// overworld, at a given spawn rate
+pub fn get_wandering_population(open_tiles: usize, spawn_rate: u32) -> usize {
+    return open_tiles / OPEN_TILES_PER_MONSTER * spawn_rate as usize / 100;
+}
+
+fn place_monsters(map: &GameMap, open: &mut Vec<(u16, u16)>, rooms: &[Room], kind: LevelKind, depth: u16, spawn_rate: u32, rng: &mut Rng) -> Vec<(MonsterKind, u16, u16)> {
+    let mut result: Vec<(MonsterKind, u16, u16)> = Vec::new();
+    let weights: Vec<(MonsterKind, u32)> = get_monster_weights(kind, depth).into_iter().collect();
+    let largest_group = i32::min(4, 1 + depth as i32 / 2);
+
+    if rooms.is_empty() {
+        let count = get_wandering_population(open.len(), spawn_rate);
+        for _ in 0..count {
+            let maybe_kind = pick_weighted(&weights, RngStream::MapGen, rng);
+            let maybe_spot = pick_spot(open, rooms, rng);
+            if maybe_kind.is_none() || maybe_spot.is_none() {
+                break;
             }
-            else {
-                map.set_tile(x, y, map::Tile::Floor);
+            let (x, y) = open.swap_remove(maybe_spot.unwrap());
+            result.push((maybe_kind.unwrap(), x, y));
+        }
+        return result;
+    }
+
+    let chance = u32::min(100, MONSTER_ROOM_CHANCE * spawn_rate / 100);
+    for room in rooms {
+        // Nothing waits right where the player comes in
+        let is_arrival = (room.y..room.y + room.height).any(|y| (room.x..room.x + room.width).any(|x| *map.get_tile(x, y) == Tile::StairUp));
+        if is_arrival || !rng.chance(RngStream::MapGen, chance) {
+            continue;
+        }
+        let maybe_kind = pick_weighted(&weights, RngStream::MapGen, rng);
+        if maybe_kind.is_none() {
+            break;
+        }
+        for _ in 0..rng.range(RngStream::MapGen, 1, largest_group) {
+            let maybe_spot = pick_spot_in(open, room, rng);
+            if maybe_spot.is_none() {
+                break;
             }
+            let (x, y) = open.swap_remove(maybe_spot.unwrap());
+            result.push((maybe_kind.unwrap(), x, y));
         }
     }
+    return result;
+}
+
+// Stocks a freshly generated map with items, traps and monsters from the
+// tables for its kind of level and depth. Items go on the map straight
+// away, and the monsters are handed back to be spawned once it's the
+// current map. Nothing goes on the ways in and out, and monsters and traps
+// keep their distance from them.
+pub fn populate_map(map: &mut GameMap, rooms: &[Room], kind: LevelKind, depth: u16, spawn_rate: u32, rng: &mut Rng) -> Vec<(MonsterKind, u16, u16)> {
+    let mut open: Vec<(u16, u16)> = (0..map.tiles.len())
+        .filter(|index| is_open_ground(&map.tiles[*index]) && map.items[*index].is_empty())
+        .map(|index| map.index_to_coordinates(index))
+        .collect();
+    place_items(map, &open, rooms, kind, depth, rng);
+
+    let ways: Vec<(u16, u16)> = (0..map.tiles.len())
+        .filter(|index| map::is_way_out(&map.tiles[*index]))
+        .map(|index| map.index_to_coordinates(index))
+        .collect();
+    open.retain(|(x, y)| {
+        ways.iter().all(|(way_x, way_y)| pathfinding::get_distance(*x, *y, *way_x, *way_y) > CLEAR_RADIUS) && map.get_items(*x, *y).is_empty()
+    });
+    place_traps(map, &mut open, kind, depth, rng);
+    return place_monsters(map, &mut open, rooms, kind, depth, spawn_rate, rng);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::bsp::{self, BspSettings};
+
+    #[test]
+    fn test_population_follows_tables() {
+        let populate = |seed, depth| {
+            let mut rng = Rng::new(seed);
+            let mut generated = bsp::generate(0, 80, 40, &BspSettings::new(), &mut rng);
+            let spawns = populate_map(&mut generated.map, &generated.rooms, LevelKind::Dungeon, depth, 100, &mut rng);
+            return (generated, spawns);
+        };
 
-    //TODO(ches) remove the random items
-    //TODO(ches) store items in components instead?
+        for seed in 0..4 {
+            let (generated, spawns) = populate(seed, 1);
+            let map = &generated.map;
+            let weights = get_monster_weights(LevelKind::Dungeon, 1);
+            assert!(!spawns.is_empty());
+            let stairs: Vec<(u16, u16)> = generated.stairs_up.iter().chain(generated.stairs_down.iter()).cloned().collect();
+            for (kind, x, y) in &spawns {
+                assert!(weights[*kind] > 0);
+                assert!(generated.rooms.iter().any(|room| room.contains(*x, *y)));
+                assert!(stairs.iter().all(|(stairs_x, stairs_y)| pathfinding::get_distance(*x, *y, *stairs_x, *stairs_y) > CLEAR_RADIUS));
+            }
+
+            // The treasure stays within budget and off the stairs
+            let items: Vec<&Item> = map.items.iter().flatten().collect();
+            assert!(!items.is_empty());
+            let open_tiles = map.tiles.iter().filter(|tile| is_open_ground(tile)).count();
+            assert!(items.iter().map(|item| get_value(item)).sum::<u32>() <= ITEM_BUDGET_PER_DEPTH * get_item_count(open_tiles) as u32);
+            for (x, y) in &stairs {
+                assert!(map.get_items(*x, *y).is_empty());
+            }
+            let traps: Vec<usize> = (0..map.traps.len()).filter(|index| map.traps[*index].is_some()).collect();
+            assert_eq!(traps.len(), 2);
+            assert!(traps.iter().all(|index| map.tiles[*index] == Tile::Floor));
+        }
 
-    map.get_items_mut(2, 1).push(Item{item_type: ItemType::Abacus});
-    map.get_items_mut(7, 1).push(Item{item_type: ItemType::LightCrossbow});
-    map.get_items_mut(5, 1).push(Item{item_type: ItemType::Crystal});
-    map.get_items_mut(8, 2).push(Item{item_type: ItemType::Coin});
-    map.get_items_mut(5, 2).push(Item{item_type: ItemType::Waterskin});
-    map.get_items_mut(2, 3).push(Item{item_type: ItemType::Lute});
-    map.get_items_mut(3, 4).push(Item{item_type: ItemType::Dagger});
-    map.get_items_mut(10, 8).push(Item{item_type: ItemType::CrossbowBolt});
+        // Steel weapons don't all come out of the same forge
+        let materials: Vec<Material> = (0..200).map(|seed| make_item(ItemType::Dagger, 3, &mut Rng::new(seed)).material).collect();
+        assert!(materials.contains(&Material::Steel) && materials.contains(&Material::Bronze));
+        assert_eq!(make_item(ItemType::Rope, 3, &mut Rng::new(0)).material, item::get_default_material(ItemType::Rope));
 
-}
\ No newline at end of file
+        // The same seed stocks a level the same way
+        let (first, first_spawns) = populate(9, 3);
+        let (second, second_spawns) = populate(9, 3);
+        assert_eq!(first_spawns, second_spawns);
+        assert_eq!(first.map.traps, second.map.traps);
+        let item_types = |generated: &GeneratedMap| generated.map.items.iter().flatten().map(|item| item.item_type).collect::<Vec<ItemType>>();
+        assert_eq!(item_types(&first), item_types(&second));
+    }
+
+    #[test]
+    fn test_bigger_maps_have_more_items() {
+        let count_items = |width, height| {
+            let mut total = 0;
+            for seed in 0..4 {
+                let mut map = GameMap::new(0, width, height);
+                fill(&mut map, Tile::Floor);
+                populate_map(&mut map, &[], LevelKind::Dungeon, 1, 100, &mut Rng::new(seed));
+                total += map.items.iter().flatten().count();
+            }
+            return total;
+        };
+        assert!(count_items(160, 80) > 2 * count_items(80, 40));
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{dijkstra::DijkstraMap, lighting::LightLevel, map::{GameMap, MapID, Tile}, pathfinding::{self, NEIGHBOR_OFFSETS}, rng::{Rng, RngStream}};
+
+// Each tile of the overworld is a few hundred yards across, which takes
+// about five minutes to walk
+pub const ROUNDS_PER_TILE: u32 = 50;
+
+pub struct OverworldSettings {
+    // How far apart the hills and valleys are, in tiles
+    pub feature_size: u16,
+    // Elevations run from 0 to 100. Below the water level is lakes and sea,
+    // and above the hill and mountain levels is high ground.
+    pub water_level: i32,
+    pub hill_level: i32,
+    pub mountain_level: i32,
+    // Moistures also run from 0 to 100, from dry plains up to swamp
+    pub forest_moisture: i32,
+    pub swamp_moisture: i32,
+    pub settlements: usize,
+    pub entrances: usize,
+}
+
+impl OverworldSettings {
+    pub fn new() -> Self {
+        Self {
+            feature_size: 16,
+            water_level: 25,
+            hill_level: 68,
+            mountain_level: 82,
+            forest_moisture: 62,
+            swamp_moisture: 78,
+            settlements: 4,
+            entrances: 4,
+        }
+    }
+}
+
+// A generated overworld, along with where the settlements and the ways down
+// into the levels below are
+pub struct GeneratedOverworld {
+    pub map: GameMap,
+    pub settlements: Vec<(u16, u16)>,
+    pub entrances: Vec<(u16, u16)>,
+}
+
+fn smooth_step(t: f64) -> f64 {
+    return t * t * (3.0 - 2.0 * t);
+}
+
+// Smooth random noise from 0 to 100 for every tile, made by blending random
+// values on a coarse grid and adding finer grids on top for detail
+fn make_noise(width: u16, height: u16, feature_size: u16, rng: &mut Rng) -> Vec<i32> {
+    let mut values: Vec<f64> = vec![0.0; width as usize * height as usize];
+    let mut cell = f64::max(2.0, feature_size as f64);
+    let mut weight = 1.0;
+    while cell >= 2.0 {
+        let grid_width = (width as f64 / cell) as usize + 2;
+        let grid_height = (height as f64 / cell) as usize + 2;
+        let grid: Vec<f64> = (0..grid_width * grid_height).map(|_| rng.range(RngStream::MapGen, 0, 1000) as f64 / 1000.0).collect();
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let grid_x = x as f64 / cell;
+                let grid_y = y as f64 / cell;
+                let (left, top) = (grid_x as usize, grid_y as usize);
+                let across = smooth_step(grid_x - left as f64);
+                let down = smooth_step(grid_y - top as f64);
+                let corner = |corner_x: usize, corner_y: usize| grid[corner_y * grid_width + corner_x];
+                let upper = corner(left, top) + (corner(left + 1, top) - corner(left, top)) * across;
+                let lower = corner(left, top + 1) + (corner(left + 1, top + 1) - corner(left, top + 1)) * across;
+                values[y * width as usize + x] += (upper + (lower - upper) * down) * weight;
+            }
+        }
+        cell /= 2.0;
+        weight /= 2.0;
+    }
+
+    // Stretched out to cover the whole range, so the levels in the settings
+    // mean the same on every map
+    let lowest = values.iter().cloned().fold(f64::MAX, f64::min);
+    let highest = values.iter().cloned().fold(f64::MIN, f64::max);
+    let span = f64::max(highest - lowest, f64::EPSILON);
+    return values.iter().map(|value| ((value - lowest) / span * 100.0).round() as i32).collect();
+}
+
+fn get_biome(elevation: i32, moisture: i32, settings: &OverworldSettings) -> Tile {
+    if elevation < settings.water_level {
+        return Tile::Water;
+    }
+    if elevation >= settings.mountain_level {
+        return Tile::Mountain;
+    }
+    if elevation >= settings.hill_level {
+        return Tile::Hills;
+    }
+    // Water pools in the low ground
+    if moisture >= settings.swamp_moisture && elevation < (settings.water_level + settings.hill_level) / 2 {
+        return Tile::Swamp;
+    }
+    if moisture >= settings.forest_moisture {
+        return Tile::Forest;
+    }
+    return Tile::Plains;
+}
+
+// Picks up to a number of tiles that pass a test, each some way from the
+// others and from anything already taken
+fn pick_sites(map: &GameMap, count: usize, spacing: u32, taken: &[(u16, u16)], is_suitable: impl Fn(u16, u16) -> bool, rng: &mut Rng) -> Vec<(u16, u16)> {
+    let mut candidates: Vec<(u16, u16)> = (0..map.tiles.len())
+        .map(|index| map.index_to_coordinates(index))
+        .filter(|(x, y)| is_suitable(*x, *y))
+        .collect();
+    let mut result: Vec<(u16, u16)> = Vec::new();
+    while result.len() < count && !candidates.is_empty() {
+        let (x, y) = candidates.swap_remove(rng.range(RngStream::MapGen, 0, candidates.len() as i32 - 1) as usize);
+        let is_clear = |sites: &[(u16, u16)]| sites.iter().all(|(site_x, site_y)| pathfinding::get_distance(x, y, *site_x, *site_y) >= spacing);
+        if is_clear(&result) && is_clear(taken) {
+            result.push((x, y));
+        }
+    }
+    return result;
+}
+
+// Clears a patch of plains somewhere inland for when the land has nowhere
+// fit to settle, as long as there's anywhere inland at all
+fn force_settlement(map: &mut GameMap, is_inland: impl Fn(u16, u16) -> bool, rng: &mut Rng) -> Option<(u16, u16)> {
+    let inland: Vec<(u16, u16)> = (0..map.tiles.len())
+        .map(|index| map.index_to_coordinates(index))
+        .filter(|(x, y)| is_inland(*x, *y))
+        .collect();
+    if inland.is_empty() {
+        return None;
+    }
+    let (x, y) = inland[rng.range(RngStream::MapGen, 0, inland.len() as i32 - 1) as usize];
+    map.set_tile(x, y, Tile::Plains);
+    return Some((x, y));
+}
+
+// Digs an entrance a couple of tiles out from an inland settlement, with
+// hills raised behind it, for when there's no high ground to be reached
+fn force_entrance(map: &mut GameMap, settlement: (u16, u16), rng: &mut Rng) -> (u16, u16) {
+    let directions: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    let (offset_x, offset_y) = directions[rng.range(RngStream::MapGen, 0, 3) as usize];
+    let step = |distance: i32| ((settlement.0 as i32 + offset_x * distance) as u16, (settlement.1 as i32 + offset_y * distance) as u16);
+    let (path_x, path_y) = step(1);
+    let (entrance_x, entrance_y) = step(2);
+    let (hills_x, hills_y) = step(3);
+    map.set_tile(path_x, path_y, Tile::Plains);
+    map.set_tile(entrance_x, entrance_y, Tile::Plains);
+    map.set_tile(hills_x, hills_y, Tile::Hills);
+    return (entrance_x, entrance_y);
+}
+
+// Joins every settlement to the road network, nearest first, so that each
+// new road branches off towards the closest settlement already joined
+fn lay_roads(map: &mut GameMap, settlements: &[(u16, u16)]) {
+    let mut joined: Vec<(u16, u16)> = settlements.iter().take(1).cloned().collect();
+    let mut pending: Vec<(u16, u16)> = settlements.iter().skip(1).cloned().collect();
+    while !pending.is_empty() {
+        let mut best = (0, 0, u32::MAX);
+        for (index, (x, y)) in pending.iter().enumerate() {
+            for (other, (joined_x, joined_y)) in joined.iter().enumerate() {
+                let distance = pathfinding::get_distance(*x, *y, *joined_x, *joined_y);
+                if distance < best.2 {
+                    best = (index, other, distance);
+                }
+            }
+        }
+        let from = pending.swap_remove(best.0);
+        let to = joined[best.1];
+        map.set_tile(from.0, from.1, Tile::Road);
+        if let Some(path) = pathfinding::find_path(map, from, to, |_, _| false) {
+            for (x, y) in path {
+                map.set_tile(x, y, Tile::Road);
+            }
+        }
+        joined.push(from);
+    }
+}
+
+pub fn generate(id: MapID, width: u16, height: u16, settings: &OverworldSettings, rng: &mut Rng) -> GeneratedOverworld {
+    let mut map = GameMap::new(id, width, height);
+    map.rounds_per_tile = ROUNDS_PER_TILE;
+    // Starlight keeps the open country from going completely dark at night
+    map.ambient_light = LightLevel::Dim;
+
+    let elevation = make_noise(width, height, settings.feature_size, rng);
+    let moisture = make_noise(width, height, settings.feature_size, rng);
+    for index in 0..map.tiles.len() {
+        let (x, y) = map.index_to_coordinates(index);
+        let mut tile = get_biome(elevation[index], moisture[index], settings);
+        // A few lone trees and patches of herbs out on the plains
+        if tile == Tile::Plains && rng.chance(RngStream::MapGen, 3) {
+            tile = if rng.chance(RngStream::MapGen, 50) { Tile::Tree } else { Tile::Herbs };
+        }
+        map.set_tile(x, y, tile);
+    }
+
+    // Settlements go on open plains away from the edge of the map, all within
+    // walking distance of the first
+    let spacing = u32::max(4, width as u32 / (settings.settlements as u32 + 1));
+    let is_inland = |x: u16, y: u16| x >= 3 && y >= 3 && x + 3 < width && y + 3 < height;
+    let mut settlements = pick_sites(&map, 1, spacing, &[], |x, y| *map.get_tile(x, y) == Tile::Plains && is_inland(x, y), rng);
+    if settlements.is_empty() {
+        settlements.extend(force_settlement(&mut map, is_inland, rng));
+    }
+    let reachable = match settlements.first() {
+        Some((x, y)) => DijkstraMap::new(&map, &[(*x, *y, 0)]),
+        None => DijkstraMap::empty(),
+    };
+    let is_settled_land = |x: u16, y: u16| *map.get_tile(x, y) == Tile::Plains && is_inland(x, y) && reachable.get_distance(&map, x, y).is_some();
+    let others = pick_sites(&map, settings.settlements.saturating_sub(1), spacing, &settlements, is_settled_land, rng);
+    settlements.extend(others);
+    lay_roads(&mut map, &settlements);
+    for (x, y) in &settlements {
+        map.set_tile(*x, *y, Tile::Gate);
+    }
+
+    // Entrances are dug into the foot of the high ground
+    let is_foothill = |x: u16, y: u16| {
+        let tile = *map.get_tile(x, y);
+        return (tile == Tile::Plains || tile == Tile::Forest) && reachable.get_distance(&map, x, y).is_some() && NEIGHBOR_OFFSETS.iter().any(|offset| {
+            pathfinding::get_neighbor(&map, x, y, *offset).is_some_and(|(next_x, next_y)| matches!(map.get_tile(next_x, next_y), Tile::Hills | Tile::Mountain))
+        });
+    };
+    let mut entrances = pick_sites(&map, settings.entrances, spacing / 2, &settlements, is_foothill, rng);
+    if entrances.is_empty() && !settlements.is_empty() {
+        entrances.push(force_entrance(&mut map, settlements[0], rng));
+    }
+    for (x, y) in &entrances {
+        map.set_tile(*x, *y, Tile::Entrance);
+    }
+
+    return GeneratedOverworld {
+        map,
+        settlements,
+        entrances,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overworld_is_connected() {
+        let settings = OverworldSettings::new();
+        for seed in 0..4 {
+            let generated = generate(0, 80, 40, &settings, &mut Rng::new(seed));
+            let map = &generated.map;
+            assert!(generated.settlements.len() >= 2);
+            assert!(!generated.entrances.is_empty());
+
+            // Every settlement and entrance can be walked to from the first
+            // settlement, and there's some variety in between
+            let (x, y) = generated.settlements[0];
+            let distances = DijkstraMap::new(map, &[(x, y, 0)]);
+            for (site_x, site_y) in generated.settlements.iter().chain(generated.entrances.iter()) {
+                assert!(distances.get_distance(map, *site_x, *site_y).is_some());
+            }
+            for (entrance_x, entrance_y) in &generated.entrances {
+                assert_eq!(*map.get_tile(*entrance_x, *entrance_y), Tile::Entrance);
+            }
+            assert!(map.tiles.contains(&Tile::Road));
+            let biomes = [Tile::Water, Tile::Plains, Tile::Forest, Tile::Hills, Tile::Mountain];
+            assert!(biomes.iter().filter(|biome| map.tiles.contains(biome)).count() >= 4);
+        }
+
+        let first = generate(0, 80, 40, &settings, &mut Rng::new(7));
+        let second = generate(0, 80, 40, &settings, &mut Rng::new(7));
+        assert_eq!(first.map.tiles, second.map.tiles);
+    }
+
+    #[test]
+    fn test_tiny_overworld_without_settlements() {
+        // Too small for anywhere inland, so nothing gets settled or dug into
+        let generated = generate(0, 6, 6, &OverworldSettings::new(), &mut Rng::new(3));
+        assert!(generated.settlements.is_empty());
+        assert!(generated.entrances.is_empty());
+        assert!(!generated.map.tiles.contains(&Tile::Gate));
+    }
+
+    #[test]
+    fn test_small_overworlds_always_have_a_way_in() {
+        // Even when the land has nowhere fit to settle or dig into, one of
+        // each gets cleared, and the entrance can be walked to
+        for seed in 0..300 {
+            let generated = generate(0, 30, 15, &OverworldSettings::new(), &mut Rng::new(seed));
+            let map = &generated.map;
+            assert!(!generated.settlements.is_empty() && !generated.entrances.is_empty(), "seed {}", seed);
+            let (x, y) = generated.settlements[0];
+            assert_eq!(*map.get_tile(x, y), Tile::Gate);
+            let distances = DijkstraMap::new(map, &[(x, y, 0)]);
+            assert!(generated.entrances.iter().all(|(entrance_x, entrance_y)| distances.get_distance(map, *entrance_x, *entrance_y).is_some()), "seed {}", seed);
+        }
+    }
+}
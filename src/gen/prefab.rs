@@ -0,0 +1,484 @@
+use std::{collections::HashMap, fmt, fs, path::Path, str::FromStr};
+
+use crate::{dijkstra::DijkstraMap, item::{Item, ItemType}, map::{GameMap, Tile}, monster::MonsterKind, rng::{Rng, RngStream}, substance::{Liquid, Substance}, trap::TrapKind};
+
+use super::map_gen::{GeneratedMap, Room};
+
+// Prefabs are hand-drawn rooms kept in plain text files, one to a file:
+//
+//   ; Lines starting with a semicolon are comments
+//   name shrine
+//   legend # = Wall
+//   legend A = Altar
+//   legend ! = Floor, item Vial PotionOfHealing 4
+//   legend r = monster GiantRat
+//   legend ^ = trap Dart
+//   map
+//   #####
+//   #.A.#
+//   #!.r#
+//
+// Each legend entry is a tile, any number of items and monsters and at most
+// one hidden trap, separated by commas. Entries without a tile are on Floor. Items
+// are a kind and either how many there are, or what they're filled with and
+// how much. Everything after the map line is the map, where a space leaves
+// whatever was there before.
+
+// What a single character of a prefab's map puts down
+#[derive(Clone, Debug)]
+pub struct Cell {
+    pub tile: Tile,
+    pub items: Vec<Item>,
+    pub monsters: Vec<MonsterKind>,
+    pub trap: Option<TrapKind>,
+}
+
+#[derive(Debug)]
+pub struct Prefab {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    // Row by row, with None where the map is left alone
+    pub cells: Vec<Option<Cell>>,
+}
+
+impl Prefab {
+    pub fn get_cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        return self.cells[y as usize * self.width as usize + x as usize].as_ref();
+    }
+}
+
+// Which way round a prefab gets stamped. It's mirrored left to right first
+// and then turned clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Orientation {
+    pub turns: u8,
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    pub fn random(rng: &mut Rng) -> Self {
+        Self {
+            turns: rng.range(RngStream::MapGen, 0, 3) as u8,
+            mirrored: rng.chance(RngStream::MapGen, 50),
+        }
+    }
+}
+
+// Something wrong with a prefab file, and where. Lines and columns count
+// from one, and are zero when the problem is with the file as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefabError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}: {}", self.file, self.message);
+        }
+        write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+    }
+}
+
+fn make_error(file: &str, line: usize, column: usize, message: String) -> PrefabError {
+    return PrefabError {
+        file: String::from(file),
+        line,
+        column,
+        message,
+    };
+}
+
+// Splits a stretch of a line into words, each with the column it starts at
+fn get_words(text: &str, first_column: usize) -> Vec<(usize, &str)> {
+    let mut result: Vec<(usize, &str)> = Vec::new();
+    let mut maybe_start: Option<usize> = None;
+    for (index, character) in text.char_indices() {
+        if character.is_whitespace() {
+            if let Some(start) = maybe_start {
+                result.push((first_column + start, &text[start..index]));
+                maybe_start = None;
+            }
+        } else if maybe_start.is_none() {
+            maybe_start = Some(index);
+        }
+    }
+    if let Some(start) = maybe_start {
+        result.push((first_column + start, &text[start..]));
+    }
+    return result;
+}
+
+fn parse_name<T: FromStr>(file: &str, line: usize, word: Option<&(usize, &str)>, end: usize, what: &str) -> Result<T, PrefabError> {
+    if word.is_none() {
+        return Err(make_error(file, line, end, format!("Expected {}", what)));
+    }
+    let (column, text) = *word.unwrap();
+    return text.parse::<T>().map_err(|_| make_error(file, line, column, format!("Unknown {} '{}'", what, text)));
+}
+
+// Reads one comma-separated part of a legend entry into the cell
+fn parse_part(file: &str, line: usize, part: &str, first_column: usize, cell: &mut Cell, has_tile: &mut bool) -> Result<(), PrefabError> {
+    let words = get_words(part, first_column);
+    let end = first_column + part.len();
+    if words.is_empty() {
+        return Err(make_error(file, line, end, String::from("Expected a tile, an item, a monster or a trap")));
+    }
+    let (column, first) = words[0];
+    match first {
+        "item" => {
+            let item_type: ItemType = parse_name(file, line, words.get(1), end, "item")?;
+            let item = match words.get(2) {
+                None => Item::new(item_type),
+                Some((_, text)) if text.parse::<u32>().is_ok() => Item::with_quantity(item_type, text.parse::<u32>().unwrap()),
+                Some(_) => {
+                    let substance: Substance = parse_name(file, line, words.get(2), end, "substance")?;
+                    let volume: u32 = parse_name(file, line, words.get(3), end, "amount")?;
+                    Item::with_liquid(item_type, Liquid::new(substance, volume))
+                },
+            };
+            cell.items.push(item);
+        },
+        "monster" => {
+            let kind: MonsterKind = parse_name(file, line, words.get(1), end, "monster")?;
+            cell.monsters.push(kind);
+        },
+        "trap" => {
+            if cell.trap.is_some() {
+                return Err(make_error(file, line, column, String::from("A legend entry can only have one trap")));
+            }
+            cell.trap = Some(parse_name(file, line, words.get(1), end, "trap")?);
+        },
+        _ => {
+            if *has_tile {
+                return Err(make_error(file, line, column, String::from("A legend entry can only have one tile")));
+            }
+            cell.tile = parse_name(file, line, Some(&words[0]), end, "tile")?;
+            *has_tile = true;
+        },
+    }
+    return Ok(());
+}
+
+// Reads a legend line, which starts with "legend" and the character it's for
+fn parse_legend_entry(file: &str, line: usize, text: &str) -> Result<(char, usize, Cell), PrefabError> {
+    let mut characters = text.char_indices().skip("legend".len());
+    let key = characters.find(|(_, character)| !character.is_whitespace());
+    if key.is_none() {
+        return Err(make_error(file, line, text.len() + 1, String::from("Expected a character for the legend")));
+    }
+    let (key_index, key_character) = key.unwrap();
+    let key_column = key_index + 1;
+    let equals = characters.find(|(_, character)| !character.is_whitespace());
+    if equals.is_none() || equals.unwrap().1 != '=' {
+        let column = equals.map(|(index, _)| index + 1).unwrap_or(text.len() + 1);
+        return Err(make_error(file, line, column, format!("Expected '=' after '{}'", key_character)));
+    }
+
+    let mut cell = Cell { tile: Tile::Floor, items: Vec::new(), monsters: Vec::new(), trap: None };
+    let mut has_tile = false;
+    let mut start = equals.unwrap().0 + 1;
+    for part in text[start..].split(',') {
+        parse_part(file, line, part, start + 1, &mut cell, &mut has_tile)?;
+        start += part.len() + 1;
+    }
+    return Ok((key_character, key_column, cell));
+}
+
+// Reads a prefab from the text of a file. The file name goes into any
+// errors, and names the prefab if it doesn't have a name of its own.
+pub fn parse_prefab(file: &str, text: &str) -> Result<Prefab, PrefabError> {
+    let mut name = String::from(Path::new(file).file_stem().and_then(|stem| stem.to_str()).unwrap_or(file));
+    let mut legend: HashMap<char, Cell> = HashMap::new();
+    let mut maybe_map_line: Option<usize> = None;
+    let mut rows: Vec<(usize, &str)> = Vec::new();
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw_line.trim_end();
+        if maybe_map_line.is_some() {
+            rows.push((line, trimmed));
+            continue;
+        }
+        if trimmed.trim_start().is_empty() || trimmed.starts_with(';') {
+            continue;
+        }
+        let first = get_words(trimmed, 1)[0].1;
+        match first {
+            "name" => name = String::from(trimmed["name".len()..].trim()),
+            "legend" => {
+                let (key, column, cell) = parse_legend_entry(file, line, trimmed)?;
+                if legend.contains_key(&key) {
+                    return Err(make_error(file, line, column, format!("'{}' is already in the legend", key)));
+                }
+                legend.insert(key, cell);
+            },
+            "map" => maybe_map_line = Some(line),
+            _ => return Err(make_error(file, line, 1, format!("Expected name, legend or map but found '{}'", first))),
+        }
+    }
+
+    if maybe_map_line.is_none() {
+        return Err(make_error(file, text.lines().count() + 1, 1, String::from("Missing a map")));
+    }
+    while rows.last().is_some_and(|(_, row)| row.is_empty()) {
+        rows.pop();
+    }
+    let width = rows.iter().map(|(_, row)| row.chars().count()).max().unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        return Err(make_error(file, maybe_map_line.unwrap(), 1, String::from("The map is empty")));
+    }
+    if width > u16::MAX as usize || rows.len() > u16::MAX as usize {
+        return Err(make_error(file, maybe_map_line.unwrap(), 1, String::from("The map is too big")));
+    }
+
+    let mut cells: Vec<Option<Cell>> = Vec::with_capacity(width * rows.len());
+    for (line, row) in &rows {
+        let mut count = 0;
+        for (column, character) in row.chars().enumerate() {
+            count += 1;
+            if character == ' ' {
+                cells.push(None);
+                continue;
+            }
+            let maybe_cell = legend.get(&character);
+            if maybe_cell.is_none() {
+                return Err(make_error(file, *line, column + 1, format!("'{}' isn't in the legend", character)));
+            }
+            cells.push(Some(maybe_cell.unwrap().clone()));
+        }
+        // Short rows are left alone past their end
+        for _ in count..width {
+            cells.push(None);
+        }
+    }
+
+    return Ok(Prefab {
+        name,
+        width: width as u16,
+        height: rows.len() as u16,
+        cells,
+    });
+}
+
+// Reads every prefab in a directory, in order of file name. A missing
+// directory just means there aren't any.
+pub fn load_prefabs(directory: &Path) -> Result<Vec<Prefab>, PrefabError> {
+    let mut result: Vec<Prefab> = Vec::new();
+    if !directory.is_dir() {
+        return Ok(result);
+    }
+    let directory_name = directory.display().to_string();
+    let entries = fs::read_dir(directory).map_err(|error| make_error(&directory_name, 0, 0, error.to_string()))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let file = path.display().to_string();
+        let text = fs::read_to_string(&path).map_err(|error| make_error(&file, 0, 0, error.to_string()))?;
+        result.push(parse_prefab(&file, &text)?);
+    }
+    return Ok(result);
+}
+
+pub fn get_oriented_size(prefab: &Prefab, orientation: Orientation) -> (u16, u16) {
+    if orientation.turns % 2 == 1 {
+        return (prefab.height, prefab.width);
+    }
+    return (prefab.width, prefab.height);
+}
+
+// Where a tile of the prefab ends up once it's been turned round
+pub fn orient(prefab: &Prefab, orientation: Orientation, x: u16, y: u16) -> (u16, u16) {
+    let (mut width, mut height) = (prefab.width, prefab.height);
+    let (mut result_x, mut result_y) = (x, y);
+    if orientation.mirrored {
+        result_x = width - 1 - result_x;
+    }
+    for _ in 0..orientation.turns % 4 {
+        (result_x, result_y) = (height - 1 - result_y, result_x);
+        (width, height) = (height, width);
+    }
+    return (result_x, result_y);
+}
+
+// Draws a prefab onto the map with its top left corner at the given spot,
+// and hands back where its monsters should go. Anything off the edge of the
+// map is left off.
+pub fn stamp(map: &mut GameMap, prefab: &Prefab, orientation: Orientation, left: u16, top: u16) -> Vec<(MonsterKind, u16, u16)> {
+    let mut result: Vec<(MonsterKind, u16, u16)> = Vec::new();
+    for y in 0..prefab.height {
+        for x in 0..prefab.width {
+            let maybe_cell = prefab.get_cell(x, y);
+            let (offset_x, offset_y) = orient(prefab, orientation, x, y);
+            let (map_x, map_y) = (left as u32 + offset_x as u32, top as u32 + offset_y as u32);
+            if maybe_cell.is_none() || map_x >= map.width as u32 || map_y >= map.height as u32 {
+                continue;
+            }
+            let cell = maybe_cell.unwrap();
+            let (map_x, map_y) = (map_x as u16, map_y as u16);
+            map.set_tile(map_x, map_y, cell.tile);
+            map.get_items_mut(map_x, map_y).extend(cell.items.iter().cloned());
+            if cell.trap.is_some() {
+                map.set_trap(map_x, map_y, cell.trap);
+            }
+            for kind in &cell.monsters {
+                result.push((*kind, map_x, map_y));
+            }
+        }
+    }
+    return result;
+}
+
+// Whether the stairs down and every room can still be got to from the
+// stairs up
+fn is_connected(generated: &GeneratedMap) -> bool {
+    if generated.stairs_up.is_none() {
+        return true;
+    }
+    let map = &generated.map;
+    let (up_x, up_y) = generated.stairs_up.unwrap();
+    let distances = DijkstraMap::new(map, &[(up_x, up_y, 0)]);
+    let is_reachable = |x: u16, y: u16| distances.get_distance(map, x, y).is_some();
+    if generated.stairs_down.is_some_and(|(x, y)| !is_reachable(x, y)) {
+        return false;
+    }
+    return generated.rooms.iter().all(|room| {
+        (room.y..room.y + room.height).any(|y| (room.x..room.x + room.width).any(|x| is_reachable(x, y)))
+    });
+}
+
+// Stamps up to a number of prefabs into rooms they fit in, turned whichever
+// way. Rooms with stairs are left alone, as is the first room, and a prefab
+// that would cut anything off gets taken back out again.
+pub fn place_vaults(generated: &mut GeneratedMap, prefabs: &[Prefab], count: usize, rng: &mut Rng) {
+    if prefabs.is_empty() {
+        return;
+    }
+    let has_stairs = |room: &Room| {
+        [generated.stairs_up, generated.stairs_down].iter().any(|stairs| stairs.is_some_and(|(x, y)| room.contains(x, y)))
+    };
+    let mut free_rooms: Vec<Room> = generated.rooms.iter().skip(1).filter(|room| !has_stairs(room)).cloned().collect();
+
+    for _ in 0..count {
+        let prefab = &prefabs[rng.range(RngStream::MapGen, 0, prefabs.len() as i32 - 1) as usize];
+        let orientation = Orientation::random(rng);
+        let (width, height) = get_oriented_size(prefab, orientation);
+        let fitting: Vec<usize> = (0..free_rooms.len()).filter(|index| free_rooms[*index].width >= width && free_rooms[*index].height >= height).collect();
+        if fitting.is_empty() {
+            continue;
+        }
+        let room = free_rooms.swap_remove(fitting[rng.range(RngStream::MapGen, 0, fitting.len() as i32 - 1) as usize]);
+        let left = room.x + (room.width - width) / 2;
+        let top = room.y + (room.height - height) / 2;
+
+        // Try the tiles out first, so they can be put back if they get in the way
+        let area = Room::new(left, top, width, height);
+        let before: Vec<Tile> = (area.y..area.y + area.height).flat_map(|y| (area.x..area.x + area.width).map(move |x| (x, y))).map(|(x, y)| *generated.map.get_tile(x, y)).collect();
+        let items_before: Vec<usize> = (area.y..area.y + area.height).flat_map(|y| (area.x..area.x + area.width).map(move |x| (x, y))).map(|(x, y)| generated.map.get_items(x, y).len()).collect();
+        let traps_before: Vec<Option<TrapKind>> = (area.y..area.y + area.height).flat_map(|y| (area.x..area.x + area.width).map(move |x| (x, y))).map(|(x, y)| generated.map.get_trap(x, y)).collect();
+        let spawns = stamp(&mut generated.map, prefab, orientation, left, top);
+        if is_connected(generated) {
+            generated.spawns.extend(spawns);
+            generated.vaults.push(prefab.name.clone());
+            continue;
+        }
+        let tiles = (area.y..area.y + area.height).flat_map(|y| (area.x..area.x + area.width).map(move |x| (x, y)));
+        for ((((x, y), tile), count), trap) in tiles.zip(before).zip(items_before).zip(traps_before) {
+            generated.map.set_tile(x, y, tile);
+            generated.map.get_items_mut(x, y).truncate(count);
+            generated.map.set_trap(x, y, trap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::bsp::{self, BspSettings};
+
+    const SHRINE: &str = "; A test shrine
+name shrine
+legend # = Wall
+legend . = Floor
+legend A = Altar
+legend ! = item Vial PotionOfHealing 4, item Coin 10
+legend r = Web, monster GiantRat
+legend ^ = trap Dart
+map
+####
+#A.^
+#!r#
+";
+
+    #[test]
+    fn test_prefabs_parse_and_orient() {
+        let prefab = parse_prefab("shrine.txt", SHRINE).unwrap();
+        assert_eq!(prefab.name, "shrine");
+        assert_eq!((prefab.width, prefab.height), (4, 3));
+        assert_eq!(prefab.get_cell(1, 1).unwrap().tile, Tile::Altar);
+        assert_eq!(prefab.get_cell(3, 1).unwrap().trap, Some(TrapKind::Dart));
+        assert_eq!(prefab.get_cell(3, 1).unwrap().tile, Tile::Floor);
+        let treasure = prefab.get_cell(1, 2).unwrap();
+        assert_eq!(treasure.tile, Tile::Floor);
+        assert_eq!(treasure.items.len(), 2);
+        assert_eq!(prefab.get_cell(2, 2).unwrap().monsters, vec![MonsterKind::GiantRat]);
+
+        // Turned once clockwise, the bottom left corner ends up top left
+        let turned = Orientation { turns: 1, mirrored: false };
+        assert_eq!(get_oriented_size(&prefab, turned), (3, 4));
+        assert_eq!(orient(&prefab, turned, 0, 2), (0, 0));
+        assert_eq!(orient(&prefab, Orientation { turns: 0, mirrored: true }, 1, 1), (2, 1));
+        assert_eq!(orient(&prefab, Orientation { turns: 2, mirrored: false }, 1, 1), (2, 1));
+
+        let mut map = GameMap::new(0, 6, 6);
+        let spawns = stamp(&mut map, &prefab, turned, 1, 1);
+        assert_eq!(*map.get_tile(2, 2), Tile::Altar);
+        assert_eq!(spawns, vec![(MonsterKind::GiantRat, 1, 3)]);
+        assert_eq!(map.get_items(1, 2).len(), 2);
+        assert_eq!(map.get_trap(2, 4), Some(TrapKind::Dart));
+    }
+
+    #[test]
+    fn test_prefab_errors_say_where() {
+        let error = parse_prefab("bad.txt", "legend A = Altr\nmap\nA").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 12));
+        assert_eq!(error.to_string(), "bad.txt:1:12: Unknown tile 'Altr'");
+
+        let error = parse_prefab("bad.txt", "legend A = Altar\nmap\nAAA\nAxA").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 2));
+
+        let error = parse_prefab("bad.txt", "legend $ = Floor, item Vial Ale 4\nmap\n$").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 29));
+        assert!(parse_prefab("bad.txt", "legend A = Altar\n").is_err());
+        assert!(parse_prefab("bad.txt", "legend A Altar\nmap\nA").is_err());
+    }
+
+    #[test]
+    fn test_vaults_fit_without_cutting_anything_off() {
+        let prefabs = load_prefabs(Path::new("data/prefabs")).unwrap();
+        assert!(prefabs.iter().any(|prefab| prefab.name == "shrine"));
+        let mut placed = 0;
+        for seed in 0..6 {
+            let mut rng = Rng::new(seed);
+            let mut generated = bsp::generate(0, 80, 40, &BspSettings::new(), &mut rng);
+            let before = generated.map.tiles.clone();
+            place_vaults(&mut generated, &prefabs, 3, &mut rng);
+            assert!(is_connected(&generated));
+            if generated.map.tiles != before {
+                placed += 1;
+            }
+            assert!(generated.vaults.len() <= 3);
+            assert!(generated.vaults.iter().all(|name| prefabs.iter().any(|prefab| prefab.name == *name)));
+            assert_eq!(generated.map.tiles != before, !generated.vaults.is_empty());
+        }
+        assert!(placed > 0);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{game::Game, item::{Item, ItemType}, lighting::LightLevel, map::{self, GameMap, MapID, Tile}, npc::{self, NpcRole}, rng::{Rng, RngStream}, substance::{Liquid, Substance}};
+
+use super::map_gen::{self, GeneratedMap, Room};
+
+// What a lot in town is used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildingRole {
+    Graveyard,
+    Home,
+    Shop,
+    Smithy,
+    Tavern,
+    Temple,
+}
+
+pub struct Building {
+    // The inside, not counting the walls
+    pub room: Room,
+    // Where it opens onto the street
+    pub door: (u16, u16),
+    pub role: BuildingRole,
+}
+
+pub struct TownSettings {
+    // Smallest and largest a lot can be, counting the building's walls. The
+    // depth runs back from the street.
+    pub min_lot_width: u16,
+    pub max_lot_width: u16,
+    pub min_lot_depth: u16,
+    pub max_lot_depth: u16,
+    pub shops: usize,
+    // How many townsfolk are out and about on the streets
+    pub street_folk: usize,
+}
+
+impl TownSettings {
+    pub fn new() -> Self {
+        Self {
+            min_lot_width: 5,
+            max_lot_width: 9,
+            min_lot_depth: 4,
+            max_lot_depth: 6,
+            shops: 2,
+            street_folk: 4,
+        }
+    }
+}
+
+// A generated town. The gate counts as its stairs up, since that's the way
+// back out onto the overworld.
+pub struct GeneratedTown {
+    pub generated: GeneratedMap,
+    pub buildings: Vec<Building>,
+}
+
+// A lot along a street, counting the walls
+struct Lot {
+    area: Room,
+    door: (u16, u16),
+}
+
+// Lays out lots along both sides of a street, leaving a gap between each and
+// keeping clear of the main street
+fn lay_out_lots(map: &GameMap, street: u16, main_street: u16, settings: &TownSettings, rng: &mut Rng) -> Vec<Lot> {
+    let mut result: Vec<Lot> = Vec::new();
+    for above in [true, false] {
+        let mut x = 2;
+        while x + settings.min_lot_width < map.width - 1 {
+            let width = rng.range(RngStream::MapGen, settings.min_lot_width as i32, settings.max_lot_width as i32) as u16;
+            if x + width >= map.width - 1 {
+                break;
+            }
+            if x <= main_street + 1 && x + width >= main_street {
+                x = main_street + 2;
+                continue;
+            }
+            let depth = rng.range(RngStream::MapGen, settings.min_lot_depth as i32, settings.max_lot_depth as i32) as u16;
+            let top = if above { street - depth } else { street + 1 };
+            let door_x = rng.range(RngStream::MapGen, (x + 1) as i32, (x + width - 2) as i32) as u16;
+            let door_y = if above { street - 1 } else { street + 1 };
+            result.push(Lot { area: Room::new(x, top, width, depth), door: (door_x, door_y) });
+            x += width + 1;
+        }
+    }
+    return result;
+}
+
+// The biggest lots go to the temple and the graveyard, then the rest are
+// handed out at random, with homes filling whatever's left
+fn assign_roles(lots: &[Lot], settings: &TownSettings, rng: &mut Rng) -> Vec<BuildingRole> {
+    let mut order: Vec<usize> = (0..lots.len()).collect();
+    order.sort_by_key(|index| std::cmp::Reverse(lots[*index].area.width as u32 * lots[*index].area.height as u32));
+    for position in (3..order.len()).rev() {
+        let other = rng.range(RngStream::MapGen, 2, position as i32) as usize;
+        order.swap(position, other);
+    }
+
+    let mut wanted = vec![BuildingRole::Temple, BuildingRole::Graveyard, BuildingRole::Smithy, BuildingRole::Tavern];
+    wanted.extend(std::iter::repeat_n(BuildingRole::Shop, settings.shops));
+    let mut result = vec![BuildingRole::Home; lots.len()];
+    for (index, role) in order.iter().zip(wanted) {
+        result[*index] = role;
+    }
+    return result;
+}
+
+fn build(map: &mut GameMap, lot: &Lot, role: BuildingRole) -> Building {
+    let area = lot.area;
+    let room = Room::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2);
+    let (door_x, door_y) = lot.door;
+    // The side of the building away from the street
+    let back = if door_y == area.y { room.y + room.height - 1 } else { room.y };
+
+    // Graveyards are open ground with rows of headstones
+    if role == BuildingRole::Graveyard {
+        for y in area.y..area.y + area.height {
+            for x in area.x..area.x + area.width {
+                let headstone = room.contains(x, y) && (x - room.x).is_multiple_of(2) && (y - room.y).is_multiple_of(2);
+                map.set_tile(x, y, if headstone { Tile::Tombstone } else { Tile::Graveyard });
+            }
+        }
+        return Building { room, door: lot.door, role };
+    }
+
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            map.set_tile(x, y, Tile::Building);
+        }
+    }
+    map_gen::carve_room(map, &room);
+    map.set_tile(door_x, door_y, Tile::DoorClosed);
+
+    match role {
+        BuildingRole::Smithy => {
+            let forge_x = if door_x - room.x < room.width / 2 { room.x + room.width - 1 } else { room.x };
+            map.set_tile(forge_x, back, Tile::Forge);
+        },
+        BuildingRole::Temple => {
+            let (center_x, _) = room.center();
+            map.set_tile(center_x, back, Tile::Altar);
+            if room.width >= 5 {
+                map.set_tile(center_x - 2, back, Tile::Statue);
+                map.set_tile(center_x + 2, back, Tile::Statue);
+            }
+        },
+        BuildingRole::Graveyard => {},
+        BuildingRole::Home => {},
+        BuildingRole::Shop => {},
+        BuildingRole::Tavern => {},
+    }
+    return Building { room, door: lot.door, role };
+}
+
+pub fn generate(id: MapID, width: u16, height: u16, settings: &TownSettings, rng: &mut Rng) -> GeneratedTown {
+    let mut map = GameMap::new(id, width, height);
+    // Lamps in the windows keep the streets from going completely dark
+    map.ambient_light = LightLevel::Dim;
+    map_gen::fill(&mut map, Tile::Plains);
+    for y in 0..height {
+        for x in 0..width {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                map.set_tile(x, y, Tile::Wall);
+            } else if rng.chance(RngStream::MapGen, 2) {
+                map.set_tile(x, y, Tile::Tree);
+            }
+        }
+    }
+
+    // Streets run across the town, with buildings backing onto each other
+    // in between, and the main street runs down the middle to the gate
+    let main_street = width / 2;
+    let mut streets: Vec<u16> = Vec::new();
+    let mut street = settings.max_lot_depth + 1;
+    while street + settings.max_lot_depth + 1 < height {
+        streets.push(street);
+        street += 2 * settings.max_lot_depth + 2;
+    }
+    let gate = (main_street, height - 1);
+    map.set_tile(gate.0, gate.1, Tile::Gate);
+    let top = streets.first().cloned().unwrap_or(height - 2);
+    for y in top..height - 1 {
+        map.set_tile(main_street, y, Tile::Road);
+    }
+    for street in &streets {
+        for x in 1..width - 1 {
+            map.set_tile(x, *street, Tile::Road);
+        }
+    }
+
+    let mut lots: Vec<Lot> = Vec::new();
+    for street in &streets {
+        lots.extend(lay_out_lots(&map, *street, main_street, settings, rng));
+    }
+    let roles = assign_roles(&lots, settings, rng);
+    let buildings: Vec<Building> = lots.iter().zip(roles).map(|(lot, role)| build(&mut map, lot, role)).collect();
+
+    return GeneratedTown {
+        generated: GeneratedMap {
+            map,
+            rooms: buildings.iter().map(|building| building.room).collect(),
+            stairs_up: Some(gate),
+            stairs_down: None,
+            spawns: Vec::new(),
+            vaults: Vec::new(),
+        },
+        buildings,
+    };
+}
+
+// What a merchant has to sell, by where they work
+fn get_stock(role: BuildingRole) -> Vec<Item> {
+    return match role {
+        BuildingRole::Graveyard => Vec::new(),
+        BuildingRole::Home => Vec::new(),
+        BuildingRole::Shop => vec![
+            Item::with_quantity(ItemType::Rations, 10),
+            Item::with_quantity(ItemType::Torch, 5),
+            Item::new(ItemType::Waterskin),
+            Item::new(ItemType::Tinderbox),
+            Item::new(ItemType::Rope),
+            Item::new(ItemType::Backpack),
+            Item::new(ItemType::Bedroll),
+            Item::with_liquid(ItemType::Flask, Liquid::new(Substance::Oil, 16)),
+        ],
+        BuildingRole::Smithy => vec![
+            Item::new(ItemType::Dagger),
+            Item::new(ItemType::Handaxe),
+            Item::new(ItemType::Mace),
+            Item::new(ItemType::Longsword),
+            Item::new(ItemType::Shield),
+            Item::new(ItemType::ChainShirtArmor),
+        ],
+        BuildingRole::Tavern => vec![
+            Item::with_liquid(ItemType::Bottle, Liquid::new(Substance::Wine, 24)),
+            Item::with_liquid(ItemType::Bottle, Liquid::new(Substance::Wine, 24)),
+            Item::with_quantity(ItemType::Rations, 5),
+        ],
+        BuildingRole::Temple => vec![
+            Item::with_liquid(ItemType::Vial, Liquid::new(Substance::PotionOfHealing, 4)),
+            Item::with_liquid(ItemType::Vial, Liquid::new(Substance::PotionOfHealing, 4)),
+        ],
+    };
+}
+
+// A free tile inside a building for someone to stand on
+fn find_spot(game: &Game, room: &Room) -> Option<(u16, u16)> {
+    let map = &game.current_map;
+    return (room.y..room.y + room.height)
+        .flat_map(|y| (room.x..room.x + room.width).map(move |x| (x, y)))
+        .find(|(x, y)| !map::is_impassable(map.get_tile(*x, *y)) && game.components.get_creature_at(map.id, *x, *y).is_none());
+}
+
+// Where whoever works or lives in a building waits. Merchants keep to the
+// back across from the door, where the counter would be.
+fn get_post(building: &Building) -> (u16, u16) {
+    let room = building.room;
+    if building.role == BuildingRole::Home {
+        return room.center();
+    }
+    let (door_x, door_y) = building.door;
+    let back = if door_y < room.y { room.y + room.height - 1 } else { room.y };
+    return (door_x, back);
+}
+
+// Puts someone to work in every building that needs it, someone at home in
+// each house, and a few townsfolk out on the streets. The town needs to be
+// the current map.
+pub fn populate_town(game: &mut Game, buildings: &[Building], settings: &TownSettings) {
+    for building in buildings {
+        let role = match building.role {
+            BuildingRole::Graveyard => None,
+            BuildingRole::Home => Some(NpcRole::Townsfolk),
+            BuildingRole::Shop => Some(NpcRole::Merchant),
+            BuildingRole::Smithy => Some(NpcRole::Merchant),
+            BuildingRole::Tavern => Some(NpcRole::Merchant),
+            BuildingRole::Temple => Some(NpcRole::Merchant),
+        };
+        let (post_x, post_y) = get_post(building);
+        let maybe_spot = find_spot(game, &Room::new(post_x, post_y, 1, 1))
+            .or_else(|| find_spot(game, &building.room));
+        if role.is_none() || maybe_spot.is_none() {
+            continue;
+        }
+        let (x, y) = maybe_spot.unwrap();
+        npc::spawn_npc(game, role.unwrap(), x, y, get_stock(building.role));
+    }
+
+    let map = &game.current_map;
+    let mut streets: Vec<(u16, u16)> = (0..map.tiles.len())
+        .filter(|index| map.tiles[*index] == Tile::Road)
+        .map(|index| map.index_to_coordinates(index))
+        .collect();
+    for _ in 0..settings.street_folk {
+        if streets.is_empty() {
+            break;
+        }
+        let (x, y) = streets.swap_remove(game.rng.range(RngStream::MapGen, 0, streets.len() as i32 - 1) as usize);
+        if game.components.get_creature_at(game.current_map.id, x, y).is_none() {
+            npc::spawn_npc(game, NpcRole::Townsfolk, x, y, Vec::new());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dijkstra::DijkstraMap;
+
+    #[test]
+    fn test_town_layout_and_townsfolk() {
+        let settings = TownSettings::new();
+        let town = generate(1, 80, 40, &settings, &mut Rng::new(5));
+        let map = &town.generated.map;
+        let (gate_x, gate_y) = town.generated.stairs_up.unwrap();
+        assert_eq!(*map.get_tile(gate_x, gate_y), Tile::Gate);
+
+        // Every building can be walked into from the gate
+        let distances = DijkstraMap::new(map, &[(gate_x, gate_y, 0)]);
+        for building in &town.buildings {
+            let (door_x, door_y) = building.door;
+            assert!(distances.get_distance(map, door_x, door_y).is_some());
+        }
+        let roles: Vec<BuildingRole> = town.buildings.iter().map(|building| building.role).collect();
+        for role in [BuildingRole::Graveyard, BuildingRole::Home, BuildingRole::Shop, BuildingRole::Smithy, BuildingRole::Tavern, BuildingRole::Temple] {
+            assert!(roles.contains(&role));
+        }
+        for tile in [Tile::Altar, Tile::Forge, Tile::Tombstone, Tile::DoorClosed] {
+            assert!(map.tiles.contains(&tile));
+        }
+
+        let mut game = Game::new();
+        let buildings = town.buildings;
+        game.current_map = Box::new(town.generated.map);
+        populate_town(&mut game, &buildings, &settings);
+        let npcs: Vec<NpcRole> = game.components.get_living_creatures().iter().filter_map(|creature| npc::get_npc_role(&game, *creature)).collect();
+        assert_eq!(npcs.iter().filter(|role| **role == NpcRole::Merchant).count(), 3 + settings.shops);
+        assert!(npcs.iter().filter(|role| **role == NpcRole::Townsfolk).count() >= settings.street_folk);
+        for building in buildings.iter().filter(|building| building.role == BuildingRole::Shop) {
+            let (post_x, post_y) = get_post(building);
+            assert!(building.room.contains(post_x, post_y));
+            assert!(game.components.get_creature_at(game.current_map.id, post_x, post_y).is_some());
+        }
+
+        let first = generate(1, 80, 40, &settings, &mut Rng::new(8));
+        let second = generate(1, 80, 40, &settings, &mut Rng::new(8));
+        assert_eq!(first.generated.map.tiles, second.generated.map.tiles);
+    }
+}
@@ -1,12 +1,88 @@
 use enum_map::{Enum, EnumMap, enum_map};
+use strum_macros::EnumString;
 
-use crate::tag::Tag;
+use crate::{material::{self, Material}, substance::{self, Liquid, Volume}, tabletop::Dice, tag::{Tag, TagCache, TagSet}, temperature::{self, Temperature, AMBIENT_TEMPERATURE}, time::{Seconds, SECONDS_PER_DAY, SECONDS_PER_HOUR}};
 
+// Weights are stored in thousandths of a pound so that things like ball
+// bearings and coins don't round down to nothing
+pub type Weight = u32;
+pub const WEIGHT_PER_POUND: Weight = 1000;
+
+pub const SPOILED_FOOD_DAMAGE: Dice = Dice::new(1, 6, 0);
+
+#[derive(Clone, Debug)]
 pub struct Item {
     pub item_type: ItemType,
+    pub material: Material,
+    pub quantity: u32,
+    pub contents: Vec<Item>,
+    pub liquid: Option<Liquid>,
+    // How long organic items have been going off for
+    pub age: Seconds,
+    // Tags that come and go during play, like Burning or Wet
+    pub state: TagSet,
+    // How much longer a lit item will burn for
+    pub fuel: Seconds,
+    pub temperature: Temperature,
+}
+
+impl Item {
+    pub fn new(item_type: ItemType) -> Self {
+        Self::with_quantity(item_type, 1)
+    }
+
+    pub fn with_quantity(item_type: ItemType, quantity: u32) -> Self {
+        Self {
+            item_type,
+            material: get_default_material(item_type),
+            quantity,
+            contents: Vec::new(),
+            liquid: None,
+            age: 0,
+            state: TagSet::new(),
+            fuel: 0,
+            temperature: AMBIENT_TEMPERATURE,
+        }
+    }
+
+    pub fn can_stack_with(&self, other: &Item, item_tag_map: &ItemTagMap) -> bool {
+        return self.item_type == other.item_type
+            && self.material == other.material
+            && self.state == other.state
+            && self.fuel == other.fuel
+            && is_stackable(self.item_type, item_tag_map)
+            && self.contents.is_empty()
+            && other.contents.is_empty()
+            && self.liquid == other.liquid
+            && is_spoiled(self) == is_spoiled(other);
+    }
+
+    pub fn with_material(item_type: ItemType, material: Material) -> Self {
+        let mut result = Self::new(item_type);
+        result.material = material;
+        return result;
+    }
+
+    pub fn with_liquid(item_type: ItemType, liquid: Liquid) -> Self {
+        let mut result = Self::new(item_type);
+        result.liquid = Some(liquid);
+        return result;
+    }
+
+    // Removes up to `quantity` from this stack and returns them as a new
+    // stack. Splitting off the whole stack leaves this one with a quantity
+    // of zero, which the caller is expected to clean up.
+    pub fn split(&mut self, quantity: u32) -> Item {
+        let amount = u32::min(quantity, self.quantity);
+        self.quantity -= amount;
+
+        let mut result = self.clone();
+        result.quantity = amount;
+        return result;
+    }
 }
 
-#[derive(Clone, Copy, Debug, Enum)]
+#[derive(Clone, Copy, Debug, Enum, EnumString, PartialEq, Eq)]
 pub enum ItemType {
     Abacus,
     Amulet,
@@ -72,6 +148,7 @@ pub enum ItemType {
     HandCrossbow,
     HealersKit,
     HeavyCrossbow,
+    Herbs,
     HideArmor,
     Horn,
     Hourglass,
@@ -147,6 +224,7 @@ pub enum ItemType {
     Staff,
     StuddedLeatherArmor,
     Tent,
+    ThievesTools,
     Tinderbox,
     Torch,
     Totem,
@@ -163,6 +241,7 @@ pub enum ItemType {
     Whistle,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CoinType {
     Copper,
     Silver,
@@ -171,9 +250,21 @@ pub enum CoinType {
     Platinum
 }
 
+// What each coin is worth in copper pieces
+pub const fn get_coin_value(coin_type: CoinType) -> u32 {
+    match coin_type {
+        CoinType::Copper => 1,
+        CoinType::Silver => 10,
+        CoinType::Electrum => 50,
+        CoinType::Gold => 100,
+        CoinType::Platinum => 1000,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CurrencyAmount {
-    amount: u32,
-    coin_type: CoinType,
+    pub amount: u32,
+    pub coin_type: CoinType,
 }
 
 impl CurrencyAmount {
@@ -186,6 +277,90 @@ impl CurrencyAmount {
             coin_type: CoinType::Copper
         }
     }
+
+    // Scales the amount by a ratio, breaking it into smaller coins if it no
+    // longer comes to a whole number of the original ones
+    pub fn scaled(&self, numerator: u32, denominator: u32) -> Self {
+        if denominator == 0 || numerator == denominator {
+            return *self;
+        }
+        let copper: u64 = self.amount as u64 * get_coin_value(self.coin_type) as u64 * numerator as u64 / denominator as u64;
+        for coin_type in [CoinType::Gold, CoinType::Silver] {
+            let coin_value = get_coin_value(coin_type) as u64;
+            if copper >= coin_value && copper.is_multiple_of(coin_value) {
+                return Self::new((copper / coin_value) as u32, coin_type);
+            }
+        }
+        return Self::new(copper as u32, CoinType::Copper);
+    }
+
+    pub const fn in_copper(&self) -> u32 {
+        return self.amount * get_coin_value(self.coin_type);
+    }
+
+    pub const fn times(&self, quantity: u32) -> Self {
+        Self {
+            amount: self.amount * quantity,
+            coin_type: self.coin_type,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capacity {
+    pub weight: Weight,
+    pub volume: Volume,
+}
+
+impl Capacity {
+    pub const fn new(pounds: Weight, pints: Volume) -> Self {
+        Self {
+            weight: pounds * WEIGHT_PER_POUND,
+            volume: pints * substance::VOLUME_PER_PINT,
+        }
+    }
+}
+
+// How much a container can hold. Solid contents are limited by weight,
+// liquids by volume.
+pub const fn get_capacity(item_type: ItemType) -> Option<Capacity> {
+    match item_type {
+        ItemType::Backpack => Some(Capacity::new(30, 0)),
+        ItemType::Barrel => Some(Capacity::new(200, 320)),
+        ItemType::Basket => Some(Capacity::new(40, 0)),
+        ItemType::Bottle => Some(Capacity { weight: 0, volume: 24 }),
+        ItemType::Bucket => Some(Capacity::new(20, 24)),
+        ItemType::Chest => Some(Capacity::new(300, 0)),
+        ItemType::ComponentPouch => Some(Capacity::new(2, 0)),
+        ItemType::CrossbowBoltCase => Some(Capacity { weight: 1500, volume: 0 }),
+        ItemType::Flask => Some(Capacity::new(0, 1)),
+        ItemType::Jug => Some(Capacity::new(0, 8)),
+        ItemType::Lamp => Some(Capacity::new(0, 1)),
+        ItemType::Lantern => Some(Capacity::new(0, 1)),
+        ItemType::MapCase => Some(Capacity::new(1, 0)),
+        ItemType::Pot => Some(Capacity::new(10, 8)),
+        ItemType::Pouch => Some(Capacity::new(6, 0)),
+        ItemType::Quiver => Some(Capacity::new(1, 0)),
+        ItemType::Sack => Some(Capacity::new(30, 0)),
+        ItemType::Vial => Some(Capacity { weight: 0, volume: 4 }),
+        ItemType::Waterskin => Some(Capacity::new(0, 4)),
+        _ => None,
+    }
+}
+
+// If the item is a bundle of smaller items, returns the type and number of
+// items that opening it produces.
+pub const fn get_bundle_contents(item_type: ItemType) -> Option<(ItemType, u32)> {
+    match item_type {
+        ItemType::ArrowBundle => Some((ItemType::Arrow, 20)),
+        ItemType::BallBearingBundle => Some((ItemType::BallBearing, 1000)),
+        ItemType::BlowgunNeedleBundle => Some((ItemType::BlowgunNeedle, 50)),
+        ItemType::CaltropBundle => Some((ItemType::Caltrop, 20)),
+        ItemType::CrossbowBoltBundle => Some((ItemType::CrossbowBolt, 20)),
+        ItemType::SlingBulletBundle => Some((ItemType::SlingBullet, 20)),
+        ItemType::SpikeBundle => Some((ItemType::Spike, 10)),
+        _ => None,
+    }
 }
 
 pub const fn get_cost(item_type: ItemType) -> CurrencyAmount {
@@ -254,6 +429,7 @@ pub const fn get_cost(item_type: ItemType) -> CurrencyAmount {
         ItemType::HandCrossbow => CurrencyAmount::new(75, CoinType::Gold),
         ItemType::HealersKit => CurrencyAmount::new(5, CoinType::Gold),
         ItemType::HeavyCrossbow => CurrencyAmount::new(50, CoinType::Gold),
+        ItemType::Herbs => CurrencyAmount::new(1, CoinType::Copper),
         ItemType::HideArmor => CurrencyAmount::new(10, CoinType::Gold),
         ItemType::Horn => CurrencyAmount::new(3, CoinType::Gold),
         ItemType::Hourglass => CurrencyAmount::new(25, CoinType::Gold),
@@ -329,6 +505,7 @@ pub const fn get_cost(item_type: ItemType) -> CurrencyAmount {
         ItemType::Staff => CurrencyAmount::new(5, CoinType::Gold),
         ItemType::StuddedLeatherArmor => CurrencyAmount::new(45, CoinType::Gold),
         ItemType::Tent => CurrencyAmount::new(2, CoinType::Gold),
+        ItemType::ThievesTools => CurrencyAmount::new(25, CoinType::Gold),
         ItemType::Tinderbox => CurrencyAmount::new(5, CoinType::Silver),
         ItemType::Torch => CurrencyAmount::new(1, CoinType::Copper),
         ItemType::Totem => CurrencyAmount::new(1, CoinType::Gold),
@@ -353,14 +530,14 @@ pub fn generate_item_tag_map() -> ItemTagMap {
     enum_map! {
         ItemType::Abacus => vec!(),
         ItemType::Amulet => vec!(),
-        ItemType::Arrow => vec!(Tag::Ammunition),
-        ItemType::ArrowBundle => vec!(),
-        ItemType::Backpack => vec!(),
+        ItemType::Arrow => vec!(Tag::Ammunition, Tag::Stackable),
+        ItemType::ArrowBundle => vec!(Tag::Stackable),
+        ItemType::Backpack => vec!(Tag::SolidContainer),
         ItemType::Bagpipes => vec!(Tag::Instrument),
-        ItemType::BallBearing => vec!(),
-        ItemType::BallBearingBundle => vec!(),
-        ItemType::Barrel => vec!(),
-        ItemType::Basket => vec!(),
+        ItemType::BallBearing => vec!(Tag::Stackable),
+        ItemType::BallBearingBundle => vec!(Tag::Stackable),
+        ItemType::Barrel => vec!(Tag::LiquidContainer, Tag::Openable, Tag::SolidContainer),
+        ItemType::Basket => vec!(Tag::SolidContainer),
         ItemType::BatteringRam => vec!(),
         ItemType::Battleaxe => vec!(),
         ItemType::Bedroll => vec!(),
@@ -368,40 +545,40 @@ pub fn generate_item_tag_map() -> ItemTagMap {
         ItemType::Blanket => vec!(),
         ItemType::BlockAndTackle => vec!(),
         ItemType::Blowgun => vec!(),
-        ItemType::BlowgunNeedle => vec!(),
-        ItemType::BlowgunNeedleBundle => vec!(),
+        ItemType::BlowgunNeedle => vec!(Tag::Ammunition, Tag::Stackable),
+        ItemType::BlowgunNeedleBundle => vec!(Tag::Stackable),
         ItemType::Book => vec!(),
-        ItemType::Bottle => vec!(),
-        ItemType::BreastplateArmor => vec!(),
-        ItemType::Bucket => vec!(),
-        ItemType::Caltrop => vec!(),
-        ItemType::CaltropBundle => vec!(),
-        ItemType::Candle => vec!(),
+        ItemType::Bottle => vec!(Tag::LiquidContainer),
+        ItemType::BreastplateArmor => vec!(Tag::MediumArmor),
+        ItemType::Bucket => vec!(Tag::LiquidContainer, Tag::SolidContainer),
+        ItemType::Caltrop => vec!(Tag::Stackable),
+        ItemType::CaltropBundle => vec!(Tag::Stackable),
+        ItemType::Candle => vec!(Tag::Stackable),
         ItemType::Chain => vec!(),
-        ItemType::ChainMailArmor => vec!(),
-        ItemType::ChainShirtArmor => vec!(),
-        ItemType::Chalk => vec!(),
-        ItemType::Chest => vec!(),
+        ItemType::ChainMailArmor => vec!(Tag::HeavyArmor),
+        ItemType::ChainShirtArmor => vec!(Tag::MediumArmor),
+        ItemType::Chalk => vec!(Tag::Stackable),
+        ItemType::Chest => vec!(Tag::Lockable, Tag::Openable, Tag::SolidContainer),
         ItemType::ClimbersKit => vec!(),
         ItemType::ClothesCommon => vec!(),
         ItemType::ClothesCostume => vec!(),
         ItemType::ClothesFine => vec!(),
         ItemType::ClothesTraveler => vec!(),
         ItemType::Club => vec!(),
-        ItemType::Coin => vec!(),
-        ItemType::ComponentPouch => vec!(),
-        ItemType::CrossbowBolt => vec!(),
-        ItemType::CrossbowBoltBundle => vec!(),
-        ItemType::CrossbowBoltCase => vec!(),
+        ItemType::Coin => vec!(Tag::Stackable),
+        ItemType::ComponentPouch => vec!(Tag::SolidContainer),
+        ItemType::CrossbowBolt => vec!(Tag::Ammunition, Tag::Stackable),
+        ItemType::CrossbowBoltBundle => vec!(Tag::Stackable),
+        ItemType::CrossbowBoltCase => vec!(Tag::SolidContainer),
         ItemType::Crowbar => vec!(),
         ItemType::Crystal => vec!(),
         ItemType::Dagger => vec!(),
-        ItemType::Dart => vec!(),
+        ItemType::Dart => vec!(Tag::Stackable),
         ItemType::Drum => vec!(),
         ItemType::Emblem => vec!(),
         ItemType::FishingTackle => vec!(),
         ItemType::Flail => vec!(),
-        ItemType::Flask => vec!(),
+        ItemType::Flask => vec!(Tag::LiquidContainer),
         ItemType::Flute => vec!(),
         ItemType::Glaive => vec!(),
         ItemType::GrapplingHook => vec!(),
@@ -409,24 +586,25 @@ pub fn generate_item_tag_map() -> ItemTagMap {
         ItemType::Greatclub => vec!(),
         ItemType::Greatsword => vec!(),
         ItemType::Halberd => vec!(),
-        ItemType::HalfPlateArmor => vec!(),
+        ItemType::HalfPlateArmor => vec!(Tag::MediumArmor),
         ItemType::Hammer => vec!(),
         ItemType::Handaxe => vec!(),
         ItemType::HandCrossbow => vec!(),
         ItemType::HealersKit => vec!(),
         ItemType::HeavyCrossbow => vec!(),
-        ItemType::HideArmor => vec!(),
+        ItemType::Herbs => vec!(Tag::Food, Tag::Organic, Tag::Stackable),
+        ItemType::HideArmor => vec!(Tag::MediumArmor),
         ItemType::Horn => vec!(),
         ItemType::Hourglass => vec!(),
         ItemType::HuntingTrap => vec!(),
         ItemType::Ink => vec!(),
-        ItemType::Javelin => vec!(),
-        ItemType::Jug => vec!(),
+        ItemType::Javelin => vec!(Tag::Stackable),
+        ItemType::Jug => vec!(Tag::LiquidContainer),
         ItemType::Ladder => vec!(),
-        ItemType::Lamp => vec!(),
+        ItemType::Lamp => vec!(Tag::LiquidContainer),
         ItemType::Lance => vec!(),
-        ItemType::Lantern => vec!(),
-        ItemType::LeatherArmor => vec!(),
+        ItemType::Lantern => vec!(Tag::LiquidContainer),
+        ItemType::LeatherArmor => vec!(Tag::LightArmor),
         ItemType::LightCrossbow => vec!(),
         ItemType::LightHammer => vec!(),
         ItemType::Lock => vec!(),
@@ -437,69 +615,70 @@ pub fn generate_item_tag_map() -> ItemTagMap {
         ItemType::Mace => vec!(),
         ItemType::MagnifyingGlass => vec!(),
         ItemType::Manacles => vec!(),
-        ItemType::MapCase => vec!(),
+        ItemType::MapCase => vec!(Tag::SolidContainer),
         ItemType::Maul => vec!(),
         ItemType::MessKit => vec!(),
         ItemType::Mirror => vec!(),
         ItemType::Morningstar => vec!(),
         ItemType::Net => vec!(),
         ItemType::Orb => vec!(),
-        ItemType::PaddedArmor => vec!(),
+        ItemType::PaddedArmor => vec!(Tag::LightArmor),
         ItemType::PanFlute => vec!(),
-        ItemType::Paper => vec!(),
-        ItemType::Parchment => vec!(),
+        ItemType::Paper => vec!(Tag::Stackable),
+        ItemType::Parchment => vec!(Tag::Stackable),
         ItemType::Pen => vec!(),
         ItemType::Perfume => vec!(),
         ItemType::Pickaxe => vec!(),
         ItemType::Pike => vec!(),
-        ItemType::Piton => vec!(),
-        ItemType::PlateArmor => vec!(),
+        ItemType::Piton => vec!(Tag::Stackable),
+        ItemType::PlateArmor => vec!(Tag::HeavyArmor),
         ItemType::Pole => vec!(),
-        ItemType::Pot => vec!(),
-        ItemType::Pouch => vec!(),
+        ItemType::Pot => vec!(Tag::LiquidContainer, Tag::SolidContainer),
+        ItemType::Pouch => vec!(Tag::SolidContainer),
         ItemType::Quarterstaff => vec!(),
-        ItemType::Quiver => vec!(),
+        ItemType::Quiver => vec!(Tag::SolidContainer),
         ItemType::Rapier => vec!(),
-        ItemType::Rations => vec!(),
+        ItemType::Rations => vec!(Tag::Food, Tag::Organic, Tag::Stackable),
         ItemType::Reliquary => vec!(),
         ItemType::Ring => vec!(),
-        ItemType::RingMailArmor => vec!(),
+        ItemType::RingMailArmor => vec!(Tag::HeavyArmor),
         ItemType::Robes => vec!(),
         ItemType::Rod => vec!(),
         ItemType::Rope => vec!(),
-        ItemType::Sack => vec!(),
+        ItemType::Sack => vec!(Tag::SolidContainer),
         ItemType::Scale => vec!(),
-        ItemType::ScaleMailArmor => vec!(),
+        ItemType::ScaleMailArmor => vec!(Tag::MediumArmor),
         ItemType::Scimitar => vec!(),
-        ItemType::Shield => vec!(),
+        ItemType::Shield => vec!(Tag::Shield),
         ItemType::Shortbow => vec!(),
         ItemType::Shortsword => vec!(),
         ItemType::Shovel => vec!(),
         ItemType::Sickle => vec!(),
         ItemType::Sledgehammer => vec!(),
         ItemType::Sling => vec!(),
-        ItemType::SlingBullet => vec!(),
-        ItemType::SlingBulletBundle => vec!(),
-        ItemType::Soap => vec!(),
+        ItemType::SlingBullet => vec!(Tag::Ammunition, Tag::Stackable),
+        ItemType::SlingBulletBundle => vec!(Tag::Stackable),
+        ItemType::Soap => vec!(Tag::Stackable),
         ItemType::Spear => vec!(),
         ItemType::Spellbook => vec!(),
-        ItemType::Spike => vec!(),
-        ItemType::SpikeBundle => vec!(),
-        ItemType::SplintArmor => vec!(),
+        ItemType::Spike => vec!(Tag::Stackable),
+        ItemType::SpikeBundle => vec!(Tag::Stackable),
+        ItemType::SplintArmor => vec!(Tag::HeavyArmor),
         ItemType::Spyglass => vec!(),
         ItemType::Staff => vec!(),
-        ItemType::StuddedLeatherArmor => vec!(),
+        ItemType::StuddedLeatherArmor => vec!(Tag::LightArmor),
         ItemType::Tent => vec!(),
+        ItemType::ThievesTools => vec!(),
         ItemType::Tinderbox => vec!(),
-        ItemType::Torch => vec!(),
+        ItemType::Torch => vec!(Tag::Stackable),
         ItemType::Totem => vec!(),
         ItemType::Trident => vec!(),
-        ItemType::Vial => vec!(),
+        ItemType::Vial => vec!(Tag::LiquidContainer),
         ItemType::Violin => vec!(),
         ItemType::Wand => vec!(),
         ItemType::Warhammer => vec!(),
         ItemType::WarPick => vec!(),
-        ItemType::Waterskin => vec!(),
+        ItemType::Waterskin => vec!(Tag::LiquidContainer),
         ItemType::Wax => vec!(),
         ItemType::Whetstone => vec!(),
         ItemType::Whip => vec!(),
@@ -507,4 +686,774 @@ pub fn generate_item_tag_map() -> ItemTagMap {
     }
 }
 
-//TODO(ches) Weights
+pub const fn get_name(item_type: ItemType) -> &'static str {
+    match item_type {
+        ItemType::Abacus => "Abacus",
+        ItemType::Amulet => "Amulet",
+        ItemType::Arrow => "Arrow",
+        ItemType::ArrowBundle => "Bundle of Arrows",
+        ItemType::Backpack => "Backpack",
+        ItemType::Bagpipes => "Bagpipes",
+        ItemType::BallBearing => "Ball Bearing",
+        ItemType::BallBearingBundle => "Bag of Ball Bearings",
+        ItemType::Barrel => "Barrel",
+        ItemType::Basket => "Basket",
+        ItemType::BatteringRam => "Battering Ram",
+        ItemType::Battleaxe => "Battleaxe",
+        ItemType::Bedroll => "Bedroll",
+        ItemType::Bell => "Bell",
+        ItemType::Blanket => "Blanket",
+        ItemType::BlockAndTackle => "Block and Tackle",
+        ItemType::Blowgun => "Blowgun",
+        ItemType::BlowgunNeedle => "Blowgun Needle",
+        ItemType::BlowgunNeedleBundle => "Bundle of Blowgun Needles",
+        ItemType::Book => "Book",
+        ItemType::Bottle => "Bottle",
+        ItemType::BreastplateArmor => "Breastplate",
+        ItemType::Bucket => "Bucket",
+        ItemType::Caltrop => "Caltrop",
+        ItemType::CaltropBundle => "Bag of Caltrops",
+        ItemType::Candle => "Candle",
+        ItemType::Chain => "Chain",
+        ItemType::ChainMailArmor => "Chain Mail",
+        ItemType::ChainShirtArmor => "Chain Shirt",
+        ItemType::Chalk => "Chalk",
+        ItemType::Chest => "Chest",
+        ItemType::ClimbersKit => "Climber's Kit",
+        ItemType::ClothesCommon => "Common Clothes",
+        ItemType::ClothesCostume => "Costume",
+        ItemType::ClothesFine => "Fine Clothes",
+        ItemType::ClothesTraveler => "Traveler's Clothes",
+        ItemType::Club => "Club",
+        ItemType::Coin => "Coin",
+        ItemType::ComponentPouch => "Component Pouch",
+        ItemType::CrossbowBolt => "Crossbow Bolt",
+        ItemType::CrossbowBoltBundle => "Bundle of Crossbow Bolts",
+        ItemType::CrossbowBoltCase => "Crossbow Bolt Case",
+        ItemType::Crowbar => "Crowbar",
+        ItemType::Crystal => "Crystal",
+        ItemType::Dagger => "Dagger",
+        ItemType::Dart => "Dart",
+        ItemType::Drum => "Drum",
+        ItemType::Emblem => "Emblem",
+        ItemType::FishingTackle => "Fishing Tackle",
+        ItemType::Flail => "Flail",
+        ItemType::Flask => "Flask",
+        ItemType::Flute => "Flute",
+        ItemType::Glaive => "Glaive",
+        ItemType::GrapplingHook => "Grappling Hook",
+        ItemType::Greataxe => "Greataxe",
+        ItemType::Greatclub => "Greatclub",
+        ItemType::Greatsword => "Greatsword",
+        ItemType::Halberd => "Halberd",
+        ItemType::HalfPlateArmor => "Half Plate",
+        ItemType::Hammer => "Hammer",
+        ItemType::Handaxe => "Handaxe",
+        ItemType::HandCrossbow => "Hand Crossbow",
+        ItemType::HealersKit => "Healer's Kit",
+        ItemType::HeavyCrossbow => "Heavy Crossbow",
+        ItemType::Herbs => "Herbs",
+        ItemType::HideArmor => "Hide Armor",
+        ItemType::Horn => "Horn",
+        ItemType::Hourglass => "Hourglass",
+        ItemType::HuntingTrap => "Hunting Trap",
+        ItemType::Ink => "Ink",
+        ItemType::Javelin => "Javelin",
+        ItemType::Jug => "Jug",
+        ItemType::Ladder => "Ladder",
+        ItemType::Lamp => "Lamp",
+        ItemType::Lance => "Lance",
+        ItemType::Lantern => "Lantern",
+        ItemType::LeatherArmor => "Leather Armor",
+        ItemType::LightCrossbow => "Light Crossbow",
+        ItemType::LightHammer => "Light Hammer",
+        ItemType::Lock => "Lock",
+        ItemType::Longbow => "Longbow",
+        ItemType::Longsword => "Longsword",
+        ItemType::Lute => "Lute",
+        ItemType::Lyre => "Lyre",
+        ItemType::Mace => "Mace",
+        ItemType::MagnifyingGlass => "Magnifying Glass",
+        ItemType::Manacles => "Manacles",
+        ItemType::MapCase => "Map Case",
+        ItemType::Maul => "Maul",
+        ItemType::MessKit => "Mess Kit",
+        ItemType::Mirror => "Mirror",
+        ItemType::Morningstar => "Morningstar",
+        ItemType::Net => "Net",
+        ItemType::Orb => "Orb",
+        ItemType::PaddedArmor => "Padded Armor",
+        ItemType::PanFlute => "Pan Flute",
+        ItemType::Paper => "Paper",
+        ItemType::Parchment => "Parchment",
+        ItemType::Pen => "Pen",
+        ItemType::Perfume => "Perfume",
+        ItemType::Pickaxe => "Pickaxe",
+        ItemType::Pike => "Pike",
+        ItemType::Piton => "Piton",
+        ItemType::PlateArmor => "Plate Armor",
+        ItemType::Pole => "Pole",
+        ItemType::Pot => "Pot",
+        ItemType::Pouch => "Pouch",
+        ItemType::Quarterstaff => "Quarterstaff",
+        ItemType::Quiver => "Quiver",
+        ItemType::Rapier => "Rapier",
+        ItemType::Rations => "Rations",
+        ItemType::Reliquary => "Reliquary",
+        ItemType::Ring => "Ring",
+        ItemType::RingMailArmor => "Ring Mail",
+        ItemType::Robes => "Robes",
+        ItemType::Rod => "Rod",
+        ItemType::Rope => "Rope",
+        ItemType::Sack => "Sack",
+        ItemType::Scale => "Scale",
+        ItemType::ScaleMailArmor => "Scale Mail",
+        ItemType::Scimitar => "Scimitar",
+        ItemType::Shield => "Shield",
+        ItemType::Shortbow => "Shortbow",
+        ItemType::Shortsword => "Shortsword",
+        ItemType::Shovel => "Shovel",
+        ItemType::Sickle => "Sickle",
+        ItemType::Sledgehammer => "Sledgehammer",
+        ItemType::Sling => "Sling",
+        ItemType::SlingBullet => "Sling Bullet",
+        ItemType::SlingBulletBundle => "Pouch of Sling Bullets",
+        ItemType::Soap => "Soap",
+        ItemType::Spear => "Spear",
+        ItemType::Spellbook => "Spellbook",
+        ItemType::Spike => "Iron Spike",
+        ItemType::SpikeBundle => "Bundle of Iron Spikes",
+        ItemType::SplintArmor => "Splint Armor",
+        ItemType::Spyglass => "Spyglass",
+        ItemType::Staff => "Staff",
+        ItemType::StuddedLeatherArmor => "Studded Leather Armor",
+        ItemType::Tent => "Tent",
+        ItemType::ThievesTools => "Thieves' Tools",
+        ItemType::Tinderbox => "Tinderbox",
+        ItemType::Torch => "Torch",
+        ItemType::Totem => "Totem",
+        ItemType::Trident => "Trident",
+        ItemType::Vial => "Vial",
+        ItemType::Violin => "Violin",
+        ItemType::Wand => "Wand",
+        ItemType::Warhammer => "Warhammer",
+        ItemType::WarPick => "War Pick",
+        ItemType::Waterskin => "Waterskin",
+        ItemType::Wax => "Wax",
+        ItemType::Whetstone => "Whetstone",
+        ItemType::Whip => "Whip",
+        ItemType::Whistle => "Whistle",
+    }
+}
+
+pub const fn get_weight(item_type: ItemType) -> Weight {
+    match item_type {
+        ItemType::Abacus => 2000,
+        ItemType::Amulet => 1000,
+        ItemType::Arrow => 50,
+        ItemType::ArrowBundle => 1000,
+        ItemType::Backpack => 5000,
+        ItemType::Bagpipes => 6000,
+        ItemType::BallBearing => 2,
+        ItemType::BallBearingBundle => 2000,
+        ItemType::Barrel => 70000,
+        ItemType::Basket => 2000,
+        ItemType::BatteringRam => 35000,
+        ItemType::Battleaxe => 4000,
+        ItemType::Bedroll => 7000,
+        ItemType::Bell => 0,
+        ItemType::Blanket => 3000,
+        ItemType::BlockAndTackle => 5000,
+        ItemType::Blowgun => 1000,
+        ItemType::BlowgunNeedle => 20,
+        ItemType::BlowgunNeedleBundle => 1000,
+        ItemType::Book => 5000,
+        ItemType::Bottle => 2000,
+        ItemType::BreastplateArmor => 20000,
+        ItemType::Bucket => 2000,
+        ItemType::Caltrop => 100,
+        ItemType::CaltropBundle => 2000,
+        ItemType::Candle => 0,
+        ItemType::Chain => 10000,
+        ItemType::ChainMailArmor => 55000,
+        ItemType::ChainShirtArmor => 20000,
+        ItemType::Chalk => 0,
+        ItemType::Chest => 25000,
+        ItemType::ClimbersKit => 12000,
+        ItemType::ClothesCommon => 3000,
+        ItemType::ClothesCostume => 4000,
+        ItemType::ClothesFine => 6000,
+        ItemType::ClothesTraveler => 4000,
+        ItemType::Club => 2000,
+        ItemType::Coin => 20,
+        ItemType::ComponentPouch => 2000,
+        ItemType::CrossbowBolt => 75,
+        ItemType::CrossbowBoltBundle => 1500,
+        ItemType::CrossbowBoltCase => 1000,
+        ItemType::Crowbar => 5000,
+        ItemType::Crystal => 1000,
+        ItemType::Dagger => 1000,
+        ItemType::Dart => 250,
+        ItemType::Drum => 3000,
+        ItemType::Emblem => 0,
+        ItemType::FishingTackle => 4000,
+        ItemType::Flail => 2000,
+        ItemType::Flask => 1000,
+        ItemType::Flute => 1000,
+        ItemType::Glaive => 6000,
+        ItemType::GrapplingHook => 4000,
+        ItemType::Greataxe => 7000,
+        ItemType::Greatclub => 10000,
+        ItemType::Greatsword => 6000,
+        ItemType::Halberd => 6000,
+        ItemType::HalfPlateArmor => 40000,
+        ItemType::Hammer => 3000,
+        ItemType::Handaxe => 2000,
+        ItemType::HandCrossbow => 3000,
+        ItemType::HealersKit => 3000,
+        ItemType::HeavyCrossbow => 18000,
+        ItemType::Herbs => 100,
+        ItemType::HideArmor => 12000,
+        ItemType::Horn => 2000,
+        ItemType::Hourglass => 1000,
+        ItemType::HuntingTrap => 25000,
+        ItemType::Ink => 0,
+        ItemType::Javelin => 2000,
+        ItemType::Jug => 4000,
+        ItemType::Ladder => 25000,
+        ItemType::Lamp => 1000,
+        ItemType::Lance => 6000,
+        ItemType::Lantern => 2000,
+        ItemType::LeatherArmor => 10000,
+        ItemType::LightCrossbow => 5000,
+        ItemType::LightHammer => 2000,
+        ItemType::Lock => 1000,
+        ItemType::Longbow => 2000,
+        ItemType::Longsword => 3000,
+        ItemType::Lute => 2000,
+        ItemType::Lyre => 2000,
+        ItemType::Mace => 4000,
+        ItemType::MagnifyingGlass => 0,
+        ItemType::Manacles => 6000,
+        ItemType::MapCase => 1000,
+        ItemType::Maul => 10000,
+        ItemType::MessKit => 1000,
+        ItemType::Mirror => 500,
+        ItemType::Morningstar => 4000,
+        ItemType::Net => 3000,
+        ItemType::Orb => 3000,
+        ItemType::PaddedArmor => 8000,
+        ItemType::PanFlute => 2000,
+        ItemType::Paper => 0,
+        ItemType::Parchment => 0,
+        ItemType::Pen => 0,
+        ItemType::Perfume => 0,
+        ItemType::Pickaxe => 10000,
+        ItemType::Pike => 18000,
+        ItemType::Piton => 250,
+        ItemType::PlateArmor => 65000,
+        ItemType::Pole => 7000,
+        ItemType::Pot => 10000,
+        ItemType::Pouch => 1000,
+        ItemType::Quarterstaff => 4000,
+        ItemType::Quiver => 1000,
+        ItemType::Rapier => 2000,
+        ItemType::Rations => 2000,
+        ItemType::Reliquary => 2000,
+        ItemType::Ring => 0,
+        ItemType::RingMailArmor => 40000,
+        ItemType::Robes => 4000,
+        ItemType::Rod => 2000,
+        ItemType::Rope => 10000,
+        ItemType::Sack => 500,
+        ItemType::Scale => 3000,
+        ItemType::ScaleMailArmor => 45000,
+        ItemType::Scimitar => 3000,
+        ItemType::Shield => 6000,
+        ItemType::Shortbow => 2000,
+        ItemType::Shortsword => 2000,
+        ItemType::Shovel => 5000,
+        ItemType::Sickle => 2000,
+        ItemType::Sledgehammer => 10000,
+        ItemType::Sling => 0,
+        ItemType::SlingBullet => 75,
+        ItemType::SlingBulletBundle => 1500,
+        ItemType::Soap => 0,
+        ItemType::Spear => 3000,
+        ItemType::Spellbook => 3000,
+        ItemType::Spike => 500,
+        ItemType::SpikeBundle => 5000,
+        ItemType::SplintArmor => 60000,
+        ItemType::Spyglass => 1000,
+        ItemType::Staff => 4000,
+        ItemType::StuddedLeatherArmor => 13000,
+        ItemType::Tent => 20000,
+        ItemType::ThievesTools => 1000,
+        ItemType::Tinderbox => 1000,
+        ItemType::Torch => 1000,
+        ItemType::Totem => 0,
+        ItemType::Trident => 4000,
+        ItemType::Vial => 0,
+        ItemType::Violin => 1000,
+        ItemType::Wand => 1000,
+        ItemType::Warhammer => 2000,
+        ItemType::WarPick => 2000,
+        ItemType::Waterskin => 1000,
+        ItemType::Wax => 0,
+        ItemType::Whetstone => 1000,
+        ItemType::Whip => 3000,
+        ItemType::Whistle => 0,
+    }
+}
+
+// What each kind of item is usually made of. Its weight and price are for
+// this material.
+pub const fn get_default_material(item_type: ItemType) -> Material {
+    match item_type {
+        ItemType::Abacus => Material::Wood,
+        ItemType::Amulet => Material::Silver,
+        ItemType::Arrow => Material::Steel,
+        ItemType::ArrowBundle => Material::Steel,
+        ItemType::Backpack => Material::Leather,
+        ItemType::Bagpipes => Material::Leather,
+        ItemType::BallBearing => Material::Iron,
+        ItemType::BallBearingBundle => Material::Iron,
+        ItemType::Barrel => Material::Wood,
+        ItemType::Basket => Material::Wood,
+        ItemType::BatteringRam => Material::Wood,
+        ItemType::Battleaxe => Material::Steel,
+        ItemType::Bedroll => Material::Cloth,
+        ItemType::Bell => Material::Bronze,
+        ItemType::Blanket => Material::Cloth,
+        ItemType::BlockAndTackle => Material::Wood,
+        ItemType::Blowgun => Material::Wood,
+        ItemType::BlowgunNeedle => Material::Steel,
+        ItemType::BlowgunNeedleBundle => Material::Steel,
+        ItemType::Book => Material::Paper,
+        ItemType::Bottle => Material::Glass,
+        ItemType::BreastplateArmor => Material::Steel,
+        ItemType::Bucket => Material::Wood,
+        ItemType::Caltrop => Material::Iron,
+        ItemType::CaltropBundle => Material::Iron,
+        ItemType::Candle => Material::Wax,
+        ItemType::Chain => Material::Iron,
+        ItemType::ChainMailArmor => Material::Steel,
+        ItemType::ChainShirtArmor => Material::Steel,
+        ItemType::Chalk => Material::Stone,
+        ItemType::Chest => Material::Wood,
+        ItemType::ClimbersKit => Material::Iron,
+        ItemType::ClothesCommon => Material::Cloth,
+        ItemType::ClothesCostume => Material::Cloth,
+        ItemType::ClothesFine => Material::Cloth,
+        ItemType::ClothesTraveler => Material::Cloth,
+        ItemType::Club => Material::Wood,
+        ItemType::Coin => Material::Copper,
+        ItemType::ComponentPouch => Material::Leather,
+        ItemType::CrossbowBolt => Material::Steel,
+        ItemType::CrossbowBoltBundle => Material::Steel,
+        ItemType::CrossbowBoltCase => Material::Wood,
+        ItemType::Crowbar => Material::Iron,
+        ItemType::Crystal => Material::Glass,
+        ItemType::Dagger => Material::Steel,
+        ItemType::Dart => Material::Steel,
+        ItemType::Drum => Material::Wood,
+        ItemType::Emblem => Material::Silver,
+        ItemType::FishingTackle => Material::Wood,
+        ItemType::Flail => Material::Steel,
+        ItemType::Flask => Material::Glass,
+        ItemType::Flute => Material::Wood,
+        ItemType::Glaive => Material::Steel,
+        ItemType::GrapplingHook => Material::Iron,
+        ItemType::Greataxe => Material::Steel,
+        ItemType::Greatclub => Material::Wood,
+        ItemType::Greatsword => Material::Steel,
+        ItemType::Halberd => Material::Steel,
+        ItemType::HalfPlateArmor => Material::Steel,
+        ItemType::Hammer => Material::Iron,
+        ItemType::Handaxe => Material::Steel,
+        ItemType::HandCrossbow => Material::Wood,
+        ItemType::HealersKit => Material::Cloth,
+        ItemType::HeavyCrossbow => Material::Wood,
+        ItemType::Herbs => Material::Food,
+        ItemType::HideArmor => Material::Leather,
+        ItemType::Horn => Material::Bone,
+        ItemType::Hourglass => Material::Glass,
+        ItemType::HuntingTrap => Material::Iron,
+        ItemType::Ink => Material::Glass,
+        ItemType::Javelin => Material::Steel,
+        ItemType::Jug => Material::Stone,
+        ItemType::Ladder => Material::Wood,
+        ItemType::Lamp => Material::Bronze,
+        ItemType::Lance => Material::Steel,
+        ItemType::Lantern => Material::Iron,
+        ItemType::LeatherArmor => Material::Leather,
+        ItemType::LightCrossbow => Material::Wood,
+        ItemType::LightHammer => Material::Steel,
+        ItemType::Lock => Material::Iron,
+        ItemType::Longbow => Material::Wood,
+        ItemType::Longsword => Material::Steel,
+        ItemType::Lute => Material::Wood,
+        ItemType::Lyre => Material::Wood,
+        ItemType::Mace => Material::Steel,
+        ItemType::MagnifyingGlass => Material::Glass,
+        ItemType::Manacles => Material::Iron,
+        ItemType::MapCase => Material::Leather,
+        ItemType::Maul => Material::Iron,
+        ItemType::MessKit => Material::Iron,
+        ItemType::Mirror => Material::Steel,
+        ItemType::Morningstar => Material::Steel,
+        ItemType::Net => Material::Cloth,
+        ItemType::Orb => Material::Glass,
+        ItemType::PaddedArmor => Material::Cloth,
+        ItemType::PanFlute => Material::Wood,
+        ItemType::Paper => Material::Paper,
+        ItemType::Parchment => Material::Paper,
+        ItemType::Pen => Material::Wood,
+        ItemType::Perfume => Material::Glass,
+        ItemType::Pickaxe => Material::Iron,
+        ItemType::Pike => Material::Steel,
+        ItemType::Piton => Material::Iron,
+        ItemType::PlateArmor => Material::Steel,
+        ItemType::Pole => Material::Wood,
+        ItemType::Pot => Material::Iron,
+        ItemType::Pouch => Material::Cloth,
+        ItemType::Quarterstaff => Material::Wood,
+        ItemType::Quiver => Material::Leather,
+        ItemType::Rapier => Material::Steel,
+        ItemType::Rations => Material::Food,
+        ItemType::Reliquary => Material::Silver,
+        ItemType::Ring => Material::Gold,
+        ItemType::RingMailArmor => Material::Steel,
+        ItemType::Robes => Material::Cloth,
+        ItemType::Rod => Material::Wood,
+        ItemType::Rope => Material::Cloth,
+        ItemType::Sack => Material::Cloth,
+        ItemType::Scale => Material::Bronze,
+        ItemType::ScaleMailArmor => Material::Steel,
+        ItemType::Scimitar => Material::Steel,
+        ItemType::Shield => Material::Wood,
+        ItemType::Shortbow => Material::Wood,
+        ItemType::Shortsword => Material::Steel,
+        ItemType::Shovel => Material::Iron,
+        ItemType::Sickle => Material::Steel,
+        ItemType::Sledgehammer => Material::Iron,
+        ItemType::Sling => Material::Leather,
+        ItemType::SlingBullet => Material::Iron,
+        ItemType::SlingBulletBundle => Material::Iron,
+        ItemType::Soap => Material::Wax,
+        ItemType::Spear => Material::Steel,
+        ItemType::Spellbook => Material::Paper,
+        ItemType::Spike => Material::Iron,
+        ItemType::SpikeBundle => Material::Iron,
+        ItemType::SplintArmor => Material::Steel,
+        ItemType::Spyglass => Material::Glass,
+        ItemType::Staff => Material::Wood,
+        ItemType::StuddedLeatherArmor => Material::Leather,
+        ItemType::Tent => Material::Cloth,
+        ItemType::ThievesTools => Material::Steel,
+        ItemType::Tinderbox => Material::Steel,
+        ItemType::Torch => Material::Wood,
+        ItemType::Totem => Material::Wood,
+        ItemType::Trident => Material::Steel,
+        ItemType::Vial => Material::Glass,
+        ItemType::Violin => Material::Wood,
+        ItemType::Wand => Material::Wood,
+        ItemType::Warhammer => Material::Steel,
+        ItemType::WarPick => Material::Steel,
+        ItemType::Waterskin => Material::Leather,
+        ItemType::Wax => Material::Wax,
+        ItemType::Whetstone => Material::Stone,
+        ItemType::Whip => Material::Leather,
+        ItemType::Whistle => Material::Wood,
+    }
+}
+
+pub fn get_display_name(item: &Item) -> String {
+    let mut name = String::from(get_name(item.item_type));
+    if item.material != get_default_material(item.item_type) {
+        name = format!("{} {}", material::get_name(item.material), name);
+    }
+    if is_spoiled(item) {
+        name = format!("Spoiled {}", name);
+    }
+    if temperature::is_frozen(item) {
+        name = format!("Frozen {}", name);
+    } else if item.state.contains(Tag::Wet) {
+        name = format!("Wet {}", name);
+    }
+    if item.state.contains(Tag::Burning) {
+        name = format!("Lit {}", name);
+    } else if item.temperature >= temperature::HOT_TEMPERATURE {
+        name = format!("Hot {}", name);
+    }
+    if item.quantity > 1 {
+        return format!("{} ({})", name, item.quantity);
+    }
+    return name;
+}
+
+// How long things that can be lit and carried around burn for. Lamps burn
+// oil instead, and this is how long each ounce of it lasts.
+pub fn get_burn_time(item_type: ItemType) -> Option<Seconds> {
+    return match item_type {
+        ItemType::Candle => Some(SECONDS_PER_HOUR),
+        ItemType::Lamp => Some(OIL_BURN_TIME),
+        ItemType::Lantern => Some(OIL_BURN_TIME),
+        ItemType::Torch => Some(SECONDS_PER_HOUR),
+        _ => None,
+    };
+}
+
+// A pint of oil keeps a lamp going for six hours
+pub const OIL_BURN_TIME: Seconds = 6 * SECONDS_PER_HOUR / substance::VOLUME_PER_PINT as Seconds;
+
+pub fn burns_oil(item_type: ItemType) -> bool {
+    return matches!(item_type, ItemType::Lamp | ItemType::Lantern);
+}
+
+// How far a lit item sheds bright light, in tiles. Dim light reaches as far
+// again beyond that.
+pub fn get_light_radius(item_type: ItemType) -> u16 {
+    return match item_type {
+        ItemType::Candle => 1,
+        ItemType::Lamp => 3,
+        ItemType::Lantern => 6,
+        ItemType::Torch => 4,
+        _ => 0,
+    };
+}
+
+// How long food keeps you going for
+pub fn get_nutrition(item_type: ItemType) -> Seconds {
+    return match item_type {
+        ItemType::Herbs => SECONDS_PER_DAY / 4,
+        ItemType::Rations => SECONDS_PER_DAY,
+        _ => 0,
+    };
+}
+
+// How long organic items last before they spoil
+pub fn get_shelf_life(item_type: ItemType) -> Option<Seconds> {
+    return match item_type {
+        ItemType::Herbs => Some(3 * SECONDS_PER_DAY),
+        ItemType::Rations => Some(60 * SECONDS_PER_DAY),
+        _ => None,
+    };
+}
+
+pub fn is_spoiled(item: &Item) -> bool {
+    return match get_shelf_life(item.item_type) {
+        Some(shelf_life) => item.age >= shelf_life,
+        None => false,
+    };
+}
+
+// Objects with a lower armor class than the attack roll against them break
+pub fn get_armor_class(item: &Item) -> u8 {
+    return material::get_armor_class(item.material);
+}
+
+pub fn get_price(item: &Item) -> CurrencyAmount {
+    let default_value = material::get_value(get_default_material(item.item_type));
+    let value = material::get_value(item.material);
+    return get_cost(item.item_type).scaled(value, default_value).times(item.quantity);
+}
+
+// Everything the item is, from its kind, its material and its current state
+pub fn get_tags(item: &Item, tag_cache: &TagCache) -> TagSet {
+    return tag_cache.items[item.item_type]
+        .union(tag_cache.materials[item.material])
+        .union(tag_cache.expand(item.state))
+        .union(tag_cache.expand(temperature::get_item_temperature_tags(item)));
+}
+
+pub fn has_tag(item: &Item, tag: Tag, tag_cache: &TagCache) -> bool {
+    return get_tags(item, tag_cache).contains(tag);
+}
+
+// The weight of one of the item, which depends on what it's made of
+pub fn get_unit_weight(item: &Item) -> Weight {
+    let default_density = material::get_density(get_default_material(item.item_type));
+    let density = material::get_density(item.material);
+    if default_density == 0 || density == default_density {
+        return get_weight(item.item_type);
+    }
+    return (get_weight(item.item_type) as u64 * density as u64 / default_density as u64) as Weight;
+}
+
+pub fn get_total_weight(item: &Item) -> Weight {
+    let mut result = get_unit_weight(item) * item.quantity + get_pile_weight(&item.contents);
+    if let Some(liquid) = &item.liquid {
+        result += substance::get_liquid_weight(liquid);
+    }
+    return result;
+}
+
+pub fn holds_items(item_type: ItemType, item_tag_map: &ItemTagMap) -> bool {
+    return item_tag_map[item_type].contains(&Tag::SolidContainer);
+}
+
+pub fn holds_liquids(item_type: ItemType, item_tag_map: &ItemTagMap) -> bool {
+    return item_tag_map[item_type].contains(&Tag::LiquidContainer);
+}
+
+pub fn get_free_weight(container: &Item) -> Weight {
+    return match get_capacity(container.item_type) {
+        Some(capacity) => capacity.weight.saturating_sub(get_pile_weight(&container.contents)),
+        None => 0,
+    };
+}
+
+pub fn get_free_volume(container: &Item) -> Volume {
+    let used: Volume = match container.liquid {
+        Some(liquid) => liquid.volume,
+        None => 0,
+    };
+    return match get_capacity(container.item_type) {
+        Some(capacity) => capacity.volume.saturating_sub(used),
+        None => 0,
+    };
+}
+
+pub fn get_pile_weight(items: &[Item]) -> Weight {
+    return items.iter().map(get_total_weight).sum();
+}
+
+pub fn is_stackable(item_type: ItemType, item_tag_map: &ItemTagMap) -> bool {
+    return item_tag_map[item_type].contains(&Tag::Stackable);
+}
+
+// Adds the item to a pile, merging it into an identical stack if there is
+// one already.
+pub fn add_to_pile(items: &mut Vec<Item>, item: Item, item_tag_map: &ItemTagMap) {
+    for existing in items.iter_mut() {
+        if existing.can_stack_with(&item, item_tag_map) {
+            // The whole stack is only as fresh as its oldest part, and
+            // settles somewhere between the two temperatures
+            let total = existing.quantity + item.quantity;
+            existing.temperature = ((existing.temperature as i64 * existing.quantity as i64 + item.temperature as i64 * item.quantity as i64) / total as i64) as Temperature;
+            existing.quantity = total;
+            existing.age = Seconds::max(existing.age, item.age);
+            return;
+        }
+    }
+    items.push(item);
+}
+
+// Removes up to `quantity` items from the stack at `index`, removing the
+// stack entirely if nothing is left of it.
+pub fn take_from_pile(items: &mut Vec<Item>, index: usize, quantity: u32) -> Option<Item> {
+    let stack = items.get_mut(index)?;
+    if quantity == 0 {
+        return None;
+    }
+
+    let result = stack.split(quantity);
+    if stack.quantity == 0 {
+        items.remove(index);
+    }
+    return Some(result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::substance::{Substance, VOLUME_PER_PINT};
+
+    #[test]
+    fn test_add_to_pile() {
+        let item_tag_map = generate_item_tag_map();
+        let mut pile: Vec<Item> = Vec::new();
+
+        add_to_pile(&mut pile, Item::with_quantity(ItemType::Arrow, 5), &item_tag_map);
+        add_to_pile(&mut pile, Item::with_quantity(ItemType::Arrow, 7), &item_tag_map);
+        assert_eq!(pile.len(), 1);
+        assert_eq!(pile[0].quantity, 12);
+
+        add_to_pile(&mut pile, Item::new(ItemType::Dagger), &item_tag_map);
+        add_to_pile(&mut pile, Item::new(ItemType::Dagger), &item_tag_map);
+        assert_eq!(pile.len(), 3);
+    }
+
+    #[test]
+    fn test_take_from_pile() {
+        let mut pile: Vec<Item> = vec!(Item::with_quantity(ItemType::Coin, 10), Item::new(ItemType::Lute));
+
+        let taken = take_from_pile(&mut pile, 0, 4).unwrap();
+        assert_eq!(taken.quantity, 4);
+        assert_eq!(pile[0].quantity, 6);
+
+        let taken = take_from_pile(&mut pile, 0, 100).unwrap();
+        assert_eq!(taken.quantity, 6);
+        assert_eq!(pile.len(), 1);
+
+        assert!(take_from_pile(&mut pile, 0, 0).is_none());
+        assert!(take_from_pile(&mut pile, 5, 1).is_none());
+    }
+
+    #[test]
+    fn test_quantity_scaling() {
+        let arrows = Item::with_quantity(ItemType::Arrow, 20);
+        assert_eq!(get_total_weight(&arrows), get_weight(ItemType::ArrowBundle));
+        assert_eq!(get_price(&arrows), CurrencyAmount::new(100, CoinType::Copper));
+    }
+
+    #[test]
+    fn test_bundle_contents() {
+        for item_type in [ItemType::ArrowBundle, ItemType::CrossbowBoltBundle, ItemType::CaltropBundle, ItemType::SpikeBundle] {
+            let (contents, count) = get_bundle_contents(item_type).unwrap();
+            assert_eq!(get_weight(contents) * count, get_weight(item_type));
+        }
+        assert!(get_bundle_contents(ItemType::Arrow).is_none());
+    }
+
+    #[test]
+    fn test_container_capacity() {
+        let mut sack = Item::new(ItemType::Sack);
+        assert_eq!(get_free_weight(&sack), 30 * WEIGHT_PER_POUND);
+
+        sack.contents.push(Item::with_quantity(ItemType::Torch, 2));
+        assert_eq!(get_free_weight(&sack), 28 * WEIGHT_PER_POUND);
+        assert_eq!(get_total_weight(&sack), 2 * WEIGHT_PER_POUND + get_weight(ItemType::Sack));
+
+        let mut flask = Item::new(ItemType::Flask);
+        assert_eq!(get_free_volume(&flask), VOLUME_PER_PINT);
+        flask.liquid = Some(Liquid::new(Substance::Water, 6));
+        assert_eq!(get_free_volume(&flask), VOLUME_PER_PINT - 6);
+    }
+
+    #[test]
+    fn test_material_weight_and_price() {
+        let steel = Item::new(ItemType::Longsword);
+        let silver = Item::with_material(ItemType::Longsword, Material::Silver);
+        assert_eq!(get_total_weight(&steel), get_weight(ItemType::Longsword));
+        assert!(get_total_weight(&silver) > get_total_weight(&steel));
+
+        assert_eq!(get_price(&steel), CurrencyAmount::new(15, CoinType::Gold));
+        assert_eq!(get_price(&silver), CurrencyAmount::new(150, CoinType::Gold));
+        assert_eq!(CurrencyAmount::new(1, CoinType::Silver).scaled(1, 4), CurrencyAmount::new(2, CoinType::Copper));
+
+        assert!(!steel.can_stack_with(&silver, &generate_item_tag_map()));
+    }
+
+    #[test]
+    fn test_material_tags() {
+        let tag_cache = TagCache::new(&crate::tag::generate_tag_map(), &generate_item_tag_map(),
+            &material::generate_material_map(), &substance::generate_substance_tag_map());
+
+        let sword = Item::new(ItemType::Longsword);
+        assert!(has_tag(&sword, Tag::Conductive, &tag_cache));
+        assert!(!has_tag(&sword, Tag::Flammable, &tag_cache));
+
+        let mut wooden_sword = Item::with_material(ItemType::Longsword, Material::Wood);
+        assert!(has_tag(&wooden_sword, Tag::Flammable, &tag_cache));
+        assert!(!has_tag(&wooden_sword, Tag::Conductive, &tag_cache));
+
+        wooden_sword.state.insert(Tag::Burning);
+        assert!(has_tag(&wooden_sword, Tag::Hot, &tag_cache));
+
+        let rations = Item::new(ItemType::Rations);
+        assert!(has_tag(&rations, Tag::Food, &tag_cache));
+        assert!(has_tag(&rations, Tag::Consumable, &tag_cache));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{entity::EntityID, fire, fov, game::Game, item::{self, Item}, monster, tabletop::{self, AdvantageStatus, Skill}, tag::Tag};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LightLevel {
+    Dark,
+    Dim,
+    Bright,
+}
+
+// How far anyone can see when there's light to see by
+pub const SIGHT_RADIUS: u16 = 40;
+
+// Everything that gives off light, as (x, y, radius of bright light)
+pub fn get_light_sources(game: &Game) -> Vec<(u16, u16, u16)> {
+    let map = &game.current_map;
+    let mut result: Vec<(u16, u16, u16)> = Vec::new();
+
+    for index in 0..map.fires.len() {
+        let (x, y) = map.index_to_coordinates(index);
+        if map.fires[index] > 0 {
+            result.push((x, y, fire::FIRE_LIGHT_RADIUS));
+        }
+        let radius = get_pile_light_radius(&map.items[index]);
+        if radius > 0 {
+            result.push((x, y, radius));
+        }
+    }
+
+    for creature in game.components.get_living_creatures() {
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        let inventory = game.components.get_inventory(creature);
+        if map_index.is_none() || position.is_none() || inventory.is_none() || map_index.unwrap().map != map.id {
+            continue;
+        }
+        let radius = get_pile_light_radius(&inventory.unwrap().items);
+        if radius > 0 {
+            result.push((position.unwrap().x, position.unwrap().y, radius));
+        }
+    }
+
+    return result;
+}
+
+fn get_pile_light_radius(items: &[Item]) -> u16 {
+    return items.iter()
+        .filter(|item| item.state.contains(Tag::Burning))
+        .map(|item| item::get_light_radius(item.item_type))
+        .max()
+        .unwrap_or(0);
+}
+
+// Light is bright within a source's radius and dim for as far again
+fn get_source_light(game: &Game, source: (u16, u16, u16), x: u16, y: u16) -> LightLevel {
+    let (source_x, source_y, radius) = source;
+    if !fov::is_within_radius(source_x, source_y, x, y, radius * 2) || !fov::has_line_of_sight(&game.current_map, source_x, source_y, x, y) {
+        return LightLevel::Dark;
+    }
+    if fov::is_within_radius(source_x, source_y, x, y, radius) {
+        return LightLevel::Bright;
+    }
+    return LightLevel::Dim;
+}
+
+pub fn get_light_level(game: &Game, x: u16, y: u16) -> LightLevel {
+    let mut result = game.current_map.get_ambient_light(x, y);
+    for source in get_light_sources(game) {
+        result = LightLevel::max(result, get_source_light(game, source, x, y));
+    }
+    return result;
+}
+
+// The light level of every tile on the current map
+pub fn compute_light_map(game: &Game) -> Vec<LightLevel> {
+    let map = &game.current_map;
+    let mut result: Vec<LightLevel> = (0..map.tiles.len())
+        .map(|index| {
+            let (x, y) = map.index_to_coordinates(index);
+            return map.get_ambient_light(x, y);
+        })
+        .collect();
+    if map.ambient_light == LightLevel::Bright {
+        return result;
+    }
+
+    for source in get_light_sources(game) {
+        let (source_x, source_y, radius) = source;
+        let reach = radius * 2;
+        for y in source_y.saturating_sub(reach)..=u16::min(source_y.saturating_add(reach), map.height - 1) {
+            for x in source_x.saturating_sub(reach)..=u16::min(source_x.saturating_add(reach), map.width - 1) {
+                let index = map.coordinates_to_index(x, y);
+                result[index] = LightLevel::max(result[index], get_source_light(game, source, x, y));
+            }
+        }
+    }
+
+    return result;
+}
+
+// Darkvision lets a creature see dim light as if it were bright, and
+// darkness as if it were dim, as long as it's close enough
+pub fn get_perceived_light(game: &Game, viewer: EntityID, x: u16, y: u16, light: LightLevel) -> LightLevel {
+    let creature = game.components.get_creature(viewer);
+    let position = game.components.get_position(viewer);
+    if creature.is_none() || position.is_none() {
+        return light;
+    }
+    let darkvision = monster::get_darkvision(game, viewer).unwrap_or(tabletop::get_darkvision(&creature.unwrap().race));
+    if darkvision == 0 || !fov::is_within_radius(position.unwrap().x, position.unwrap().y, x, y, darkvision) {
+        return light;
+    }
+    return match light {
+        LightLevel::Dark => LightLevel::Dim,
+        LightLevel::Dim => LightLevel::Bright,
+        LightLevel::Bright => LightLevel::Bright,
+    };
+}
+
+// Anything short of bright light makes it harder to spot things, and easier
+// to stay hidden
+pub fn get_light_advantage(game: &Game, entity: EntityID, skill: Skill) -> AdvantageStatus {
+    let position = game.components.get_position(entity);
+    if position.is_none() || !game.current_map.is_in_bounds(position.unwrap().x, position.unwrap().y) {
+        return AdvantageStatus::Normal;
+    }
+    let (x, y) = (position.unwrap().x, position.unwrap().y);
+    let light = get_light_level(game, x, y);
+
+    return match skill {
+        Skill::Perception if get_perceived_light(game, entity, x, y, light) < LightLevel::Bright => AdvantageStatus::Disadvantage,
+        Skill::Stealth if light < LightLevel::Bright => AdvantageStatus::Advantage,
+        _ => AdvantageStatus::Normal,
+    };
+}
+
+// Works out what the player can see from where they stand, and remembers it
+pub fn update_visibility(game: &mut Game) {
+    let player = game.special_entities.player;
+    let position = game.components.get_position(player);
+    if position.is_none() || !game.current_map.is_in_bounds(position.unwrap().x, position.unwrap().y) {
+        return;
+    }
+    let (player_x, player_y) = (position.unwrap().x, position.unwrap().y);
+
+    let in_view = fov::compute_fov(&game.current_map, player_x, player_y, SIGHT_RADIUS);
+    let light_map = compute_light_map(game);
+    let mut visible: Vec<bool> = vec![false; in_view.len()];
+    for index in 0..in_view.len() {
+        let (x, y) = game.current_map.index_to_coordinates(index);
+        visible[index] = in_view[index] && get_perceived_light(game, player, x, y, light_map[index]) > LightLevel::Dark;
+    }
+    // You can always make out where you're standing
+    visible[game.current_map.coordinates_to_index(player_x, player_y)] = true;
+
+    let map = game.current_map.as_mut();
+    for (explored, seen) in map.explored.iter_mut().zip(visible.iter()) {
+        *explored = *explored || *seen;
+    }
+    map.visible = visible;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{map::{GameMap, Tile}, tabletop::Race};
+
+    fn make_dark_game() -> Game {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 20, 1));
+        for x in 0..20 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+        game.current_map.ambient_light = LightLevel::Dark;
+        return game;
+    }
+
+    #[test]
+    fn test_torch_light() {
+        let mut game = make_dark_game();
+        let mut torch = Item::new(item::ItemType::Torch);
+        torch.state.insert(Tag::Burning);
+        game.current_map.get_items_mut(0, 0).push(torch);
+
+        assert_eq!(get_light_level(&game, 4, 0), LightLevel::Bright);
+        assert_eq!(get_light_level(&game, 8, 0), LightLevel::Dim);
+        assert_eq!(get_light_level(&game, 9, 0), LightLevel::Dark);
+
+        let light_map = compute_light_map(&game);
+        assert_eq!(light_map[8], LightLevel::Dim);
+
+        game.current_map.set_tile(2, 0, Tile::Wall);
+        assert_eq!(get_light_level(&game, 4, 0), LightLevel::Dark);
+    }
+
+    #[test]
+    fn test_darkvision() {
+        let mut game = make_dark_game();
+        let player = game.special_entities.player;
+        assert_eq!(get_light_advantage(&game, player, Skill::Perception), AdvantageStatus::Disadvantage);
+        assert_eq!(get_light_advantage(&game, player, Skill::Stealth), AdvantageStatus::Advantage);
+
+        game.current_map.ambient_light = LightLevel::Dim;
+        game.components.get_creature_mut(player).unwrap().race = Race::Dwarf;
+        assert_eq!(get_perceived_light(&game, player, 5, 0, LightLevel::Dim), LightLevel::Bright);
+        assert_eq!(get_perceived_light(&game, player, 19, 0, LightLevel::Dim), LightLevel::Dim);
+        assert_eq!(get_light_advantage(&game, player, Skill::Perception), AdvantageStatus::Normal);
+
+        update_visibility(&mut game);
+        assert!(game.current_map.visible[19]);
+        assert!(game.current_map.explored[19]);
+    }
+}
@@ -2,31 +2,91 @@ use std::{thread, time::{Duration, Instant}};
 
 use action::ActionRequest;
 use game::{Game, GameState};
-use gen::map_gen;
-use map::GameMap;
 use ringbuffer::RingBuffer;
+use rng::Rng;
 use tabletop::{Alignment, Class, Race, Size};
 use ui::{menu::MenuType, terminal::terminal_util};
 
 mod action;
+mod ai;
 mod component;
 mod constants;
+mod dijkstra;
+mod electricity;
 mod entity;
+mod fov;
+mod fire;
 mod item;
 mod game;
 mod gen;
+mod lighting;
 mod map;
 mod material;
+mod monster;
+mod npc;
+mod pathfinding;
+mod registry;
+mod rng;
+mod scheduler;
+mod simulation;
+mod substance;
 mod tabletop;
 mod tag;
+mod temperature;
 mod time;
+mod trap;
+mod travel;
 mod ui;
+mod weather;
 
 const FRAMES_PER_SECOND: u8 = 30;
 const NANOS_PER_FRAME: u64 = 1_000_000_000 / (FRAMES_PER_SECOND as u64);
 const NANOS_PER_REFRESH: u64 = 1_000_000_000;
+const PREFAB_DIRECTORY: &str = "data/prefabs";
+
+// The prefabs are looked for under wherever the game is run from, then
+// beside the executable, then in the source tree it was built from, so
+// running it from some other directory still finds them
+fn find_prefab_directory() -> std::path::PathBuf {
+    let mut candidates: Vec<std::path::PathBuf> = vec![std::path::PathBuf::from(PREFAB_DIRECTORY)];
+    if let Ok(executable) = std::env::current_exe() {
+        candidates.extend(executable.ancestors().skip(1).map(|directory| directory.join(PREFAB_DIRECTORY)));
+    }
+    candidates.push(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(PREFAB_DIRECTORY));
+    return candidates.into_iter().find(|candidate| candidate.is_dir()).unwrap_or_else(|| std::path::PathBuf::from(PREFAB_DIRECTORY));
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let seed = match rng::parse_seed_argument(&args) {
+        Ok(maybe_seed) => maybe_seed.unwrap_or_else(rng::make_seed),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    // Mistakes in the prefab files are reported before the screen takes over
+    let prefabs = match gen::prefab::load_prefabs(&find_prefab_directory()) {
+        Ok(prefabs) => prefabs,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    // Dumping generated levels doesn't need the screen at all
+    let maybe_dump = match gen::dump::parse_dump_arguments(&args) {
+        Ok(maybe_options) => maybe_options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    if let Some(dump) = maybe_dump {
+        let all_connected = gen::dump::run_dump(&dump, seed, prefabs);
+        std::process::exit(if all_connected { 0 } else { 1 });
+    }
 
     terminal_util::game_drawing_begin();
 
@@ -46,12 +106,17 @@ fn main() {
     };
 
     let mut game = Game::new();
+    game.rng = Rng::new(seed);
 
     initialize_player(&mut game);
 
-    game.current_map = Box::new(GameMap::new(0, render_state.screen.width, render_state.screen.height));
-
-    map_gen::populate_map(game.current_map.as_mut());
+    game.maps.prefabs = prefabs;
+    game.maps.level_width = render_state.screen.width;
+    game.maps.level_height = render_state.screen.height - terminal_util::HUD_HEIGHT;
+    registry::create_world(&mut game);
+    weather::roll_weather(&mut game);
+    weather::update_outdoors(&mut game);
+    lighting::update_visibility(&mut game);
 
     const FRAME_DURATION: Duration = Duration::from_nanos(NANOS_PER_FRAME);
     const REFRESH_DURATION: Duration = Duration::from_nanos(NANOS_PER_REFRESH);
@@ -75,12 +140,22 @@ fn main() {
         
         terminal_util::read_input(&mut game);
 
-        if !game.action_queue.is_empty() {
+        // Each of the player's actions runs the world up to their next turn,
+        // so everything queued up can be handled this frame
+        while !game.action_queue.is_empty() {
             let action:ActionRequest = game.action_queue.pop_front().unwrap();
             
             action::execute_action(&mut game, action);
         }
 
+        // Exploring and travelling take a step a frame so that they can be
+        // watched and interrupted
+        if game.state == GameState::Running {
+            if let Some(step) = travel::get_next_step(&mut game) {
+                action::execute_action(&mut game, step);
+            }
+        }
+
         let elapsed_since_refresh = refresh_timer.elapsed();
         if elapsed_since_refresh >= REFRESH_DURATION {
             terminal_util::refresh_back_buffer(&mut render_state);
@@ -118,6 +193,13 @@ fn initialize_player(game: &mut Game) {
     let character = game.components.get_character_mut(game.special_entities.player).unwrap();
     character.class = Class::Fighter;
 
+    // First level characters start with their full hit die plus constitution
+    let constitution = game.components.get_creature(game.special_entities.player).unwrap().stats.constitution;
+    let hit_points: i32 = tabletop::hit_die(&Class::Fighter) as i32 + tabletop::modifier(constitution) as i32;
+    let health = game.components.get_health_mut(game.special_entities.player).unwrap();
+    health.current = hit_points;
+    health.maximum = hit_points;
+
     let map_location = game.components.get_map_index_mut(game.special_entities.player).unwrap();
     map_location.map = 0;
 
@@ -1,8 +1,9 @@
 use enum_map::Enum;
+use strum_macros::EnumString;
 
-use crate::item::Item;
+use crate::{item::Item, lighting::LightLevel, substance::Substance, tag::{Tag, TagCache}, temperature::{Temperature, AMBIENT_TEMPERATURE}, trap::TrapKind};
 
-#[derive(Clone, Copy, Debug, Enum)]
+#[derive(Clone, Copy, Debug, Enum, EnumString, PartialEq, Eq)]
 pub enum Tile {
     Air,
     Altar,
@@ -19,6 +20,7 @@ pub enum Tile {
     Hills,
     Hive,
     Hole,
+    Ice,
     Lever,
     Magma,
     Mountain,
@@ -41,34 +43,171 @@ pub enum Tile {
 
 pub type MapID = u32;
 
+pub fn get_liquid_source(tile: &Tile) -> Option<Substance> {
+    return match tile {
+        Tile::Pool => Some(Substance::Water),
+        Tile::Water => Some(Substance::Water),
+        _ => None,
+    };
+}
+
+// Tiles that can't be seen past
+pub fn is_opaque(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Building | Tile::DoorClosed | Tile::Forest | Tile::Mountain | Tile::Tree | Tile::Wall);
+}
+
+// Tiles nothing can walk into. Closed doors can be opened by walking into
+// them, so they don't count.
+pub fn is_impassable(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Air | Tile::Building | Tile::Mountain | Tile::Statue | Tile::Tree | Tile::Wall);
+}
+
+// How many moves it takes to cross a tile, with rough ground taking longer
+pub fn get_move_cost(tile: &Tile) -> u32 {
+    return match tile {
+        Tile::DoorClosed => 2,
+        Tile::Forest => 2,
+        Tile::Hills => 2,
+        Tile::Ice => 2,
+        Tile::Swamp => 2,
+        Tile::Water => 2,
+        Tile::Web => 3,
+        _ => 1,
+    };
+}
+
+// Tiles that hurt or trap anything that walks into them
+pub fn is_hazardous(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Hole | Tile::Magma | Tile::TrapKnown);
+}
+
+// Tiles that lead off the map to somewhere else
+pub fn is_way_out(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Entrance | Tile::Gate | Tile::StairDown | Tile::StairUp);
+}
+
+// Tiles under the open sky, which follow the sun and the seasons
+pub fn is_outdoors(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Forest | Tile::Herbs | Tile::Hills | Tile::Mountain | Tile::Plains | Tile::Road | Tile::Swamp | Tile::Tree);
+}
+
+// Tiles that items can be put on top of
+pub fn is_supporter(tile: &Tile) -> bool {
+    return matches!(tile, Tile::Altar | Tile::Forge | Tile::Statue | Tile::Tombstone);
+}
+
+// How many turns a tile keeps burning for once it catches fire
+pub fn get_tile_fuel(tile: &Tile) -> u16 {
+    return match tile {
+        Tile::Forest => 20,
+        Tile::Herbs => 3,
+        Tile::Tree => 12,
+        Tile::Web => 1,
+        _ => 0,
+    };
+}
+
+// What a mechanism becomes when it's powered, like a door swinging open
+pub fn get_toggled_mechanism(tile: &Tile) -> Option<Tile> {
+    return match tile {
+        Tile::DoorClosed => Some(Tile::DoorOpen),
+        Tile::DoorOpen => Some(Tile::DoorClosed),
+        _ => None,
+    };
+}
+
+// What's left of a tile once it has burned out
+pub fn get_burnt_tile(tile: &Tile) -> Tile {
+    return match tile {
+        Tile::Forest => Tile::Plains,
+        Tile::Herbs => Tile::Plains,
+        Tile::Tree => Tile::Floor,
+        Tile::Web => Tile::Floor,
+        _ => *tile,
+    };
+}
+
 pub struct GameMap {
     pub id: MapID,
     pub width: u16,
     pub height: u16,
+    // How well lit the map is away from any light sources
+    pub ambient_light: LightLevel,
+    // What everything cools or warms back to away from heat sources
+    pub ambient_temperature: Temperature,
+    // How well lit and how warm outdoor tiles are, which changes with the
+    // time of day and the season
+    pub daylight: LightLevel,
+    pub outdoor_temperature: Temperature,
+    // How many rounds it takes to walk across a tile, which is more than one
+    // on maps drawn at a larger scale like the overworld
+    pub rounds_per_tile: u32,
+    // Tiles that have changed in a way that affects getting around since the
+    // distance maps were last brought up to date
+    pub changed_tiles: Vec<usize>,
+    // Tiles the player has seen before
+    pub explored: Vec<bool>,
+    // Turns of fuel left on each burning tile
+    pub fires: Vec<u16>,
     pub items: Vec<Vec<Item>>,
+    // Turns until the smoke hanging over each tile clears
+    pub smoke: Vec<u8>,
+    // Liquid spilled on a tile, which leaves it wet
+    pub spills: Vec<Option<Substance>>,
+    pub temperatures: Vec<Temperature>,
     pub tiles: Vec<Tile>,
+    // Traps laid in the ground, which aren't shown on the map
+    pub traps: Vec<Option<TrapKind>>,
+    // Tiles the player can see right now
+    pub visible: Vec<bool>,
 }
 
 impl GameMap {
     pub fn new(id: MapID, width: u16, height: u16) -> Self {
+        // Big maps have more tiles than fit in a u16
+        let size = width as usize * height as usize;
         let mut result = Self {
             id,
             width,
             height,
-            items: Vec::with_capacity((width * height).into()),
-            tiles: Vec::with_capacity((width * height).into()),
+            ambient_light: LightLevel::Bright,
+            ambient_temperature: AMBIENT_TEMPERATURE,
+            daylight: LightLevel::Bright,
+            outdoor_temperature: AMBIENT_TEMPERATURE,
+            rounds_per_tile: 1,
+            changed_tiles: Vec::new(),
+            explored: Vec::with_capacity(size),
+            fires: Vec::with_capacity(size),
+            items: Vec::with_capacity(size),
+            smoke: Vec::with_capacity(size),
+            spills: Vec::with_capacity(size),
+            temperatures: Vec::with_capacity(size),
+            tiles: Vec::with_capacity(size),
+            traps: Vec::with_capacity(size),
+            visible: Vec::with_capacity(size),
         };
 
-        for _ in 0..width*height {
+        for _ in 0..size {
             result.tiles.push(Tile::Air);
+            result.explored.push(false);
+            result.visible.push(false);
+            result.fires.push(0);
             result.items.push(Vec::new());
+            result.smoke.push(0);
+            result.spills.push(None);
+            result.temperatures.push(AMBIENT_TEMPERATURE);
+            result.traps.push(None);
         }
 
         return result;
     }
 
     pub fn coordinates_to_index(&self, x: u16, y: u16) -> usize {
-        return (y * self.width + x).into();
+        return y as usize * self.width as usize + x as usize;
+    }
+
+    pub fn is_in_bounds(&self, x: u16, y: u16) -> bool {
+        return x < self.width && y < self.height;
     }
 
     pub fn index_to_coordinates(&self, index: usize) -> (u16, u16) {
@@ -87,6 +226,84 @@ impl GameMap {
         return &mut self.items[index];
     }
 
+    pub fn blocks_sight(&self, x: u16, y: u16) -> bool {
+        let index: usize = self.coordinates_to_index(x, y);
+        return is_opaque(&self.tiles[index]) || self.smoke[index] > 0;
+    }
+
+    pub fn is_burning(&self, x: u16, y: u16) -> bool {
+        let index: usize = self.coordinates_to_index(x, y);
+        return self.fires[index] > 0;
+    }
+
+    pub fn get_spill(&self, x: u16, y: u16) -> Option<Substance> {
+        let index: usize = self.coordinates_to_index(x, y);
+        return self.spills[index];
+    }
+
+    pub fn set_spill(&mut self, x: u16, y: u16, spill: Option<Substance>) {
+        let index: usize = self.coordinates_to_index(x, y);
+        self.spills[index] = spill;
+    }
+
+    pub fn get_trap(&self, x: u16, y: u16) -> Option<TrapKind> {
+        let index: usize = self.coordinates_to_index(x, y);
+        return self.traps[index];
+    }
+
+    pub fn set_trap(&mut self, x: u16, y: u16, trap: Option<TrapKind>) {
+        let index: usize = self.coordinates_to_index(x, y);
+        self.traps[index] = trap;
+    }
+
+    pub fn get_temperature(&self, x: u16, y: u16) -> Temperature {
+        let index: usize = self.coordinates_to_index(x, y);
+        return self.temperatures[index];
+    }
+
+    // How well lit a tile is away from any light sources
+    pub fn get_ambient_light(&self, x: u16, y: u16) -> LightLevel {
+        if is_outdoors(self.get_tile(x, y)) {
+            return LightLevel::max(self.ambient_light, self.daylight);
+        }
+        return self.ambient_light;
+    }
+
+    // What a tile cools or warms back to away from heat sources
+    pub fn get_ambient_temperature(&self, x: u16, y: u16) -> Temperature {
+        if is_outdoors(self.get_tile(x, y)) {
+            return self.outdoor_temperature;
+        }
+        return self.ambient_temperature;
+    }
+
+    pub fn get_orthogonal_neighbors(&self, x: u16, y: u16) -> Vec<(u16, u16)> {
+        let mut result: Vec<(u16, u16)> = Vec::with_capacity(4);
+        if x > 0 {
+            result.push((x - 1, y));
+        }
+        if y > 0 {
+            result.push((x, y - 1));
+        }
+        if x + 1 < self.width {
+            result.push((x + 1, y));
+        }
+        if y + 1 < self.height {
+            result.push((x, y + 1));
+        }
+        return result;
+    }
+
+    // Standing water or a puddle of anything that isn't oily soaks whatever
+    // lies there, and carries a current
+    pub fn is_wet(&self, x: u16, y: u16, tag_cache: &TagCache) -> bool {
+        if get_liquid_source(self.get_tile(x, y)).is_some() {
+            return true;
+        }
+        let spill = self.get_spill(x, y);
+        return spill.is_some() && !tag_cache.substances[spill.unwrap()].contains(Tag::Flammable);
+    }
+
     pub fn get_tile(&self, x: u16, y: u16) -> &Tile {
         let index: usize = self.coordinates_to_index(x, y);
         return &self.tiles[index];
@@ -94,6 +311,9 @@ impl GameMap {
 
     pub fn set_tile(&mut self, x: u16, y: u16, tile: Tile) {
         let index: usize = self.coordinates_to_index(x, y);
+        if self.tiles[index] != tile {
+            self.changed_tiles.push(index);
+        }
         self.tiles[index] = tile;
     }
 
@@ -102,8 +322,21 @@ impl GameMap {
             id: 0,
             width: 0,
             height: 0,
+            ambient_light: LightLevel::Bright,
+            ambient_temperature: AMBIENT_TEMPERATURE,
+            daylight: LightLevel::Bright,
+            outdoor_temperature: AMBIENT_TEMPERATURE,
+            rounds_per_tile: 1,
+            changed_tiles: Vec::new(),
+            explored: Vec::new(),
+            fires: Vec::new(),
             items: Vec::new(),
+            smoke: Vec::new(),
+            spills: Vec::new(),
+            temperatures: Vec::new(),
             tiles: Vec::new(),
+            traps: Vec::new(),
+            visible: Vec::new(),
         }
     }
 }
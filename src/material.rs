@@ -2,15 +2,24 @@ use enum_map::{enum_map, Enum, EnumMap};
 
 use crate::tag::Tag;
 
-#[derive(Clone, Copy, Debug, Enum)]
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
 pub enum Material {
+    Bone,
     Bronze,
+    Cloth,
+    Copper,
     Fire,
+    Food,
     Glass,
+    Gold,
     Iron,
+    Leather,
     Metal,
+    Paper,
+    Silver,
     Steel,
     Stone,
+    Wax,
     Wood,
 }
 
@@ -21,28 +30,184 @@ pub struct MaterialData {
 
 pub type MaterialMap = EnumMap<Material, MaterialData>;
 
+// Object armor class from the 5e rules for breaking things
+pub const fn get_armor_class(material: Material) -> u8 {
+    match material {
+        Material::Bone => 15,
+        Material::Bronze => 19,
+        Material::Cloth => 11,
+        Material::Copper => 17,
+        Material::Fire => 0,
+        Material::Food => 11,
+        Material::Glass => 13,
+        Material::Gold => 17,
+        Material::Iron => 19,
+        Material::Leather => 11,
+        Material::Metal => 19,
+        Material::Paper => 11,
+        Material::Silver => 17,
+        Material::Steel => 19,
+        Material::Stone => 17,
+        Material::Wax => 11,
+        Material::Wood => 15,
+    }
+}
+
+// Relative densities in tenths of a gram per cubic centimetre, used to scale
+// the weight of an item made from something other than usual
+pub const fn get_density(material: Material) -> u32 {
+    match material {
+        Material::Bone => 19,
+        Material::Bronze => 88,
+        Material::Cloth => 15,
+        Material::Copper => 89,
+        Material::Fire => 0,
+        Material::Food => 10,
+        Material::Glass => 25,
+        Material::Gold => 193,
+        Material::Iron => 79,
+        Material::Leather => 9,
+        Material::Metal => 79,
+        Material::Paper => 8,
+        Material::Silver => 105,
+        Material::Steel => 78,
+        Material::Stone => 27,
+        Material::Wax => 9,
+        Material::Wood => 7,
+    }
+}
+
+pub const fn get_name(material: Material) -> &'static str {
+    match material {
+        Material::Bone => "Bone",
+        Material::Bronze => "Bronze",
+        Material::Cloth => "Cloth",
+        Material::Copper => "Copper",
+        Material::Fire => "Fire",
+        Material::Food => "Food",
+        Material::Glass => "Glass",
+        Material::Gold => "Gold",
+        Material::Iron => "Iron",
+        Material::Leather => "Leather",
+        Material::Metal => "Metal",
+        Material::Paper => "Paper",
+        Material::Silver => "Silver",
+        Material::Steel => "Steel",
+        Material::Stone => "Stone",
+        Material::Wax => "Wax",
+        Material::Wood => "Wood",
+    }
+}
+
+// Relative worth in percent, used to scale the price of an item made from
+// something other than usual
+pub const fn get_value(material: Material) -> u32 {
+    match material {
+        Material::Bone => 50,
+        Material::Bronze => 80,
+        Material::Cloth => 100,
+        Material::Copper => 60,
+        Material::Fire => 0,
+        Material::Food => 100,
+        Material::Glass => 100,
+        Material::Gold => 5000,
+        Material::Iron => 80,
+        Material::Leather => 100,
+        Material::Metal => 100,
+        Material::Paper => 100,
+        Material::Silver => 1000,
+        Material::Steel => 100,
+        Material::Stone => 50,
+        Material::Wax => 100,
+        Material::Wood => 100,
+    }
+}
+
+// Whether the material is the ancestor or is a kind of it, so Steel is a Metal
+pub fn is_a(material: Material, ancestor: Material, material_map: &MaterialMap) -> bool {
+    let mut current: Option<Material> = Some(material);
+    while current.is_some() {
+        if current.unwrap() == ancestor {
+            return true;
+        }
+        current = material_map[current.unwrap()].parent;
+    }
+    return false;
+}
+
+// Collects the tags of a material along with those of every material it
+// derives from
+pub fn get_material_tags(material: Material, material_map: &MaterialMap) -> Vec<Tag> {
+    let mut result: Vec<Tag> = Vec::new();
+    let mut current: Option<Material> = Some(material);
+
+    while current.is_some() {
+        let data = &material_map[current.unwrap()];
+        for tag in &data.tags {
+            if !result.contains(tag) {
+                result.push(*tag);
+            }
+        }
+        current = data.parent;
+    }
+
+    return result;
+}
+
 pub fn generate_material_map() -> MaterialMap {
     let result: MaterialMap = enum_map! {
+        Material::Bone => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Solid)
+        },
         Material::Bronze => MaterialData{
             parent: Some(Material::Metal),
             tags: vec!()
         },
+        Material::Cloth => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Flammable, Tag::Organic, Tag::Solid, Tag::Wettable)
+        },
+        Material::Copper => MaterialData{
+            parent: Some(Material::Metal),
+            tags: vec!()
+        },
         Material::Fire => MaterialData{
             parent: None,
             tags: vec!(Tag::Burning, Tag::Gas, Tag::Transparent)
         },
+        Material::Food => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Organic, Tag::Solid)
+        },
         Material::Glass => MaterialData{
             parent: None,
-            tags: vec!(Tag::Solid, Tag::Transparent)
+            tags: vec!(Tag::Fragile, Tag::Solid, Tag::Transparent)
+        },
+        Material::Gold => MaterialData{
+            parent: Some(Material::Metal),
+            tags: vec!()
         },
         Material::Iron => MaterialData{
             parent: Some(Material::Metal),
             tags: vec!()
         },
+        Material::Leather => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Organic, Tag::Solid)
+        },
         Material::Metal => MaterialData{
             parent: None,
             tags: vec!(Tag::Conductive, Tag::Solid)
         },
+        Material::Paper => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Flammable, Tag::Organic, Tag::Solid, Tag::Wettable)
+        },
+        Material::Silver => MaterialData{
+            parent: Some(Material::Metal),
+            tags: vec!()
+        },
         Material::Steel => MaterialData{
             parent: Some(Material::Metal),
             tags: vec!()
@@ -51,6 +216,10 @@ pub fn generate_material_map() -> MaterialMap {
             parent: None,
             tags: vec!(Tag::Solid)
         },
+        Material::Wax => MaterialData{
+            parent: None,
+            tags: vec!(Tag::Flammable, Tag::Solid)
+        },
         Material::Wood => MaterialData{
             parent: None,
             tags: vec!(Tag::Organic, Tag::Solid, Tag::Flammable)
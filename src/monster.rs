@@ -0,0 +1,423 @@
+use enum_map::Enum;
+use strum_macros::{EnumIter, EnumString};
+
+use crate::{action, ai::{self, Behavior}, component::{EntityType, Position}, entity::EntityID, game::Game, npc, tabletop::{self, DamageType, Dice, Size, Stats}};
+
+#[derive(Clone, Copy, Debug, Enum, EnumIter, EnumString, PartialEq, Eq)]
+pub enum MonsterKind {
+    Bat,
+    GiantRat,
+    GiantSpider,
+    Goblin,
+    Kobold,
+    Ogre,
+    Orc,
+    Skeleton,
+    Wolf,
+    Zombie,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Challenge {
+    Zero,
+    Eighth,
+    Quarter,
+    Half,
+    Whole(u8),
+}
+
+pub fn get_challenge_name(challenge: Challenge) -> String {
+    return match challenge {
+        Challenge::Zero => String::from("0"),
+        Challenge::Eighth => String::from("1/8"),
+        Challenge::Quarter => String::from("1/4"),
+        Challenge::Half => String::from("1/2"),
+        Challenge::Whole(rating) => rating.to_string(),
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonsterAttack {
+    pub name: &'static str,
+    pub to_hit: i8,
+    pub damage: Dice,
+    pub damage_type: DamageType,
+    // How far the attack reaches, in tiles
+    pub range: u16,
+}
+
+pub struct MonsterDefinition {
+    pub name: &'static str,
+    pub size: Size,
+    pub stats: Stats,
+    pub armor_class: u8,
+    pub hit_dice: Dice,
+    // In feet per round
+    pub speed: u16,
+    // In tiles
+    pub darkvision: u16,
+    pub attacks: &'static [MonsterAttack],
+    pub resistances: &'static [DamageType],
+    pub immunities: &'static [DamageType],
+    pub vulnerabilities: &'static [DamageType],
+    pub challenge: Challenge,
+    pub behaviors: &'static [Behavior],
+}
+
+const fn stats(strength: u8, dexterity: u8, constitution: u8, intelligence: u8, wisdom: u8, charisma: u8) -> Stats {
+    return Stats { charisma, constitution, dexterity, intelligence, strength, wisdom };
+}
+
+pub const fn get_monster_definition(kind: MonsterKind) -> MonsterDefinition {
+    match kind {
+        MonsterKind::Bat => MonsterDefinition {
+            name: "bat",
+            size: Size::Tiny,
+            stats: stats(2, 15, 8, 2, 12, 4),
+            armor_class: 12,
+            hit_dice: Dice::new(1, 4, -1),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "bite", to_hit: 0, damage: Dice { count: 0, sides: 0, bonus: 1 }, damage_type: DamageType::Piercing, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Zero,
+            behaviors: &[Behavior::Flee, Behavior::Wander],
+        },
+        MonsterKind::GiantRat => MonsterDefinition {
+            name: "giant rat",
+            size: Size::Small,
+            stats: stats(7, 15, 11, 2, 10, 4),
+            armor_class: 12,
+            hit_dice: Dice::new(2, 6, 0),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "bite", to_hit: 4, damage: Dice { count: 1, sides: 4, bonus: 2 }, damage_type: DamageType::Piercing, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Eighth,
+            behaviors: &[Behavior::Flee, Behavior::Hunt, Behavior::Wander],
+        },
+        MonsterKind::GiantSpider => MonsterDefinition {
+            name: "giant spider",
+            size: Size::Large,
+            stats: stats(14, 16, 12, 2, 11, 4),
+            armor_class: 14,
+            hit_dice: Dice::new(4, 10, 4),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "bite", to_hit: 5, damage: Dice { count: 1, sides: 8, bonus: 3 }, damage_type: DamageType::Piercing, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Whole(1),
+            behaviors: &[Behavior::Guard(5)],
+        },
+        MonsterKind::Goblin => MonsterDefinition {
+            name: "goblin",
+            size: Size::Small,
+            stats: stats(8, 14, 10, 10, 8, 8),
+            armor_class: 15,
+            hit_dice: Dice::new(2, 6, 0),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[
+                MonsterAttack { name: "scimitar", to_hit: 4, damage: Dice { count: 1, sides: 6, bonus: 2 }, damage_type: DamageType::Slashing, range: 1 },
+                MonsterAttack { name: "shortbow", to_hit: 4, damage: Dice { count: 1, sides: 6, bonus: 2 }, damage_type: DamageType::Piercing, range: 16 },
+            ],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Quarter,
+            behaviors: &[Behavior::Flee, Behavior::KeepRange(4), Behavior::Wander],
+        },
+        MonsterKind::Kobold => MonsterDefinition {
+            name: "kobold",
+            size: Size::Small,
+            stats: stats(7, 15, 9, 8, 7, 8),
+            armor_class: 12,
+            hit_dice: Dice::new(2, 6, -2),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[
+                MonsterAttack { name: "dagger", to_hit: 4, damage: Dice { count: 1, sides: 4, bonus: 2 }, damage_type: DamageType::Piercing, range: 1 },
+                MonsterAttack { name: "sling", to_hit: 4, damage: Dice { count: 1, sides: 4, bonus: 2 }, damage_type: DamageType::Bludgeoning, range: 6 },
+            ],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Eighth,
+            behaviors: &[Behavior::Flee, Behavior::Hunt, Behavior::Wander],
+        },
+        MonsterKind::Ogre => MonsterDefinition {
+            name: "ogre",
+            size: Size::Large,
+            stats: stats(19, 8, 16, 5, 7, 7),
+            armor_class: 11,
+            hit_dice: Dice::new(7, 10, 21),
+            speed: 40,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "greatclub", to_hit: 6, damage: Dice { count: 2, sides: 8, bonus: 4 }, damage_type: DamageType::Bludgeoning, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Whole(2),
+            behaviors: &[Behavior::Hunt, Behavior::Wander],
+        },
+        MonsterKind::Orc => MonsterDefinition {
+            name: "orc",
+            size: Size::Medium,
+            stats: stats(16, 12, 16, 7, 11, 10),
+            armor_class: 13,
+            hit_dice: Dice::new(2, 8, 6),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "greataxe", to_hit: 5, damage: Dice { count: 1, sides: 12, bonus: 3 }, damage_type: DamageType::Slashing, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Half,
+            behaviors: &[Behavior::Hunt, Behavior::Wander],
+        },
+        MonsterKind::Skeleton => MonsterDefinition {
+            name: "skeleton",
+            size: Size::Medium,
+            stats: stats(10, 14, 15, 6, 8, 5),
+            armor_class: 13,
+            hit_dice: Dice::new(2, 8, 4),
+            speed: 30,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "shortsword", to_hit: 4, damage: Dice { count: 1, sides: 6, bonus: 2 }, damage_type: DamageType::Piercing, range: 1 }],
+            resistances: &[],
+            immunities: &[DamageType::Poison],
+            vulnerabilities: &[DamageType::Bludgeoning],
+            challenge: Challenge::Quarter,
+            behaviors: &[Behavior::Guard(8)],
+        },
+        MonsterKind::Wolf => MonsterDefinition {
+            name: "wolf",
+            size: Size::Medium,
+            stats: stats(12, 15, 12, 3, 12, 6),
+            armor_class: 13,
+            hit_dice: Dice::new(2, 8, 2),
+            speed: 40,
+            darkvision: 0,
+            attacks: &[MonsterAttack { name: "bite", to_hit: 4, damage: Dice { count: 2, sides: 4, bonus: 2 }, damage_type: DamageType::Piercing, range: 1 }],
+            resistances: &[],
+            immunities: &[],
+            vulnerabilities: &[],
+            challenge: Challenge::Quarter,
+            behaviors: &[Behavior::Flee, Behavior::Hunt, Behavior::Wander],
+        },
+        MonsterKind::Zombie => MonsterDefinition {
+            name: "zombie",
+            size: Size::Medium,
+            stats: stats(13, 6, 16, 3, 6, 5),
+            armor_class: 8,
+            hit_dice: Dice::new(3, 8, 9),
+            speed: 20,
+            darkvision: 12,
+            attacks: &[MonsterAttack { name: "slam", to_hit: 3, damage: Dice { count: 1, sides: 6, bonus: 1 }, damage_type: DamageType::Bludgeoning, range: 1 }],
+            resistances: &[],
+            immunities: &[DamageType::Poison],
+            vulnerabilities: &[],
+            challenge: Challenge::Quarter,
+            behaviors: &[Behavior::Hunt, Behavior::Wander],
+        },
+    }
+}
+
+pub fn get_monster_kind(game: &Game, entity: EntityID) -> Option<MonsterKind> {
+    return game.components.get_monster(entity).map(|monster| monster.kind);
+}
+
+// Monsters have fixed hit points, the average of their hit dice
+pub fn spawn_monster(game: &mut Game, kind: MonsterKind, x: u16, y: u16) -> EntityID {
+    let definition = get_monster_definition(kind);
+    let result = game.components.create_entity(EntityType::Monster);
+    let map_id = game.current_map.id;
+    let now = game.clock.time;
+
+    game.components.get_monster_mut(result).unwrap().kind = kind;
+    let creature = game.components.get_creature_mut(result).unwrap();
+    creature.size = definition.size;
+    creature.stats = definition.stats;
+    let health = game.components.get_health_mut(result).unwrap();
+    health.current = i32::max(1, definition.hit_dice.average());
+    health.maximum = health.current;
+    *game.components.get_position_mut(result).unwrap() = Position { x, y };
+    game.components.get_map_index_mut(result).unwrap().map = map_id;
+    game.components.get_turn_mut(result).unwrap().next = now;
+    game.components.get_ai_mut(result).unwrap().behaviors = definition.behaviors.to_vec();
+    ai::set_home(game, result);
+
+    return result;
+}
+
+// What a creature calls another in messages
+pub fn get_creature_name(game: &Game, entity: EntityID) -> String {
+    if entity == game.special_entities.player {
+        return String::from("you");
+    }
+    if let Some(role) = npc::get_npc_role(game, entity) {
+        return format!("the {}", npc::get_npc_name(role));
+    }
+    return match get_monster_kind(game, entity) {
+        Some(kind) => format!("the {}", get_monster_definition(kind).name),
+        None => String::from("someone"),
+    };
+}
+
+pub fn get_speed(game: &Game, entity: EntityID) -> Option<u16> {
+    return get_monster_kind(game, entity).map(|kind| get_monster_definition(kind).speed);
+}
+
+pub fn get_darkvision(game: &Game, entity: EntityID) -> Option<u16> {
+    return get_monster_kind(game, entity).map(|kind| get_monster_definition(kind).darkvision);
+}
+
+// Until there's armor to wear, anyone but a monster only has their agility
+// to keep them from harm
+pub fn get_armor_class(game: &Game, entity: EntityID) -> u8 {
+    if let Some(kind) = get_monster_kind(game, entity) {
+        return get_monster_definition(kind).armor_class;
+    }
+    return match game.components.get_creature(entity) {
+        Some(creature) => (10 + tabletop::modifier(creature.stats.dexterity)) as u8,
+        None => 10,
+    };
+}
+
+// Until there are weapons to wield, anyone but a monster fights with their fists
+pub fn get_attacks(game: &Game, entity: EntityID) -> Vec<MonsterAttack> {
+    if let Some(kind) = get_monster_kind(game, entity) {
+        return get_monster_definition(kind).attacks.to_vec();
+    }
+    let strength = match game.components.get_creature(entity) {
+        Some(creature) => tabletop::modifier(creature.stats.strength),
+        None => 0,
+    };
+    return vec![MonsterAttack { name: "fist", to_hit: strength + tabletop::PROFICIENCY_BONUS, damage: Dice::new(0, 0, i8::max(1, 1 + strength)), damage_type: DamageType::Bludgeoning, range: 1 }];
+}
+
+pub fn get_attack_range(game: &Game, entity: EntityID) -> u16 {
+    return get_attacks(game, entity).iter().map(|attack| attack.range).max().unwrap_or(1);
+}
+
+// Melee if the target is close enough, otherwise whatever reaches
+pub fn choose_attack(game: &Game, attacker: EntityID, distance: u32) -> Option<MonsterAttack> {
+    return get_attacks(game, attacker).into_iter().find(|attack| distance <= attack.range as u32);
+}
+
+// Resistance halves damage, immunity stops it and vulnerability doubles it
+pub fn adjust_damage(game: &Game, target: EntityID, amount: i32, damage_type: DamageType) -> i32 {
+    let maybe_kind = get_monster_kind(game, target);
+    if maybe_kind.is_none() {
+        return amount;
+    }
+    let definition = get_monster_definition(maybe_kind.unwrap());
+    if definition.immunities.contains(&damage_type) {
+        return 0;
+    }
+    if definition.resistances.contains(&damage_type) {
+        return amount / 2;
+    }
+    if definition.vulnerabilities.contains(&damage_type) {
+        return amount * 2;
+    }
+    return amount;
+}
+
+// Adds any monster the player can see to the bestiary
+pub fn update_bestiary(game: &mut Game) {
+    let mut discovered: Vec<MonsterKind> = Vec::new();
+    for creature in game.components.get_living_creatures() {
+        let maybe_kind = get_monster_kind(game, creature);
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        if maybe_kind.is_none() || map_index.is_none() || position.is_none() || map_index.unwrap().map != game.current_map.id {
+            continue;
+        }
+        let (x, y) = (position.unwrap().x, position.unwrap().y);
+        let kind = maybe_kind.unwrap();
+        if game.current_map.is_in_bounds(x, y) && game.current_map.visible[game.current_map.coordinates_to_index(x, y)] && !game.bestiary[kind] && !discovered.contains(&kind) {
+            discovered.push(kind);
+        }
+    }
+
+    for kind in discovered {
+        game.bestiary[kind] = true;
+        let message = format!("New bestiary entry: {}.", get_monster_definition(kind).name);
+        action::report(game, game.special_entities.player, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::{Action, ActionRequest, Attack, Noun}, lighting, map::{GameMap, Tile}, new_action, rng::Rng};
+
+    fn make_test_game() -> Game {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 8, 1));
+        for x in 0..8 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+        let player = game.special_entities.player;
+        let health = game.components.get_health_mut(player).unwrap();
+        health.current = 20;
+        health.maximum = 20;
+        return game;
+    }
+
+    fn attack(game: &mut Game, attacker: EntityID, target: EntityID) {
+        action::execute_action(game, ActionRequest {
+            actor: attacker,
+            action: new_action!(Attack),
+            noun: Noun::Entity(target),
+            second: Noun::Nothing,
+        });
+    }
+
+    #[test]
+    fn test_spawn_and_fight() {
+        let mut game = make_test_game();
+        let player = game.special_entities.player;
+        let skeleton = spawn_monster(&mut game, MonsterKind::Skeleton, 1, 0);
+        assert_eq!(game.components.get_health(skeleton).unwrap().current, 13);
+        assert_eq!(get_creature_name(&game, skeleton), "the skeleton");
+
+        // Fists are bludgeoning, which skeletons are vulnerable to, and the
+        // skeleton gets its turn straight after to strike back. This seed has
+        // the punch land and the skeleton hit back.
+        game.rng = Rng::new(0);
+        game.components.get_creature_mut(player).unwrap().stats.strength = 16;
+        attack(&mut game, player, skeleton);
+        assert_eq!(game.components.get_health(skeleton).unwrap().current, 13 - 8);
+        assert_eq!(game.components.get_health(player).unwrap().current, 20 - 5);
+
+        // A weak punch misses
+        game.components.get_creature_mut(player).unwrap().stats.strength = 10;
+        attack(&mut game, player, skeleton);
+        assert_eq!(game.components.get_health(skeleton).unwrap().current, 13 - 8);
+
+        let zombie = spawn_monster(&mut game, MonsterKind::Zombie, 7, 0);
+        assert_eq!(adjust_damage(&game, zombie, 10, DamageType::Poison), 0);
+        assert!(choose_attack(&game, zombie, 6).is_none());
+        assert_eq!(get_speed(&game, zombie), Some(20));
+    }
+
+    #[test]
+    fn test_bestiary() {
+        let mut game = make_test_game();
+        let goblin = spawn_monster(&mut game, MonsterKind::Goblin, 5, 0);
+        assert_eq!(choose_attack(&game, goblin, 5).unwrap().name, "shortbow");
+
+        lighting::update_visibility(&mut game);
+        update_bestiary(&mut game);
+        assert!(game.bestiary[MonsterKind::Goblin]);
+        assert!(!game.bestiary[MonsterKind::Orc]);
+    }
+}
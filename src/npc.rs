@@ -0,0 +1,50 @@
+use crate::{ai::{self, Behavior}, component::{EntityType, Position}, entity::EntityID, game::Game, item::Item};
+
+// What a character who isn't the player does for a living
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpcRole {
+    // Keeps a shop, a smithy or a tavern, and has wares to sell
+    Merchant,
+    // Goes about their business around town
+    Townsfolk,
+}
+
+pub fn get_npc_name(role: NpcRole) -> &'static str {
+    return match role {
+        NpcRole::Merchant => "merchant",
+        NpcRole::Townsfolk => "townsperson",
+    };
+}
+
+// Merchants keep close to their counters, while townsfolk get about a bit
+fn get_behaviors(role: NpcRole) -> Vec<Behavior> {
+    return match role {
+        NpcRole::Merchant => vec![Behavior::Loiter(1)],
+        NpcRole::Townsfolk => vec![Behavior::Loiter(6)],
+    };
+}
+
+pub fn get_npc_role(game: &Game, entity: EntityID) -> Option<NpcRole> {
+    return game.components.get_npc(entity).and_then(|npc| npc.role);
+}
+
+// Creates someone on the current map, carrying whatever they're given
+pub fn spawn_npc(game: &mut Game, role: NpcRole, x: u16, y: u16, items: Vec<Item>) -> EntityID {
+    let result = game.components.create_entity(EntityType::Character);
+    let map_id = game.current_map.id;
+    let now = game.clock.time;
+
+    game.components.get_npc_mut(result).unwrap().role = Some(role);
+    // Ordinary folk, with a hit die's worth of health
+    let health = game.components.get_health_mut(result).unwrap();
+    health.current = 4;
+    health.maximum = 4;
+    game.components.get_inventory_mut(result).unwrap().items = items;
+    *game.components.get_position_mut(result).unwrap() = Position { x, y };
+    game.components.get_map_index_mut(result).unwrap().map = map_id;
+    game.components.get_turn_mut(result).unwrap().next = now;
+    game.components.get_ai_mut(result).unwrap().behaviors = get_behaviors(role);
+    ai::set_home(game, result);
+
+    return result;
+}
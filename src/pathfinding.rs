@@ -0,0 +1,121 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::map::{self, GameMap};
+
+// Hazards can be walked through when there's no other way, but never lightly
+pub const HAZARD_COST: u32 = 50;
+
+// The eight tiles around a tile, as offsets
+pub const NEIGHBOR_OFFSETS: [(i16, i16); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+pub fn get_neighbor(map: &GameMap, x: u16, y: u16, offset: (i16, i16)) -> Option<(u16, u16)> {
+    let next_x = x as i32 + offset.0 as i32;
+    let next_y = y as i32 + offset.1 as i32;
+    if next_x < 0 || next_y < 0 || !map.is_in_bounds(next_x as u16, next_y as u16) {
+        return None;
+    }
+    return Some((next_x as u16, next_y as u16));
+}
+
+// What it costs to step onto a tile, or None if it can't be stepped onto
+pub fn get_step_cost(map: &GameMap, x: u16, y: u16) -> Option<u32> {
+    let tile = map.get_tile(x, y);
+    if map::is_impassable(tile) {
+        return None;
+    }
+    let mut result = map::get_move_cost(tile);
+    if map::is_hazardous(tile) || map.is_burning(x, y) {
+        result += HAZARD_COST;
+    }
+    return Some(result);
+}
+
+// Moving diagonally costs the same as moving straight, so the distance is
+// however far it is along the longer axis
+pub fn get_distance(from_x: u16, from_y: u16, to_x: u16, to_y: u16) -> u32 {
+    return u16::max(from_x.abs_diff(to_x), from_y.abs_diff(to_y)) as u32;
+}
+
+// Finds the cheapest way between two tiles with A*, avoiding anything the
+// caller says is in the way. The path leaves out the start and ends on the
+// goal, which is allowed to be blocked so that a path can lead up to a
+// creature.
+pub fn find_path(map: &GameMap, from: (u16, u16), to: (u16, u16), is_blocked: impl Fn(u16, u16) -> bool) -> Option<Vec<(u16, u16)>> {
+    let start = map.coordinates_to_index(from.0, from.1);
+    let goal = map.coordinates_to_index(to.0, to.1);
+    let mut costs: Vec<Option<u32>> = vec![None; map.tiles.len()];
+    let mut came_from: Vec<Option<usize>> = vec![None; map.tiles.len()];
+    let mut frontier: BinaryHeap<Reverse<(u32, usize)>> = BinaryHeap::new();
+    costs[start] = Some(0);
+    frontier.push(Reverse((get_distance(from.0, from.1, to.0, to.1), start)));
+
+    while let Some(Reverse((_, index))) = frontier.pop() {
+        if index == goal {
+            break;
+        }
+        let (x, y) = map.index_to_coordinates(index);
+        let cost = costs[index].unwrap();
+
+        for offset in NEIGHBOR_OFFSETS {
+            let maybe_neighbor = get_neighbor(map, x, y, offset);
+            if maybe_neighbor.is_none() {
+                continue;
+            }
+            let (next_x, next_y) = maybe_neighbor.unwrap();
+            let next_index = map.coordinates_to_index(next_x, next_y);
+            let maybe_step = get_step_cost(map, next_x, next_y);
+            if maybe_step.is_none() || (next_index != goal && is_blocked(next_x, next_y)) {
+                continue;
+            }
+
+            let next_cost = cost + maybe_step.unwrap();
+            if costs[next_index].is_none() || next_cost < costs[next_index].unwrap() {
+                costs[next_index] = Some(next_cost);
+                came_from[next_index] = Some(index);
+                frontier.push(Reverse((next_cost + get_distance(next_x, next_y, to.0, to.1), next_index)));
+            }
+        }
+    }
+
+    costs[goal]?;
+    let mut result: Vec<(u16, u16)> = Vec::new();
+    let mut current = goal;
+    while current != start {
+        result.push(map.index_to_coordinates(current));
+        current = came_from[current].unwrap();
+    }
+    result.reverse();
+    return Some(result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Tile;
+
+    #[test]
+    fn test_path_around_walls() {
+        let mut map = GameMap::new(0, 5, 5);
+        for y in 0..5 {
+            for x in 0..5 {
+                map.set_tile(x, y, Tile::Floor);
+            }
+        }
+        for y in 0..4 {
+            map.set_tile(2, y, Tile::Wall);
+        }
+
+        let path = find_path(&map, (0, 0), (4, 0), |_, _| false).unwrap();
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert!(path.contains(&(2, 4)));
+        assert_eq!(path.len(), 8);
+
+        // Magma is walked around when there's another way
+        map.set_tile(2, 4, Tile::Magma);
+        assert!(find_path(&map, (0, 0), (4, 0), |x, y| x == 1 && y == 4).unwrap().contains(&(2, 4)));
+        map.set_tile(2, 0, Tile::Floor);
+        assert!(!find_path(&map, (0, 4), (4, 4), |_, _| false).unwrap().contains(&(2, 4)));
+
+        assert!(find_path(&map, (0, 0), (4, 0), |x, _| x == 2).is_none());
+    }
+}
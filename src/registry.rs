@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use crate::{component::Position, game::Game, gen::{bsp::{self, BspSettings}, caves::{self, CaveSettings}, map_gen::{self, GeneratedMap}, overworld::{self, OverworldSettings}, prefab::{self, Prefab}, town::{self, Building, TownSettings}}, lighting::{self, LightLevel}, map::{self, GameMap, MapID, Tile}, monster::{self, MonsterKind}, pathfinding::{self, NEIGHBOR_OFFSETS}, rng::RngStream, weather};
+
+// How many prefab rooms each dungeon level gets, if there's room for them
+const VAULTS_PER_LEVEL: usize = 1;
+// Percent chance each minute for another monster to wander in, while there
+// are fewer about than the hour calls for
+const WANDERER_CHANCE: u32 = 10;
+
+// What kind of place a map is, which decides how it gets generated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelKind {
+    Cave,
+    Dungeon,
+    Overworld,
+    Town,
+}
+
+pub struct Level {
+    pub kind: LevelKind,
+    // How many levels down from the surface it is
+    pub depth: u16,
+}
+
+// One end of a way between two maps, like an entrance or a flight of stairs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub map: MapID,
+    pub x: u16,
+    pub y: u16,
+    // What kind of level it leads into
+    pub kind: LevelKind,
+    // The map and tile it comes out on, once that level has been generated
+    pub destination: Option<(MapID, u16, u16)>,
+}
+
+// Keeps track of every map in the world and how they join up. Levels are
+// only generated the first time someone goes into them.
+pub struct MapRegistry {
+    // Every level by ID, whether it's been visited or not
+    pub levels: Vec<Level>,
+    pub links: Vec<Link>,
+    // How big newly generated maps are
+    pub level_width: u16,
+    pub level_height: u16,
+    // Hand-drawn rooms for generators to stamp into levels
+    pub prefabs: Vec<Prefab>,
+    // Maps nobody is on right now, kept as they were left
+    stored: HashMap<MapID, Box<GameMap>>,
+}
+
+impl MapRegistry {
+    pub fn new() -> Self {
+        Self {
+            levels: Vec::new(),
+            links: Vec::new(),
+            level_width: 80,
+            level_height: 40,
+            prefabs: Vec::new(),
+            stored: HashMap::new(),
+        }
+    }
+
+    pub fn add_level(&mut self, kind: LevelKind, depth: u16) -> MapID {
+        self.levels.push(Level { kind, depth });
+        return (self.levels.len() - 1) as MapID;
+    }
+
+    pub fn get_level(&self, id: MapID) -> Option<&Level> {
+        return self.levels.get(id as usize);
+    }
+
+    pub fn add_link(&mut self, map: MapID, x: u16, y: u16, kind: LevelKind) {
+        self.links.push(Link { map, x, y, kind, destination: None });
+    }
+
+    pub fn find_link(&self, map: MapID, x: u16, y: u16) -> Option<usize> {
+        return self.links.iter().position(|link| link.map == map && link.x == x && link.y == y);
+    }
+
+    pub fn store(&mut self, map: Box<GameMap>) {
+        self.stored.insert(map.id, map);
+    }
+
+    pub fn take(&mut self, id: MapID) -> Option<Box<GameMap>> {
+        return self.stored.remove(&id);
+    }
+}
+
+// A level that's just been generated, with what it takes to fill it in once
+// the player is there
+pub struct NewLevel {
+    pub generated: GeneratedMap,
+    pub buildings: Vec<Building>,
+}
+
+// The nearest tile to a spot that can be stood on, or the spot itself if
+// there's nowhere at all
+fn find_footing(map: &GameMap, x: u16, y: u16) -> (u16, u16) {
+    return (0..map.tiles.len())
+        .map(|index| map.index_to_coordinates(index))
+        .filter(|(tile_x, tile_y)| !map::is_impassable(map.get_tile(*tile_x, *tile_y)) && *map.get_tile(*tile_x, *tile_y) != Tile::Water)
+        .min_by_key(|(tile_x, tile_y)| pathfinding::get_distance(x, y, *tile_x, *tile_y))
+        .unwrap_or((x, y));
+}
+
+// Generates the overworld, with a way into a level below at each of its
+// entrances and into a town at each settlement, and puts the player at the
+// gate of the first settlement
+pub fn create_world(game: &mut Game) {
+    let id = game.maps.add_level(LevelKind::Overworld, 0);
+    let (width, height) = (game.maps.level_width, game.maps.level_height);
+    let mut generated = overworld::generate(id, width, height, &OverworldSettings::new(), &mut game.rng);
+    let spawn_rate = weather::get_spawn_rate(game.clock.get_time_of_day());
+    let spawns = map_gen::populate_map(&mut generated.map, &[], LevelKind::Overworld, 0, spawn_rate, &mut game.rng);
+    for (x, y) in &generated.entrances {
+        // Up in the mountains the way leads into caves rather than dungeons
+        let near_mountains = NEIGHBOR_OFFSETS.iter().any(|offset| {
+            pathfinding::get_neighbor(&generated.map, *x, *y, *offset).is_some_and(|(next_x, next_y)| *generated.map.get_tile(next_x, next_y) == Tile::Mountain)
+        });
+        let kind = if near_mountains { LevelKind::Cave } else { LevelKind::Dungeon };
+        game.maps.add_link(id, *x, *y, kind);
+    }
+    for (x, y) in &generated.settlements {
+        game.maps.add_link(id, *x, *y, LevelKind::Town);
+    }
+
+    let (start_x, start_y) = generated.settlements.first().cloned().unwrap_or_else(|| find_footing(&generated.map, width / 2, height / 2));
+    game.current_map = Box::new(generated.map);
+    let player = game.special_entities.player;
+    game.components.get_map_index_mut(player).unwrap().map = id;
+    *game.components.get_position_mut(player).unwrap() = Position { x: start_x, y: start_y };
+    for (kind, x, y) in spawns {
+        monster::spawn_monster(game, kind, x, y);
+    }
+}
+
+// Generates a level and adds it to the registry, stocked with everything
+// but its monsters and townsfolk
+pub fn generate_level(game: &mut Game, kind: LevelKind, depth: u16) -> Option<NewLevel> {
+    let (width, height) = (game.maps.level_width, game.maps.level_height);
+    let id = game.maps.add_level(kind, depth);
+    let mut buildings: Vec<Building> = Vec::new();
+    let mut generated = match kind {
+        LevelKind::Cave => caves::generate(id, width, height, &CaveSettings::new(), &mut game.rng),
+        LevelKind::Dungeon => bsp::generate(id, width, height, &BspSettings::for_depth(depth), &mut game.rng),
+        // There's only the one overworld
+        LevelKind::Overworld => return None,
+        LevelKind::Town => {
+            let town = town::generate(id, width, height, &TownSettings::new(), &mut game.rng);
+            buildings = town.buildings;
+            town.generated
+        },
+    };
+    if kind == LevelKind::Dungeon {
+        // Deep enough underground that only a little light filters down
+        generated.map.ambient_light = LightLevel::Dim;
+        prefab::place_vaults(&mut generated, &game.maps.prefabs, VAULTS_PER_LEVEL, &mut game.rng);
+    }
+    if kind != LevelKind::Town {
+        let spawn_rate = weather::get_spawn_rate(game.clock.get_time_of_day());
+        let spawns = map_gen::populate_map(&mut generated.map, &generated.rooms, kind, depth, spawn_rate, &mut game.rng);
+        generated.spawns.extend(spawns);
+    }
+    return Some(NewLevel { generated, buildings });
+}
+
+// Out in the wilds more monsters come out as it gets dark, turning up
+// somewhere out of the player's sight
+pub fn spawn_wanderers(game: &mut Game) {
+    let map = &game.current_map;
+    let is_overworld = game.maps.get_level(map.id).is_some_and(|level| level.kind == LevelKind::Overworld);
+    if !is_overworld {
+        return;
+    }
+    let open_tiles = map.tiles.iter().filter(|tile| map_gen::is_open_ground(tile)).count();
+    let wanted = map_gen::get_wandering_population(open_tiles, weather::get_spawn_rate(game.clock.get_time_of_day()));
+    let present = game.components.get_living_creatures().iter()
+        .filter(|creature| game.components.get_monster(**creature).is_some())
+        .filter(|creature| game.components.get_map_index(**creature).is_some_and(|index| index.map == map.id))
+        .count();
+    if present >= wanted || !game.rng.chance(RngStream::MapGen, WANDERER_CHANCE) {
+        return;
+    }
+
+    let hidden: Vec<(u16, u16)> = (0..map.tiles.len())
+        .filter(|index| map_gen::is_open_ground(&map.tiles[*index]) && map::is_outdoors(&map.tiles[*index]) && !map.visible[*index])
+        .map(|index| map.index_to_coordinates(index))
+        .filter(|(x, y)| game.components.get_creature_at(map.id, *x, *y).is_none())
+        .collect();
+    let weights: Vec<(MonsterKind, u32)> = map_gen::get_monster_weights(LevelKind::Overworld, 0).into_iter().collect();
+    let maybe_kind = map_gen::pick_weighted(&weights, RngStream::MapGen, &mut game.rng);
+    if hidden.is_empty() || maybe_kind.is_none() {
+        return;
+    }
+    let (x, y) = hidden[game.rng.range(RngStream::MapGen, 0, hidden.len() as i32 - 1) as usize];
+    monster::spawn_monster(game, maybe_kind.unwrap(), x, y);
+}
+
+// Fills in a level the player has just arrived on for the first time
+fn populate_level(game: &mut Game, kind: LevelKind, level: &NewLevel) {
+    for (monster_kind, x, y) in &level.generated.spawns {
+        monster::spawn_monster(game, *monster_kind, *x, *y);
+    }
+    if kind == LevelKind::Town {
+        town::populate_town(game, &level.buildings, &TownSettings::new());
+    }
+}
+
+// Moves the player onto a map that's been stored away, and stores the one
+// they were on
+fn change_map(game: &mut Game, id: MapID, x: u16, y: u16) -> Result<(), String> {
+    let maybe_map = game.maps.take(id);
+    if maybe_map.is_none() {
+        return Err(String::from("That way doesn't lead anywhere."));
+    }
+    let previous = std::mem::replace(&mut game.current_map, maybe_map.unwrap());
+    game.maps.store(previous);
+
+    let player = game.special_entities.player;
+    game.components.get_map_index_mut(player).unwrap().map = id;
+    *game.components.get_position_mut(player).unwrap() = Position { x, y };
+    game.auto_move = None;
+    game.distance_maps.travel = None;
+    weather::update_outdoors(game);
+    lighting::update_visibility(game);
+    return Ok(());
+}
+
+// Takes the player along the way out of the tile they're standing on,
+// generating the level on the other side if nobody has been there before.
+// Stairs down that nothing leads from yet go further down into the same
+// kind of level.
+pub fn follow_link(game: &mut Game, x: u16, y: u16) -> Result<(), String> {
+    let here = game.current_map.id;
+    let mut maybe_link = game.maps.find_link(here, x, y);
+    if maybe_link.is_none() && *game.current_map.get_tile(x, y) == Tile::StairDown {
+        let kind = game.maps.get_level(here).map(|level| level.kind).unwrap_or(LevelKind::Dungeon);
+        game.maps.add_link(here, x, y, kind);
+        maybe_link = Some(game.maps.links.len() - 1);
+    }
+    if maybe_link.is_none() {
+        return Err(String::from("That way doesn't lead anywhere."));
+    }
+    let link_index = maybe_link.unwrap();
+    let link = game.maps.links[link_index];
+
+    if let Some((id, arrival_x, arrival_y)) = link.destination {
+        return change_map(game, id, arrival_x, arrival_y);
+    }
+
+    // Somewhere new, which comes out on its stairs up with a way back. Towns
+    // are out on the surface, and everywhere else is further down.
+    let here_kind = game.maps.get_level(here).map(|level| level.kind).unwrap_or(LevelKind::Overworld);
+    let here_depth = game.maps.get_level(here).map(|level| level.depth).unwrap_or(0);
+    let depth = if link.kind == LevelKind::Town { here_depth } else { here_depth + 1 };
+    let maybe_level = generate_level(game, link.kind, depth);
+    if maybe_level.is_none() || maybe_level.as_ref().unwrap().generated.stairs_up.is_none() {
+        return Err(String::from("That way doesn't lead anywhere."));
+    }
+    let mut level = maybe_level.unwrap();
+    let id = level.generated.map.id;
+    let (arrival_x, arrival_y) = level.generated.stairs_up.unwrap();
+    game.maps.links[link_index].destination = Some((id, arrival_x, arrival_y));
+    game.maps.add_link(id, arrival_x, arrival_y, here_kind);
+    game.maps.links.last_mut().unwrap().destination = Some((here, x, y));
+
+    let map = std::mem::replace(&mut level.generated.map, GameMap::empty_map());
+    game.maps.store(Box::new(map));
+    change_map(game, id, arrival_x, arrival_y)?;
+    populate_level(game, link.kind, &level);
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{simulation, time::{TimeOfDay, SECONDS_PER_HOUR}};
+
+    #[test]
+    fn test_entrances_lead_to_levels() {
+        let mut game = Game::new();
+        game.maps.level_width = 60;
+        game.maps.level_height = 30;
+        create_world(&mut game);
+        let player = game.special_entities.player;
+        let overworld = game.current_map.id;
+        let entrance = game.maps.links[0];
+        assert_eq!(*game.current_map.get_tile(entrance.x, entrance.y), Tile::Entrance);
+
+        // Going in generates the level and comes out on its stairs up
+        assert!(follow_link(&mut game, entrance.x, entrance.y).is_ok());
+        let level = game.current_map.id;
+        assert_ne!(level, overworld);
+        assert_eq!(game.maps.get_level(level).unwrap().depth, 1);
+        assert_eq!(game.components.get_map_index(player).unwrap().map, level);
+        let position = game.components.get_position(player).unwrap();
+        let (up_x, up_y) = (position.x, position.y);
+        assert_eq!(*game.current_map.get_tile(up_x, up_y), Tile::StairUp);
+
+        // The stairs lead back out to the entrance, and the level is still
+        // there the next time
+        assert!(follow_link(&mut game, up_x, up_y).is_ok());
+        assert_eq!(game.current_map.id, overworld);
+        assert_eq!(game.components.get_position(player).unwrap().x, entrance.x);
+        assert!(follow_link(&mut game, entrance.x, entrance.y).is_ok());
+        assert_eq!(game.current_map.id, level);
+        assert_eq!(game.maps.levels.len(), 2);
+
+        // Stairs down go deeper into the same kind of level
+        let maybe_down = (0..game.current_map.tiles.len()).find(|index| game.current_map.tiles[*index] == Tile::StairDown);
+        let (down_x, down_y) = game.current_map.index_to_coordinates(maybe_down.unwrap());
+        assert!(follow_link(&mut game, down_x, down_y).is_ok());
+        let deeper = game.maps.get_level(game.current_map.id).unwrap();
+        assert_eq!(deeper.depth, 2);
+        assert_eq!(deeper.kind, entrance.kind);
+        assert!(follow_link(&mut game, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_gates_lead_into_towns() {
+        let mut game = Game::new();
+        game.maps.level_width = 60;
+        game.maps.level_height = 30;
+        create_world(&mut game);
+        let player = game.special_entities.player;
+        let overworld = game.current_map.id;
+        let position = game.components.get_position(player).unwrap();
+        let (gate_x, gate_y) = (position.x, position.y);
+        assert_eq!(*game.current_map.get_tile(gate_x, gate_y), Tile::Gate);
+
+        // The town is on the surface, and comes out at its own gate with
+        // people about
+        assert!(follow_link(&mut game, gate_x, gate_y).is_ok());
+        let town = game.maps.get_level(game.current_map.id).unwrap();
+        assert_eq!(town.kind, LevelKind::Town);
+        assert_eq!(town.depth, 0);
+        let arrival = game.components.get_position(player).unwrap();
+        let (arrival_x, arrival_y) = (arrival.x, arrival.y);
+        assert_eq!(*game.current_map.get_tile(arrival_x, arrival_y), Tile::Gate);
+        assert!(game.components.get_living_creatures().iter().any(|creature| crate::npc::get_npc_role(&game, *creature).is_some()));
+
+        assert!(follow_link(&mut game, arrival_x, arrival_y).is_ok());
+        assert_eq!(game.current_map.id, overworld);
+        assert_eq!(game.components.get_position(player).unwrap().x, gate_x);
+    }
+
+    #[test]
+    fn test_more_monsters_come_out_at_night() {
+        let mut game = Game::new();
+        game.maps.level_width = 60;
+        game.maps.level_height = 30;
+        create_world(&mut game);
+        let overworld = game.current_map.id;
+        let count_monsters = |game: &Game| game.components.get_living_creatures().iter()
+            .filter(|creature| game.components.get_monster(**creature).is_some() && game.components.get_map_index(**creature).unwrap().map == overworld)
+            .count();
+
+        // The world starts in the morning, and by the small hours of the
+        // next day there's more about
+        let morning = count_monsters(&game);
+        assert_eq!(game.clock.get_time_of_day(), TimeOfDay::Morning);
+        while game.clock.get_time_of_day() != TimeOfDay::Night {
+            simulation::pass_time(&mut game, SECONDS_PER_HOUR);
+        }
+        simulation::pass_time(&mut game, 3 * SECONDS_PER_HOUR);
+        assert!(count_monsters(&game) > morning, "{} in the morning and {} at night", morning, count_monsters(&game));
+    }
+
+    #[test]
+    fn test_footing_is_found_on_dry_land() {
+        let mut map = GameMap::new(0, 5, 5);
+        crate::gen::map_gen::fill(&mut map, Tile::Water);
+        map.set_tile(4, 1, Tile::Plains);
+        assert_eq!(find_footing(&map, 2, 2), (4, 1));
+        map.set_tile(4, 1, Tile::Mountain);
+        assert_eq!(find_footing(&map, 2, 2), (2, 2));
+    }
+}
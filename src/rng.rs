@@ -0,0 +1,135 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use enum_map::{Enum, EnumMap};
+
+use crate::tabletop::{AdvantageStatus, Dice};
+
+// Each part of the game draws from its own stream, so that generating a new
+// level doesn't change how the next fight goes
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum RngStream {
+    Ai,
+    Combat,
+    Loot,
+    MapGen,
+    Skill,
+    Weather,
+}
+
+pub type Seed = u64;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// SplitMix64's output function, which scrambles a number thoroughly
+fn mix(value: u64) -> u64 {
+    let mut result = value;
+    result = (result ^ (result >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    result = (result ^ (result >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    return result ^ (result >> 31);
+}
+
+// A deterministic random number generator. The same seed always gives the
+// same numbers from each stream, however the streams are interleaved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    pub seed: Seed,
+    streams: EnumMap<RngStream, u64>,
+}
+
+impl Rng {
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            seed,
+            streams: EnumMap::from_fn(|stream: RngStream| mix(seed ^ mix(stream.into_usize() as u64 + 1))),
+        }
+    }
+
+    pub fn next_u64(&mut self, stream: RngStream) -> u64 {
+        self.streams[stream] = self.streams[stream].wrapping_add(GOLDEN_GAMMA);
+        return mix(self.streams[stream]);
+    }
+
+    // A number from low to high, including both
+    pub fn range(&mut self, stream: RngStream, low: i32, high: i32) -> i32 {
+        if high <= low {
+            return low;
+        }
+        let span = (high as i64 - low as i64 + 1) as u64;
+        return (low as i64 + (((self.next_u64(stream) >> 32) * span) >> 32) as i64) as i32;
+    }
+
+    // Whether something with this percent chance happens
+    pub fn chance(&mut self, stream: RngStream, percent: u32) -> bool {
+        return (self.range(stream, 0, 99) as u32) < percent;
+    }
+
+    pub fn roll(&mut self, stream: RngStream, dice: Dice) -> i32 {
+        let mut result = dice.bonus as i32;
+        for _ in 0..dice.count {
+            result += self.range(stream, 1, dice.sides as i32);
+        }
+        return result;
+    }
+
+    // Rolls twice and keeps the better or worse die with advantage or
+    // disadvantage
+    pub fn roll_d20(&mut self, stream: RngStream, advantage: AdvantageStatus) -> i32 {
+        let first = self.range(stream, 1, 20);
+        return match advantage {
+            AdvantageStatus::Advantage => i32::max(first, self.range(stream, 1, 20)),
+            AdvantageStatus::Normal => first,
+            AdvantageStatus::Disadvantage => i32::min(first, self.range(stream, 1, 20)),
+        };
+    }
+}
+
+// A seed for when none is asked for, different every time
+pub fn make_seed() -> Seed {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_nanos()).unwrap_or(0);
+    return mix(nanos as u64 ^ (nanos >> 64) as u64);
+}
+
+// Reads a seed given on the command line as --seed <number>
+pub fn parse_seed_argument(args: &[String]) -> Result<Option<Seed>, String> {
+    let maybe_index = args.iter().position(|arg| arg == "--seed");
+    if maybe_index.is_none() {
+        return Ok(None);
+    }
+    let maybe_value = args.get(maybe_index.unwrap() + 1);
+    if maybe_value.is_none() {
+        return Err(String::from("--seed needs a number after it"));
+    }
+    return match maybe_value.unwrap().parse::<Seed>() {
+        Ok(seed) => Ok(Some(seed)),
+        Err(_) => Err(format!("Not a valid seed: {}", maybe_value.unwrap())),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut first = Rng::new(1234);
+        let mut second = Rng::new(1234);
+        for _ in 0..10 {
+            first.next_u64(RngStream::MapGen);
+        }
+        assert_eq!(first.next_u64(RngStream::Combat), second.next_u64(RngStream::Combat));
+        assert_ne!(Rng::new(1).next_u64(RngStream::Combat), Rng::new(2).next_u64(RngStream::Combat));
+
+        for _ in 0..1000 {
+            let roll = first.roll(RngStream::Combat, Dice::new(2, 6, 1));
+            assert!((3..=13).contains(&roll));
+            assert!((1..=20).contains(&first.roll_d20(RngStream::Combat, AdvantageStatus::Advantage)));
+        }
+    }
+
+    #[test]
+    fn test_parse_seed_argument() {
+        let args: Vec<String> = vec![String::from("voguelike"), String::from("--seed"), String::from("42")];
+        assert_eq!(parse_seed_argument(&args), Ok(Some(42)));
+        assert!(parse_seed_argument(&args[..2]).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{ai, action::{self, Action, ActionRequest, Noun, Wait}, dijkstra, entity::EntityID, game::Game, item::{self, WEIGHT_PER_POUND}, map, monster, new_action, simulation, tabletop, time::{Seconds, SECONDS_PER_TURN}};
+
+// How fast a creature moves in feet per round, after what it's carrying and
+// how tired it is
+pub fn get_speed(game: &Game, entity: EntityID) -> u16 {
+    let maybe_creature = game.components.get_creature(entity);
+    if maybe_creature.is_none() {
+        return tabletop::NORMAL_SPEED;
+    }
+    let creature = maybe_creature.unwrap();
+    let mut result = monster::get_speed(game, entity).unwrap_or(tabletop::get_base_speed(&creature.race));
+
+    if let Some(inventory) = game.components.get_inventory(entity) {
+        let carried = item::get_pile_weight(&inventory.items) / WEIGHT_PER_POUND;
+        let capacity = tabletop::carrying_capacity(creature.stats.strength, creature.size);
+        result = tabletop::encumbered_speed(result, u32::min(carried, u16::MAX as u32) as u16, capacity);
+    }
+
+    return tabletop::exhausted_speed(result, creature.exhaustion);
+}
+
+// How long a single round takes a creature, which is longer the slower it is.
+// A creature that can't move at all still gets to act at the normal pace.
+pub fn get_round_length(game: &Game, entity: EntityID) -> Seconds {
+    let speed = get_speed(game, entity);
+    if speed == 0 {
+        return SECONDS_PER_TURN;
+    }
+    return SECONDS_PER_TURN * tabletop::NORMAL_SPEED as Seconds / speed as Seconds;
+}
+
+// How long an action keeps a particular creature busy. Anything that takes a
+// single round takes as long as that creature's round.
+pub fn get_scaled_cost(game: &Game, entity: EntityID, action: Action) -> Seconds {
+    let cost = action::get_action_cost(action);
+    if cost != SECONDS_PER_TURN {
+        return cost;
+    }
+    let round = get_round_length(game, entity);
+
+    // Each step across a map drawn at a larger scale covers that much more
+    // ground, and rough country slows the going
+    let map = &game.current_map;
+    let maybe_position = game.components.get_position(entity).filter(|position| map.is_in_bounds(position.x, position.y));
+    if matches!(action, Action::Go(_)) && map.rounds_per_tile > 1 {
+        if let Some(position) = maybe_position {
+            return round * map.rounds_per_tile as Seconds * map::get_move_cost(map.get_tile(position.x, position.y)) as Seconds;
+        }
+    }
+    return round;
+}
+
+pub fn get_next_turn(game: &Game, entity: EntityID) -> Seconds {
+    return match game.components.get_turn(entity) {
+        Some(turn) => turn.next,
+        None => game.clock.time,
+    };
+}
+
+// Keeps a creature busy until the action it just took is done
+pub fn spend_time(game: &mut Game, entity: EntityID, seconds: Seconds) {
+    let now = game.clock.time;
+    if let Some(turn) = game.components.get_turn_mut(entity) {
+        turn.next = Seconds::max(turn.next, now) + seconds;
+    }
+}
+
+// The creature on the current map due to act soonest, other than the player
+fn get_next_actor(game: &Game) -> Option<EntityID> {
+    let player = game.special_entities.player;
+    let mut result: Option<EntityID> = None;
+    for creature in game.components.get_living_creatures() {
+        let map_index = game.components.get_map_index(creature);
+        if creature == player || map_index.is_none() || map_index.unwrap().map != game.current_map.id {
+            continue;
+        }
+        if result.is_none() || get_next_turn(game, creature) < get_next_turn(game, result.unwrap()) {
+            result = Some(creature);
+        }
+    }
+    return result;
+}
+
+// Monsters decide for themselves what to do, and anyone else bides their time
+fn take_turn(game: &mut Game, actor: EntityID) {
+    let before = get_next_turn(game, actor);
+    let request = match game.components.get_ai(actor) {
+        Some(_) => {
+            dijkstra::update_distance_maps(game);
+            ai::choose_action(game, actor)
+        },
+        None => ActionRequest {
+            actor,
+            action: new_action!(Wait),
+            noun: Noun::Nothing,
+            second: Noun::Nothing,
+        },
+    };
+    action::execute_action(game, request);
+
+    // Whatever happened, the turn is over
+    if get_next_turn(game, actor) == before {
+        let round = get_round_length(game, actor);
+        spend_time(game, actor, round);
+    }
+}
+
+// Runs the world forward, letting everyone due to act before the player take
+// their turns in order, until it's the player's turn again
+pub fn advance_to_player_turn(game: &mut Game) {
+    let player = game.special_entities.player;
+    loop {
+        let alive = game.components.get_alive(player);
+        if alive.is_none() || !alive.unwrap().alive {
+            return;
+        }
+
+        let player_turn = get_next_turn(game, player);
+        let maybe_actor = get_next_actor(game).filter(|actor| get_next_turn(game, *actor) < player_turn);
+        let until = match maybe_actor {
+            Some(actor) => get_next_turn(game, actor),
+            None => player_turn,
+        };
+        if until > game.clock.time {
+            simulation::pass_time(game, until - game.clock.time);
+        }
+
+        if maybe_actor.is_none() {
+            return;
+        }
+        take_turn(game, maybe_actor.unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::Go, component::{EntityType, Position}, map::{GameMap, Tile}, tabletop::Race};
+
+    #[test]
+    fn test_speed_and_encumbrance() {
+        let mut game = Game::new();
+        let player = game.special_entities.player;
+        assert_eq!(get_round_length(&game, player), SECONDS_PER_TURN);
+
+        game.components.get_creature_mut(player).unwrap().race = Race::Dwarf;
+        assert_eq!(get_speed(&game, player), 25);
+
+        game.components.get_inventory_mut(player).unwrap().items.push(item::Item::new(item::ItemType::PlateArmor));
+        assert_eq!(get_speed(&game, player), 15);
+        assert_eq!(get_round_length(&game, player), 2 * SECONDS_PER_TURN);
+
+        game.components.get_creature_mut(player).unwrap().exhaustion = 5;
+        assert_eq!(get_speed(&game, player), 0);
+        assert_eq!(get_round_length(&game, player), SECONDS_PER_TURN);
+    }
+
+    #[test]
+    fn test_faster_creatures_act_more_often() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 4, 1));
+        for x in 0..4 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+        let start = game.clock.time;
+        let player = game.special_entities.player;
+        game.components.get_turn_mut(player).unwrap().next = start;
+        game.components.get_creature_mut(player).unwrap().exhaustion = 2;
+
+        let monster = game.components.create_entity(EntityType::Monster);
+        game.components.get_turn_mut(monster).unwrap().next = start;
+        game.components.get_position_mut(monster).unwrap().x = 3;
+
+        // The player takes twice as long over a round as the monster does
+        action::execute_action(&mut game, ActionRequest {
+            actor: player,
+            action: new_action!(Wait),
+            noun: Noun::Nothing,
+            second: Noun::Nothing,
+        });
+        assert_eq!(game.clock.time, start + 2 * SECONDS_PER_TURN);
+        assert_eq!(get_next_turn(&game, player), game.clock.time);
+        assert_eq!(get_next_turn(&game, monster), start + 2 * SECONDS_PER_TURN);
+    }
+
+    #[test]
+    fn test_overland_steps_take_longer() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 3, 1));
+        game.current_map.set_tile(0, 0, Tile::Plains);
+        game.current_map.set_tile(1, 0, Tile::Road);
+        game.current_map.set_tile(2, 0, Tile::Forest);
+        game.current_map.rounds_per_tile = 50;
+        let player = game.special_entities.player;
+        *game.components.get_position_mut(player).unwrap() = Position { x: 0, y: 0 };
+        let start = game.clock.time;
+        game.components.get_turn_mut(player).unwrap().next = start;
+
+        let east = game.special_entities.east;
+        let step = |game: &mut Game| action::execute_action(game, ActionRequest {
+            actor: player,
+            action: new_action!(Go),
+            noun: Noun::Entity(east),
+            second: Noun::Nothing,
+        });
+        step(&mut game);
+        assert_eq!(game.clock.time, start + 50 * SECONDS_PER_TURN);
+
+        // The forest is twice as slow going as the road
+        step(&mut game);
+        assert_eq!(game.clock.time, start + 150 * SECONDS_PER_TURN);
+    }
+}
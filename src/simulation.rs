@@ -0,0 +1,191 @@
+use crate::{action, entity::EntityID, fire, game::Game, item::{self, Item}, lighting, monster, npc, registry, tabletop, tag::{Tag, TagCache}, temperature, time::{Seconds, SECONDS_PER_DAY, SECONDS_PER_MINUTE, SECONDS_PER_TURN}, weather};
+
+// Advances everything in the world that changes on its own
+pub fn pass_time(game: &mut Game, seconds: Seconds) {
+    for creature in game.components.get_living_creatures() {
+        update_nutrition(game, creature, seconds);
+    }
+    age_items(game, seconds);
+
+    // Fires and heat on creatures change with every round on the clock,
+    // however the time is split up between turns, while the world at large
+    // warms and cools slowly enough to work out once a minute
+    let end = game.clock.time + seconds;
+    loop {
+        let next_round = (game.clock.time / SECONDS_PER_TURN + 1) * SECONDS_PER_TURN;
+        if next_round > end {
+            break;
+        }
+        game.clock.advance(next_round - game.clock.time);
+        fire::update_fires(game);
+        temperature::update_creatures(game);
+        if game.clock.time.is_multiple_of(SECONDS_PER_DAY) {
+            weather::roll_weather(game);
+        }
+        if game.clock.time.is_multiple_of(SECONDS_PER_MINUTE) {
+            weather::update_outdoors(game);
+            weather::strike_lightning(game);
+            registry::spawn_wanderers(game);
+            temperature::update_temperatures(game);
+        }
+    }
+    game.clock.advance(end - game.clock.time);
+    weather::update_outdoors(game);
+    lighting::update_visibility(game);
+    monster::update_bestiary(game);
+}
+
+pub fn add_exhaustion(game: &mut Game, entity: EntityID) {
+    let maybe_creature = game.components.get_creature_mut(entity);
+    if maybe_creature.is_none() {
+        return;
+    }
+    let creature = maybe_creature.unwrap();
+    creature.exhaustion = u8::min(creature.exhaustion + 1, tabletop::MAX_EXHAUSTION);
+    let exhaustion = creature.exhaustion;
+
+    if exhaustion >= tabletop::MAX_EXHAUSTION {
+        action::kill(game, entity);
+        return;
+    }
+
+    // Losing half your hit point maximum can leave you with too many
+    if let Some(health) = game.components.get_health_mut(entity) {
+        health.current = i32::min(health.current, tabletop::exhausted_max_hp(health.maximum, exhaustion));
+    }
+    action::report(game, entity, format!("You feel exhausted. (level {})", exhaustion));
+}
+
+fn update_nutrition(game: &mut Game, entity: EntityID, seconds: Seconds) {
+    // Townsfolk see to their own meals
+    if npc::get_npc_role(game, entity).is_some() {
+        return;
+    }
+    let constitution = match game.components.get_creature(entity) {
+        Some(creature) => creature.stats.constitution,
+        None => return,
+    };
+    let maybe_nutrition = game.components.get_nutrition_mut(entity);
+    if maybe_nutrition.is_none() {
+        return;
+    }
+    let nutrition = maybe_nutrition.unwrap();
+
+    let had_food = nutrition.food > 0;
+    let had_water = nutrition.water > 0;
+    nutrition.starving += seconds.saturating_sub(nutrition.food);
+    nutrition.parched += seconds.saturating_sub(nutrition.water);
+    nutrition.food = nutrition.food.saturating_sub(seconds);
+    nutrition.water = nutrition.water.saturating_sub(seconds);
+
+    // Going without food is survivable for a few days, then each further day
+    // takes its toll. Every full day without water does.
+    let mut levels: u32 = 0;
+    let food_limit = tabletop::days_without_food(constitution) * SECONDS_PER_DAY;
+    while nutrition.starving >= food_limit {
+        nutrition.starving -= SECONDS_PER_DAY;
+        levels += 1;
+    }
+    while nutrition.parched >= SECONDS_PER_DAY {
+        nutrition.parched -= SECONDS_PER_DAY;
+        levels += 1;
+    }
+
+    let starved = had_food && nutrition.food == 0;
+    let dried_out = had_water && nutrition.water == 0;
+    if starved {
+        action::report(game, entity, String::from("You are starving."));
+    }
+    if dried_out {
+        action::report(game, entity, String::from("You are parched."));
+    }
+    for _ in 0..levels {
+        add_exhaustion(game, entity);
+    }
+}
+
+fn age_items(game: &mut Game, seconds: Seconds) {
+    let tag_cache = &game.data_tables.tag_cache;
+    for pile in game.current_map.items.iter_mut() {
+        age_pile(pile, seconds, tag_cache);
+    }
+
+    for creature in game.components.get_living_creatures() {
+        if let Some(inventory) = game.components.get_inventory_mut(creature) {
+            age_pile(&mut inventory.items, seconds, tag_cache);
+        }
+    }
+}
+
+fn age_pile(items: &mut [Item], seconds: Seconds, tag_cache: &TagCache) {
+    for item in items.iter_mut() {
+        if item::has_tag(item, Tag::Organic, tag_cache) {
+            item.age += seconds;
+        }
+        age_pile(&mut item.contents, seconds, tag_cache);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{self, ItemType};
+
+    #[test]
+    fn test_starvation_exhaustion() {
+        let mut game = Game::new();
+        let player = game.special_entities.player;
+        game.components.get_nutrition_mut(player).unwrap().water = tabletop::MAX_WATER * 10;
+
+        // Full food lasts two days, then an average constitution holds out for three more
+        for _ in 0..5 {
+            pass_time(&mut game, SECONDS_PER_DAY);
+        }
+        assert_eq!(game.components.get_creature(player).unwrap().exhaustion, 1);
+
+        pass_time(&mut game, SECONDS_PER_DAY);
+        assert_eq!(game.components.get_creature(player).unwrap().exhaustion, 2);
+    }
+
+    #[test]
+    fn test_starvation_over_several_days_at_once() {
+        let mut game = Game::new();
+        let player = game.special_entities.player;
+        game.components.get_nutrition_mut(player).unwrap().water = tabletop::MAX_WATER * 10;
+
+        // A week in one go owes every level a day at a time would have
+        pass_time(&mut game, 7 * SECONDS_PER_DAY);
+        assert_eq!(game.components.get_creature(player).unwrap().exhaustion, 3);
+    }
+
+    #[test]
+    fn test_rations_spoil() {
+        let mut game = Game::new();
+        let player = game.special_entities.player;
+        let shelf_life = item::get_shelf_life(ItemType::Rations).unwrap();
+        // Keep the player fed so they live to see the rations go off
+        let nutrition = game.components.get_nutrition_mut(player).unwrap();
+        nutrition.food = shelf_life;
+        nutrition.water = shelf_life;
+        let inventory = game.components.get_inventory_mut(player).unwrap();
+        inventory.items.push(Item::new(ItemType::Rations));
+        inventory.items.push(Item::new(ItemType::Dagger));
+
+        pass_time(&mut game, shelf_life);
+
+        let items = &game.components.get_inventory(player).unwrap().items;
+        assert!(item::is_spoiled(&items[0]));
+        assert_eq!(items[1].age, 0);
+    }
+
+    #[test]
+    fn test_time_passes_on_clock() {
+        let mut game = Game::new();
+        game.current_map = Box::new(crate::map::GameMap::new(0, 80, 40));
+        let start = game.clock.time;
+
+        pass_time(&mut game, 8 * crate::time::SECONDS_PER_HOUR);
+        assert_eq!(game.clock.time, start + 8 * crate::time::SECONDS_PER_HOUR);
+        assert_eq!(game.clock.get_hour(), 16);
+    }
+}
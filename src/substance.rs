@@ -0,0 +1,139 @@
+use enum_map::{enum_map, Enum, EnumMap};
+use strum_macros::EnumString;
+
+use crate::{item::Weight, tabletop::{DamageType, Dice}, tag::Tag, time::Seconds};
+
+// Volumes are stored in fluid ounces
+pub type Volume = u32;
+pub const VOLUME_PER_PINT: Volume = 16;
+
+// How much is swallowed in one drink, a potion vial holds exactly this much
+pub const DRINK_VOLUME: Volume = 4;
+
+// How hard it is to notice something poisonous by sense, tasting is more reliable
+pub const POISON_SMELL_DC: i8 = 15;
+pub const POISON_TASTE_DC: i8 = 10;
+
+#[derive(Clone, Copy, Debug, Enum, EnumString, PartialEq, Eq)]
+pub enum Substance {
+    Oil,
+    Poison,
+    PotionOfGreaterHealing,
+    PotionOfHealing,
+    Water,
+    Wine,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Liquid {
+    pub substance: Substance,
+    pub volume: Volume,
+}
+
+impl Liquid {
+    pub fn new(substance: Substance, volume: Volume) -> Self {
+        Self {
+            substance,
+            volume,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrinkEffect {
+    Damage(Dice, DamageType),
+    Heal(Dice),
+    Nothing,
+}
+
+// How long each ounce keeps thirst away, a gallon of water lasts a day
+pub const fn get_hydration(substance: Substance) -> Seconds {
+    match substance {
+        Substance::Oil => 0,
+        Substance::Poison => 0,
+        Substance::PotionOfGreaterHealing => 675,
+        Substance::PotionOfHealing => 675,
+        Substance::Water => 675,
+        Substance::Wine => 300,
+    }
+}
+
+pub const fn get_name(substance: Substance) -> &'static str {
+    match substance {
+        Substance::Oil => "oil",
+        Substance::Poison => "poison",
+        Substance::PotionOfGreaterHealing => "potion of greater healing",
+        Substance::PotionOfHealing => "potion of healing",
+        Substance::Water => "water",
+        Substance::Wine => "wine",
+    }
+}
+
+// Poisons are disguised until someone notices them
+pub fn get_apparent_name(substance: Substance, substance_tag_map: &SubstanceTagMap) -> &'static str {
+    if substance_tag_map[substance].contains(&Tag::Poisonous) {
+        return "clear liquid";
+    }
+    return get_name(substance);
+}
+
+pub const fn get_drink_effect(substance: Substance) -> DrinkEffect {
+    match substance {
+        Substance::Oil => DrinkEffect::Nothing,
+        Substance::Poison => DrinkEffect::Damage(Dice::new(1, 12, 0), DamageType::Poison),
+        Substance::PotionOfGreaterHealing => DrinkEffect::Heal(Dice::new(4, 4, 4)),
+        Substance::PotionOfHealing => DrinkEffect::Heal(Dice::new(2, 4, 2)),
+        Substance::Water => DrinkEffect::Nothing,
+        Substance::Wine => DrinkEffect::Nothing,
+    }
+}
+
+pub const fn get_smell(substance: Substance) -> &'static str {
+    match substance {
+        Substance::Oil => "It smells of lamp oil.",
+        Substance::Poison => "It smells faintly bitter.",
+        Substance::PotionOfGreaterHealing => "It smells of cinnamon and cloves.",
+        Substance::PotionOfHealing => "It smells of mint.",
+        Substance::Water => "It doesn't smell of anything.",
+        Substance::Wine => "It smells of grapes and oak.",
+    }
+}
+
+pub const fn get_taste(substance: Substance) -> &'static str {
+    match substance {
+        Substance::Oil => "It tastes thick and greasy.",
+        Substance::Poison => "It tastes bitter and numbs your tongue.",
+        Substance::PotionOfGreaterHealing => "It tastes warm and spicy.",
+        Substance::PotionOfHealing => "It tastes sweet and cool.",
+        Substance::Water => "It tastes clean.",
+        Substance::Wine => "It tastes rich and fruity.",
+    }
+}
+
+pub const fn get_weight_per_ounce(substance: Substance) -> Weight {
+    match substance {
+        Substance::Oil => 58,
+        Substance::Poison => 65,
+        Substance::PotionOfGreaterHealing => 65,
+        Substance::PotionOfHealing => 65,
+        Substance::Water => 65,
+        Substance::Wine => 65,
+    }
+}
+
+pub fn get_liquid_weight(liquid: &Liquid) -> Weight {
+    return get_weight_per_ounce(liquid.substance) * liquid.volume;
+}
+
+pub type SubstanceTagMap = EnumMap<Substance, Vec<Tag>>;
+
+pub fn generate_substance_tag_map() -> SubstanceTagMap {
+    enum_map! {
+        Substance::Oil => vec!(Tag::Flammable, Tag::Liquid),
+        Substance::Poison => vec!(Tag::Beverage, Tag::Liquid, Tag::Poisonous),
+        Substance::PotionOfGreaterHealing => vec!(Tag::Beverage, Tag::Liquid),
+        Substance::PotionOfHealing => vec!(Tag::Beverage, Tag::Liquid),
+        Substance::Water => vec!(Tag::Beverage, Tag::Liquid),
+        Substance::Wine => vec!(Tag::Beverage, Tag::Liquid),
+    }
+}
@@ -2,9 +2,12 @@ use std::str::FromStr;
 
 use strum_macros::EnumIter;
 
+use crate::time::{Seconds, SECONDS_PER_DAY};
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct FromStringError;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AdvantageStatus {
     Advantage,
     Normal,
@@ -117,6 +120,7 @@ impl FromStr for Class {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DamageType {
     Acid,
     Bludgeoning,
@@ -133,6 +137,28 @@ pub enum DamageType {
     Thunder,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dice {
+    pub count: u8,
+    pub sides: u8,
+    pub bonus: i8,
+}
+
+impl Dice {
+    pub const fn new(count: u8, sides: u8, bonus: i8) -> Self {
+        Self {
+            count,
+            sides,
+            bonus,
+        }
+    }
+
+    // The rounded down average of a roll, as used for fixed monster hit points
+    pub fn average(&self) -> i32 {
+        return (self.count as i32 * (self.sides as i32 + 1)) / 2 + self.bonus as i32;
+    }
+}
+
 #[derive(Debug, EnumIter)]
 pub enum Race {
     Dragonborn,
@@ -181,6 +207,7 @@ impl FromStr for Race {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Size {
     Tiny,
     Small,
@@ -190,6 +217,7 @@ pub enum Size {
     Gargantuan
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Skill {
     Acrobatics,
     AnimalHandling,
@@ -222,6 +250,18 @@ pub enum Stat {
 
 pub const NUMBER_OF_STATS: usize = 6;
 
+// Everyone starts out at first level, where the bonus is +2
+pub const CRITICAL_HIT: i32 = 20;
+pub const PROFICIENCY_BONUS: i8 = 2;
+
+// A creature dies on reaching the last level of exhaustion
+pub const MAX_EXHAUSTION: u8 = 6;
+
+// A day's worth of food can be eaten ahead, and a day's worth of water drunk
+pub const MAX_FOOD: Seconds = 2 * SECONDS_PER_DAY;
+pub const MAX_WATER: Seconds = SECONDS_PER_DAY;
+
+#[derive(Clone, Copy, Debug)]
 pub struct Stats {
     pub charisma: u8,
     pub constitution: u8,
@@ -244,6 +284,136 @@ pub fn carrying_capacity(strength: u8, size: Size) -> u16 {
     }
 }
 
+// How many days a creature can go without food before it starts to suffer
+pub fn days_without_food(constitution: u8) -> u64 {
+    return i8::max(1, 3 + modifier(constitution)) as u64;
+}
+
+// Hit point maximum is halved from the fourth level of exhaustion
+pub fn exhausted_max_hp(maximum: i32, exhaustion: u8) -> i32 {
+    if exhaustion >= 4 {
+        return maximum / 2;
+    }
+    return maximum;
+}
+
+// Walking speed in feet per round that everything else is measured against
+pub const NORMAL_SPEED: u16 = 30;
+
+pub fn get_base_speed(race: &Race) -> u16 {
+    return match race {
+        Race::Dragonborn => 30,
+        Race::Dwarf => 25,
+        Race::Elf => 30,
+        Race::Gnome => 25,
+        Race::HalfElf => 30,
+        Race::HalfOrc => 30,
+        Race::Halfling => 25,
+        Race::Human => 30,
+        Race::Tiefling => 30,
+    };
+}
+
+// Carrying more than a third of capacity slows a creature down, and more than
+// two thirds slows it down further. Anything past capacity can only be dragged.
+pub fn encumbered_speed(speed: u16, carried: u16, capacity: u16) -> u16 {
+    if carried > capacity {
+        return u16::min(speed, 5);
+    }
+    if carried > capacity * 2 / 3 {
+        return speed.saturating_sub(20);
+    }
+    if carried > capacity / 3 {
+        return speed.saturating_sub(10);
+    }
+    return speed;
+}
+
+// Speed is halved from the second level of exhaustion, and gone from the fifth
+pub fn exhausted_speed(speed: u16, exhaustion: u8) -> u16 {
+    if exhaustion >= 5 {
+        return 0;
+    }
+    if exhaustion >= 2 {
+        return speed / 2;
+    }
+    return speed;
+}
+
+// How far a race can see in the dark, in five foot tiles
+pub fn get_darkvision(race: &Race) -> u16 {
+    return match race {
+        Race::Dragonborn => 0,
+        Race::Dwarf => 12,
+        Race::Elf => 12,
+        Race::Gnome => 12,
+        Race::HalfElf => 12,
+        Race::HalfOrc => 12,
+        Race::Halfling => 0,
+        Race::Human => 0,
+        Race::Tiefling => 12,
+    };
+}
+
+pub fn get_damage_type_name(damage_type: DamageType) -> &'static str {
+    return match damage_type {
+        DamageType::Acid => "acid",
+        DamageType::Bludgeoning => "bludgeoning",
+        DamageType::Cold => "cold",
+        DamageType::Fire => "fire",
+        DamageType::Force => "force",
+        DamageType::Lightning => "lightning",
+        DamageType::Necrotic => "necrotic",
+        DamageType::Piercing => "piercing",
+        DamageType::Poison => "poison",
+        DamageType::Psychic => "psychic",
+        DamageType::Radiant => "radiant",
+        DamageType::Slashing => "slashing",
+        DamageType::Thunder => "thunder",
+    };
+}
+
+pub fn get_stat(stats: &Stats, stat: Stat) -> u8 {
+    return match stat {
+        Stat::Charisma => stats.charisma,
+        Stat::Constitution => stats.constitution,
+        Stat::Dexterity => stats.dexterity,
+        Stat::Intelligence => stats.intelligence,
+        Stat::Strength => stats.strength,
+        Stat::Wisdom => stats.wisdom,
+    };
+}
+
+pub fn hit_die(class: &Class) -> u8 {
+    return match class {
+        Class::Barbarian => 12,
+        Class::Bard => 8,
+        Class::Cleric => 8,
+        Class::Druid => 8,
+        Class::Fighter => 10,
+        Class::Monk => 8,
+        Class::Paladin => 10,
+        Class::Ranger => 10,
+        Class::Rogue => 8,
+        Class::Sorcerer => 6,
+        Class::Warlock => 8,
+        Class::Wizard => 6,
+    };
+}
+
+// A natural 1 always misses and a natural 20 always hits, critically
+pub fn is_hit(roll: i32, to_hit: i8, armor_class: u8) -> bool {
+    if roll == 1 {
+        return false;
+    }
+    return roll == CRITICAL_HIT || roll + to_hit as i32 >= armor_class as i32;
+}
+
+// A critical hit rolls all of its damage dice twice
+pub fn critical_damage(dice: Dice) -> Dice {
+    return Dice::new(dice.count * 2, dice.sides, dice.bonus);
+}
+
 pub fn modifier(ability: u8) -> i8 {
     return (ability as i8 / 2) - 5;
 }
@@ -263,6 +433,17 @@ pub fn move_capacity(strength: u8, size: Size) -> u16 {
     }
 }
 
+// Advantage and disadvantage cancel out, however many of each there are
+pub fn combine_advantage(first: AdvantageStatus, second: AdvantageStatus) -> AdvantageStatus {
+    return match (first, second) {
+        (AdvantageStatus::Normal, other) => other,
+        (other, AdvantageStatus::Normal) => other,
+        (AdvantageStatus::Advantage, AdvantageStatus::Advantage) => AdvantageStatus::Advantage,
+        (AdvantageStatus::Disadvantage, AdvantageStatus::Disadvantage) => AdvantageStatus::Disadvantage,
+        _ => AdvantageStatus::Normal,
+    };
+}
+
 pub fn passive_score(modifiers: i8, advantage: AdvantageStatus) -> i8 {
     let advantage_mod: i8 = match advantage {
         AdvantageStatus::Advantage => 5,
@@ -363,6 +544,32 @@ mod tests {
         assert_eq!(modifier(30), 10);
     }
     
+    #[test]
+    fn test_attack_rolls() {
+        assert!(is_hit(10, 3, 13));
+        assert!(!is_hit(9, 3, 13));
+        assert!(!is_hit(1, 20, 5));
+        assert!(is_hit(20, -5, 30));
+        assert_eq!(critical_damage(Dice::new(1, 6, 2)), Dice::new(2, 6, 2));
+    }
+
+    #[test]
+    fn test_dice_average() {
+        assert_eq!(Dice::new(1, 4, 0).average(), 2);
+        assert_eq!(Dice::new(1, 6, 0).average(), 3);
+        assert_eq!(Dice::new(2, 4, 2).average(), 7);
+        assert_eq!(Dice::new(4, 4, 4).average(), 14);
+        assert_eq!(Dice::new(2, 8, -1).average(), 8);
+    }
+
+    #[test]
+    fn test_days_without_food() {
+        assert_eq!(days_without_food(1), 1);
+        assert_eq!(days_without_food(6), 1);
+        assert_eq!(days_without_food(10), 3);
+        assert_eq!(days_without_food(14), 5);
+    }
+
     #[test]
     fn test_passive_score() {
         assert_eq!(passive_score(0, AdvantageStatus::Normal), 10);
@@ -375,6 +582,18 @@ mod tests {
         assert_eq!(passive_score(10, AdvantageStatus::Advantage), 25);
     }
 
+    #[test]
+    fn test_speed() {
+        assert_eq!(get_base_speed(&Race::Dwarf), 25);
+        assert_eq!(encumbered_speed(30, 50, 150), 30);
+        assert_eq!(encumbered_speed(30, 51, 150), 20);
+        assert_eq!(encumbered_speed(30, 101, 150), 10);
+        assert_eq!(encumbered_speed(30, 151, 150), 5);
+        assert_eq!(exhausted_speed(30, 1), 30);
+        assert_eq!(exhausted_speed(30, 2), 15);
+        assert_eq!(exhausted_speed(30, 5), 0);
+    }
+
     #[test]
     fn test_carrying_capacity() {
         assert_eq!(carrying_capacity(1, Size::Tiny), 7);
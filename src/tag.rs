@@ -1,6 +1,8 @@
 use enum_map::{enum_map, Enum, EnumMap};
 
-#[derive(Clone, Copy, Debug, Enum)]
+use crate::{item::{ItemTagMap, ItemType}, material::{self, Material, MaterialMap}, substance::{Substance, SubstanceTagMap}};
+
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
 pub enum Tag {
     Ammunition,
     Animate,
@@ -15,6 +17,7 @@ pub enum Tag {
     FinesseWeapon,
     Flammable,
     Food,
+    Fragile,
     Frozen,
     Gas,
     GasContainer,
@@ -44,11 +47,13 @@ pub enum Tag {
     Solid,
     SolidContainer,
     SpecialWeapon,
+    Stackable,
     ThrownWeapon,
     Transparent,
     TwoHandedWeapon,
     VersatileWeapon,
     Weapon,
+    Wet,
     Wettable
 }
 
@@ -69,6 +74,7 @@ pub fn generate_tag_map() -> TagMap {
         Tag::FinesseWeapon => Some(Tag::Weapon),
         Tag::Flammable => None,
         Tag::Food => Some(Tag::Consumable),
+        Tag::Fragile => None,
         Tag::Frozen => Some(Tag::Cold),
         Tag::Gas => None,
         Tag::GasContainer => Some(Tag::Container),
@@ -98,13 +104,164 @@ pub fn generate_tag_map() -> TagMap {
         Tag::Solid => None,
         Tag::SolidContainer => Some(Tag::Container),
         Tag::SpecialWeapon => Some(Tag::Weapon),
+        Tag::Stackable => None,
         Tag::ThrownWeapon => Some(Tag::Weapon),
         Tag::Transparent => None,
         Tag::TwoHandedWeapon => Some(Tag::Weapon),
         Tag::VersatileWeapon => Some(Tag::Weapon),
         Tag::Weapon => None,
+        Tag::Wet => None,
         Tag::Wettable => None
     };
     return result;
 }
 
+
+// Every tag fits in one bit of a u128
+const _: () = assert!(<Tag as Enum>::LENGTH <= 128);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TagSet {
+    bits: u128,
+}
+
+impl TagSet {
+    pub const fn new() -> Self {
+        Self {
+            bits: 0
+        }
+    }
+
+    pub fn from_tags(tags: &[Tag]) -> Self {
+        let mut result = Self::new();
+        for tag in tags {
+            result.insert(*tag);
+        }
+        return result;
+    }
+
+    pub fn contains(&self, tag: Tag) -> bool {
+        return self.bits & (1 << tag.into_usize()) != 0;
+    }
+
+    pub fn insert(&mut self, tag: Tag) {
+        self.bits |= 1 << tag.into_usize();
+    }
+
+    pub fn remove(&mut self, tag: Tag) {
+        self.bits &= !(1 << tag.into_usize());
+    }
+
+    pub fn union(&self, other: TagSet) -> TagSet {
+        return TagSet {
+            bits: self.bits | other.bits
+        };
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Tag> + '_ {
+        return (0..Tag::LENGTH).map(Tag::from_usize).filter(|tag| self.contains(*tag));
+    }
+}
+
+// Whether the tag is the ancestor or descends from it, so a Frozen thing is
+// also Cold
+pub fn is_a(tag: Tag, ancestor: Tag, tag_map: &TagMap) -> bool {
+    let mut current: Option<Tag> = Some(tag);
+    while current.is_some() {
+        if current.unwrap() == ancestor {
+            return true;
+        }
+        current = tag_map[current.unwrap()];
+    }
+    return false;
+}
+
+// The effective tags of every kind of item, material and substance with all
+// their ancestors filled in, so lookups during play are a single bit test
+pub struct TagCache {
+    ancestors: EnumMap<Tag, TagSet>,
+    pub items: EnumMap<ItemType, TagSet>,
+    pub materials: EnumMap<Material, TagSet>,
+    pub substances: EnumMap<Substance, TagSet>,
+}
+
+impl TagCache {
+    pub fn new(tag_map: &TagMap, item_tag_map: &ItemTagMap, material_map: &MaterialMap, substance_tag_map: &SubstanceTagMap) -> Self {
+        let mut ancestors: EnumMap<Tag, TagSet> = EnumMap::default();
+        for (tag, set) in ancestors.iter_mut() {
+            for ancestor in (0..Tag::LENGTH).map(Tag::from_usize) {
+                if is_a(tag, ancestor, tag_map) {
+                    set.insert(ancestor);
+                }
+            }
+        }
+
+        let mut result = Self {
+            ancestors,
+            items: EnumMap::default(),
+            materials: EnumMap::default(),
+            substances: EnumMap::default(),
+        };
+
+        for (item_type, tags) in item_tag_map.iter() {
+            result.items[item_type] = result.expand(TagSet::from_tags(tags));
+        }
+        for (material, _) in material_map.iter() {
+            let tags = material::get_material_tags(material, material_map);
+            result.materials[material] = result.expand(TagSet::from_tags(&tags));
+        }
+        for (substance, tags) in substance_tag_map.iter() {
+            result.substances[substance] = result.expand(TagSet::from_tags(tags));
+        }
+
+        return result;
+    }
+
+    // Adds the ancestors of every tag in the set, for runtime state tags that
+    // can't be worked out ahead of time
+    pub fn expand(&self, tags: TagSet) -> TagSet {
+        let mut result = tags;
+        for tag in tags.iter() {
+            result = result.union(self.ancestors[tag]);
+        }
+        return result;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{item, substance};
+
+    #[test]
+    fn test_is_a() {
+        let tag_map = generate_tag_map();
+        assert!(is_a(Tag::HeavyArmor, Tag::Armor, &tag_map));
+        assert!(is_a(Tag::Frozen, Tag::Cold, &tag_map));
+        assert!(is_a(Tag::Armor, Tag::Armor, &tag_map));
+        assert!(!is_a(Tag::Armor, Tag::HeavyArmor, &tag_map));
+        assert!(is_a(Tag::Burning, Tag::Hot, &tag_map));
+    }
+
+    #[test]
+    fn test_tag_set() {
+        let mut tags = TagSet::from_tags(&[Tag::Wettable, Tag::Ammunition]);
+        assert!(tags.contains(Tag::Wettable));
+        assert!(tags.contains(Tag::Ammunition));
+        assert!(!tags.contains(Tag::Weapon));
+
+        tags.remove(Tag::Wettable);
+        assert_eq!(tags.iter().collect::<Vec<Tag>>(), vec!(Tag::Ammunition));
+    }
+
+    #[test]
+    fn test_tag_cache() {
+        let cache = TagCache::new(&generate_tag_map(), &item::generate_item_tag_map(),
+            &material::generate_material_map(), &substance::generate_substance_tag_map());
+
+        assert!(cache.items[ItemType::Rations].contains(Tag::Consumable));
+        assert!(cache.materials[Material::Steel].contains(Tag::Conductive));
+        assert!(cache.substances[Substance::Water].contains(Tag::Consumable));
+        assert!(cache.expand(TagSet::from_tags(&[Tag::Frozen])).contains(Tag::Cold));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{action, game::Game, item::{self, Item}, map::{self, GameMap, Tile}, rng::RngStream, substance::Substance, tabletop::{DamageType, Dice}, tag::{Tag, TagCache, TagSet}};
+
+// Temperatures are in degrees Celsius
+pub type Temperature = i16;
+
+pub const AMBIENT_TEMPERATURE: Temperature = 15;
+pub const FREEZING_POINT: Temperature = 0;
+
+// Cold enough to chill to the touch, and warm enough to dry things out
+pub const COLD_TEMPERATURE: Temperature = 5;
+pub const DRYING_TEMPERATURE: Temperature = 30;
+
+// Too hot to hold, and too hot to stand in
+pub const HOT_TEMPERATURE: Temperature = 60;
+pub const SCORCHING_TEMPERATURE: Temperature = 200;
+
+pub const FIRE_TEMPERATURE: Temperature = 600;
+
+// Picking up something burning hot or frozen solid
+pub const HANDLING_DAMAGE: Dice = Dice::new(1, 4, 0);
+// Standing somewhere scorching that isn't actually on fire, like next to magma
+pub const SCORCHING_DAMAGE: Dice = Dice::new(2, 6, 0);
+
+// How much of the difference things close each time they warm or cool
+const TILE_CONDUCTION: i32 = 8;
+const ENTITY_CONDUCTION: i32 = 4;
+
+// Tiles that stay at a fixed temperature and warm everything around them
+pub fn get_heat_source(tile: &Tile) -> Option<Temperature> {
+    return match tile {
+        Tile::Forge => Some(400),
+        Tile::Magma => Some(1000),
+        _ => None,
+    };
+}
+
+pub const fn get_freezing_point(substance: Substance) -> Temperature {
+    match substance {
+        Substance::Oil => -20,
+        Substance::Poison => -5,
+        Substance::PotionOfGreaterHealing => -5,
+        Substance::PotionOfHealing => -5,
+        Substance::Water => 0,
+        Substance::Wine => -8,
+    }
+}
+
+// Hot and Cold follow from how warm something is rather than being set
+pub fn get_temperature_tags(temperature: Temperature) -> TagSet {
+    let mut result = TagSet::new();
+    if temperature >= HOT_TEMPERATURE {
+        result.insert(Tag::Hot);
+    }
+    if temperature <= COLD_TEMPERATURE {
+        result.insert(Tag::Cold);
+    }
+    return result;
+}
+
+pub fn get_item_temperature_tags(item: &Item) -> TagSet {
+    let mut result = get_temperature_tags(item.temperature);
+    if is_frozen(item) {
+        result.insert(Tag::Frozen);
+    }
+    return result;
+}
+
+pub fn is_frozen(item: &Item) -> bool {
+    if item.state.contains(Tag::Wet) && item.temperature <= FREEZING_POINT {
+        return true;
+    }
+    return item.liquid.is_some() && item.temperature <= get_freezing_point(item.liquid.unwrap().substance);
+}
+
+// Moves part of the way towards the target, always by at least a degree
+fn approach(current: Temperature, target: Temperature, conduction: i32) -> Temperature {
+    let difference = target as i32 - current as i32;
+    let mut step = difference / conduction;
+    if step == 0 {
+        step = difference.signum();
+    }
+    return (current as i32 + step) as Temperature;
+}
+
+// Warms and cools every tile and item on the current map by a minute
+pub fn update_temperatures(game: &mut Game) {
+    let tag_cache = &game.data_tables.tag_cache;
+    let map = game.current_map.as_mut();
+
+    conduct_heat(map);
+    for index in 0..map.tiles.len() {
+        let (x, y) = map.index_to_coordinates(index);
+        change_tile_state(map, x, y, tag_cache);
+
+        let temperature = map.temperatures[index];
+        let wet = map.is_wet(x, y, tag_cache);
+        for item in map.items[index].iter_mut() {
+            update_item(item, temperature, wet, tag_cache);
+        }
+    }
+}
+
+// Warms and cools creatures and what they carry by a round, and scorches
+// anyone standing somewhere too hot
+pub fn update_creatures(game: &mut Game) {
+    let map_id = game.current_map.id;
+    for creature in game.components.get_living_creatures() {
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        if map_index.is_none() || position.is_none() || map_index.unwrap().map != map_id {
+            continue;
+        }
+        let (x, y) = (position.unwrap().x, position.unwrap().y);
+        if !game.current_map.is_in_bounds(x, y) {
+            continue;
+        }
+        let surroundings = game.current_map.get_temperature(x, y);
+        let is_wet = game.current_map.is_wet(x, y, &game.data_tables.tag_cache);
+
+        // Standing in water or a puddle soaks whoever's there, until they
+        // step out of it again
+        if let Some(status) = game.components.get_status_mut(creature) {
+            status.temperature = approach(status.temperature, surroundings, ENTITY_CONDUCTION);
+            if is_wet {
+                status.tags.insert(Tag::Wet);
+            } else {
+                status.tags.remove(Tag::Wet);
+            }
+        }
+
+        // Whatever is carried warms and cools along with its owner, but
+        // stays dry
+        let tag_cache = &game.data_tables.tag_cache;
+        if let Some(inventory) = game.components.get_inventory_mut(creature) {
+            for item in inventory.items.iter_mut() {
+                update_item(item, surroundings, false, tag_cache);
+            }
+        }
+
+        // Fires do their own damage
+        if surroundings >= SCORCHING_TEMPERATURE && !game.current_map.is_burning(x, y) {
+            action::report(game, creature, String::from("The heat scorches you!"));
+            let damage = game.rng.roll(RngStream::Combat, SCORCHING_DAMAGE);
+            action::apply_damage(game, creature, damage, DamageType::Fire);
+        }
+    }
+}
+
+// Heat spreads to the four neighboring tiles, and everything drifts back
+// towards the ambient temperature unless something keeps it warm
+fn conduct_heat(map: &mut GameMap) {
+    let mut result: Vec<Temperature> = Vec::with_capacity(map.temperatures.len());
+
+    for index in 0..map.temperatures.len() {
+        let (x, y) = map.index_to_coordinates(index);
+        if map.fires[index] > 0 {
+            result.push(FIRE_TEMPERATURE);
+            continue;
+        }
+        if let Some(source) = get_heat_source(map.get_tile(x, y)) {
+            result.push(source);
+            continue;
+        }
+
+        let mut total: i32 = map.temperatures[index] as i32;
+        let mut count: i32 = 1;
+        if x > 0 {
+            total += map.temperatures[index - 1] as i32;
+            count += 1;
+        }
+        if x + 1 < map.width {
+            total += map.temperatures[index + 1] as i32;
+            count += 1;
+        }
+        if y > 0 {
+            total += map.temperatures[index - map.width as usize] as i32;
+            count += 1;
+        }
+        if y + 1 < map.height {
+            total += map.temperatures[index + map.width as usize] as i32;
+            count += 1;
+        }
+        let average = (total / count) as Temperature;
+        result.push(approach(average, map.get_ambient_temperature(x, y), TILE_CONDUCTION));
+    }
+
+    map.temperatures = result;
+}
+
+// Water freezes over and ice melts, and spilled liquids dry up in the heat
+fn change_tile_state(map: &mut GameMap, x: u16, y: u16, tag_cache: &TagCache) {
+    let temperature = map.get_temperature(x, y);
+    let tile = *map.get_tile(x, y);
+
+    if map::get_liquid_source(&tile) == Some(Substance::Water) && temperature <= FREEZING_POINT {
+        map.set_tile(x, y, Tile::Ice);
+    } else if matches!(tile, Tile::Ice) && temperature > FREEZING_POINT {
+        map.set_tile(x, y, Tile::Water);
+    }
+
+    let spill = map.get_spill(x, y);
+    if spill.is_some() && temperature >= DRYING_TEMPERATURE && !tag_cache.substances[spill.unwrap()].contains(Tag::Flammable) {
+        map.set_spill(x, y, None);
+    }
+}
+
+fn update_item(item: &mut Item, surroundings: Temperature, wet: bool, tag_cache: &TagCache) {
+    item.temperature = approach(item.temperature, surroundings, ENTITY_CONDUCTION);
+
+    if wet && item::has_tag(item, Tag::Wettable, tag_cache) {
+        item.state.insert(Tag::Wet);
+    } else if item.temperature >= DRYING_TEMPERATURE {
+        item.state.remove(Tag::Wet);
+    }
+
+    for content in item.contents.iter_mut() {
+        update_item(content, item.temperature, false, tag_cache);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemType;
+
+    fn make_test_game() -> Game {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 5, 1));
+        for x in 0..5 {
+            game.current_map.set_tile(x, 0, Tile::Floor);
+        }
+        return game;
+    }
+
+    #[test]
+    fn test_heat_spreads_from_magma() {
+        let mut game = make_test_game();
+        game.current_map.set_tile(0, 0, Tile::Magma);
+        for _ in 0..20 {
+            update_temperatures(&mut game);
+        }
+
+        let map = &game.current_map;
+        assert_eq!(map.get_temperature(0, 0), 1000);
+        assert!(map.get_temperature(1, 0) > map.get_temperature(2, 0));
+        assert!(map.get_temperature(2, 0) > map.get_temperature(4, 0));
+    }
+
+    #[test]
+    fn test_water_freezes_and_melts() {
+        let mut game = make_test_game();
+        game.current_map.set_tile(2, 0, Tile::Water);
+        game.current_map.ambient_temperature = -10;
+        for _ in 0..20 {
+            update_temperatures(&mut game);
+        }
+        assert!(matches!(game.current_map.get_tile(2, 0), Tile::Ice));
+
+        game.current_map.ambient_temperature = AMBIENT_TEMPERATURE;
+        for _ in 0..20 {
+            update_temperatures(&mut game);
+        }
+        assert!(matches!(game.current_map.get_tile(2, 0), Tile::Water));
+    }
+
+    #[test]
+    fn test_wet_items_dry_near_heat() {
+        let mut game = make_test_game();
+        game.current_map.set_spill(1, 0, Some(Substance::Water));
+        game.current_map.get_items_mut(1, 0).push(Item::new(ItemType::Book));
+        update_temperatures(&mut game);
+        assert!(game.current_map.get_items(1, 0)[0].state.contains(Tag::Wet));
+
+        game.current_map.set_tile(0, 0, Tile::Forge);
+        for _ in 0..20 {
+            update_temperatures(&mut game);
+        }
+        let book = &game.current_map.get_items(1, 0)[0];
+        assert!(!book.state.contains(Tag::Wet));
+        assert!(item::has_tag(book, Tag::Hot, &game.data_tables.tag_cache));
+    }
+
+    #[test]
+    fn test_frozen_liquid_will_not_pour() {
+        use crate::{action::{ActionRoutine, Empty, ItemSelection, Noun}, substance::Liquid};
+        let mut game = make_test_game();
+        let player = game.special_entities.player;
+        let items = &mut game.components.get_inventory_mut(player).unwrap().items;
+        items.clear();
+        let mut waterskin = Item::new(ItemType::Waterskin);
+        waterskin.liquid = Some(Liquid::new(Substance::Water, 2));
+        waterskin.temperature = -10;
+        items.push(waterskin);
+        items.push(Item::new(ItemType::Bottle));
+
+        let source = Noun::Item(ItemSelection { index: 0, quantity: 1 });
+        let target = Noun::Item(ItemSelection { index: 1, quantity: 1 });
+        assert!(Empty::execute(&mut game, player, source, target));
+        let items = &game.components.get_inventory(player).unwrap().items;
+        assert!(items[0].liquid.is_some());
+        assert!(items[1].liquid.is_none());
+    }
+}
@@ -1,8 +1,10 @@
+use enum_map::Enum;
 
-const DAYS_IN_A_MONTH: u32 = 30;
-const MONTHS_IN_A_YEAR: u32 = 12;
-const DAYS_IN_A_YEAR: u32 = DAYS_IN_A_MONTH * MONTHS_IN_A_YEAR;
+pub const DAYS_IN_A_MONTH: u32 = 30;
+pub const MONTHS_IN_A_YEAR: u32 = 12;
+pub const DAYS_IN_A_YEAR: u32 = DAYS_IN_A_MONTH * MONTHS_IN_A_YEAR;
 
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
 pub enum Month {
     January,
     February,
@@ -17,3 +19,165 @@ pub enum Month {
     November,
     December
 }
+
+pub type Seconds = u64;
+
+pub const SECONDS_PER_MINUTE: Seconds = 60;
+pub const SECONDS_PER_HOUR: Seconds = 60 * SECONDS_PER_MINUTE;
+pub const SECONDS_PER_DAY: Seconds = 24 * SECONDS_PER_HOUR;
+pub const SECONDS_PER_YEAR: Seconds = DAYS_IN_A_YEAR as Seconds * SECONDS_PER_DAY;
+
+// One round of combat, which is also how long most actions take
+pub const SECONDS_PER_TURN: Seconds = 6;
+
+// Adventures begin on the morning of the first of March, in the first year
+pub const START_TIME: Seconds = 2 * DAYS_IN_A_MONTH as Seconds * SECONDS_PER_DAY + 8 * SECONDS_PER_HOUR;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeOfDay {
+    Night,
+    Dawn,
+    Morning,
+    Afternoon,
+    Dusk,
+    Evening,
+}
+
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Date {
+    pub year: u32,
+    pub month: Month,
+    pub day: u32,
+}
+
+// Counts the seconds since midnight at the start of the first year
+pub struct Clock {
+    pub time: Seconds,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            time: START_TIME
+        }
+    }
+
+    pub fn advance(&mut self, seconds: Seconds) {
+        self.time += seconds;
+    }
+
+    pub fn get_date(&self) -> Date {
+        let days = (self.time / SECONDS_PER_DAY) as u32;
+        let day_of_year = ((self.time % SECONDS_PER_YEAR) / SECONDS_PER_DAY) as u32;
+        return Date {
+            year: (self.time / SECONDS_PER_YEAR) as u32 + 1,
+            month: Month::from_usize((day_of_year / DAYS_IN_A_MONTH) as usize),
+            day: days % DAYS_IN_A_MONTH + 1,
+        };
+    }
+
+    pub fn get_hour(&self) -> u32 {
+        return ((self.time % SECONDS_PER_DAY) / SECONDS_PER_HOUR) as u32;
+    }
+
+    pub fn get_minute(&self) -> u32 {
+        return ((self.time % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE) as u32;
+    }
+
+    pub fn get_time_of_day(&self) -> TimeOfDay {
+        return match self.get_hour() {
+            0..=4 => TimeOfDay::Night,
+            5..=6 => TimeOfDay::Dawn,
+            7..=11 => TimeOfDay::Morning,
+            12..=16 => TimeOfDay::Afternoon,
+            17..=18 => TimeOfDay::Dusk,
+            19..=21 => TimeOfDay::Evening,
+            _ => TimeOfDay::Night,
+        };
+    }
+}
+
+pub const fn get_month_name(month: Month) -> &'static str {
+    match month {
+        Month::January => "January",
+        Month::February => "February",
+        Month::March => "March",
+        Month::April => "April",
+        Month::May => "May",
+        Month::June => "June",
+        Month::July => "July",
+        Month::August => "August",
+        Month::September => "September",
+        Month::October => "October",
+        Month::November => "November",
+        Month::December => "December",
+    }
+}
+
+pub const fn get_season(month: Month) -> Season {
+    match month {
+        Month::March | Month::April | Month::May => Season::Spring,
+        Month::June | Month::July | Month::August => Season::Summer,
+        Month::September | Month::October | Month::November => Season::Autumn,
+        Month::December | Month::January | Month::February => Season::Winter,
+    }
+}
+
+pub const fn get_season_name(season: Season) -> &'static str {
+    match season {
+        Season::Spring => "Spring",
+        Season::Summer => "Summer",
+        Season::Autumn => "Autumn",
+        Season::Winter => "Winter",
+    }
+}
+
+pub const fn get_time_of_day_name(time_of_day: TimeOfDay) -> &'static str {
+    match time_of_day {
+        TimeOfDay::Night => "Night",
+        TimeOfDay::Dawn => "Dawn",
+        TimeOfDay::Morning => "Morning",
+        TimeOfDay::Afternoon => "Afternoon",
+        TimeOfDay::Dusk => "Dusk",
+        TimeOfDay::Evening => "Evening",
+    }
+}
+
+pub fn format_date(date: &Date) -> String {
+    return format!("{} {}, Year {}", date.day, get_month_name(date.month), date.year);
+}
+
+pub fn format_clock_time(clock: &Clock) -> String {
+    return format!("{:02}:{:02}", clock.get_hour(), clock.get_minute());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        let mut clock = Clock::new();
+        assert_eq!(format_date(&clock.get_date()), "1 March, Year 1");
+        assert_eq!(format_clock_time(&clock), "08:00");
+        assert_eq!(clock.get_time_of_day(), TimeOfDay::Morning);
+
+        clock.advance(30 * SECONDS_PER_DAY + 11 * SECONDS_PER_HOUR + 5 * SECONDS_PER_MINUTE);
+        assert_eq!(format_date(&clock.get_date()), "1 April, Year 1");
+        assert_eq!(format_clock_time(&clock), "19:05");
+        assert_eq!(clock.get_time_of_day(), TimeOfDay::Evening);
+
+        clock.advance(SECONDS_PER_YEAR);
+        assert_eq!(clock.get_date().year, 2);
+        assert_eq!(clock.get_date().month, Month::April);
+        assert_eq!(get_season(clock.get_date().month), Season::Spring);
+    }
+}
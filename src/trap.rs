@@ -0,0 +1,322 @@
+use enum_map::Enum;
+use strum_macros::EnumString;
+
+use crate::{action::{self, report}, entity::EntityID, game::Game, item::{self, Item, ItemType}, map::{GameMap, Tile}, pathfinding, rng::RngStream, scheduler, tabletop::{DamageType, Dice, Skill}, tag::Tag};
+
+// How close a trap has to be for the player to notice it in passing
+const NOTICE_RANGE: u32 = 2;
+// How far an alarm carries
+const ALARM_RANGE: u32 = 20;
+// Failing to disarm a trap by this much sets it off
+const BOTCHED_BY: i32 = 5;
+
+// What a trap does to whoever sets it off
+#[derive(Clone, Copy, Debug, Enum, EnumString, PartialEq, Eq)]
+pub enum TrapKind {
+    // Rings a bell that brings everything nearby running
+    Alarm,
+    Dart,
+    Fire,
+    Net,
+    Pit,
+}
+
+pub fn get_trap_name(kind: TrapKind) -> &'static str {
+    return match kind {
+        TrapKind::Alarm => "alarm trap",
+        TrapKind::Dart => "dart trap",
+        TrapKind::Fire => "fire trap",
+        TrapKind::Net => "net trap",
+        TrapKind::Pit => "pit trap",
+    };
+}
+
+// What it takes to spot a trap, either in passing or by searching
+pub fn get_detection_difficulty(kind: TrapKind) -> i32 {
+    return match kind {
+        TrapKind::Alarm => 10,
+        TrapKind::Dart => 15,
+        TrapKind::Fire => 15,
+        TrapKind::Net => 12,
+        TrapKind::Pit => 13,
+    };
+}
+
+pub fn get_disarm_difficulty(kind: TrapKind) -> i32 {
+    return match kind {
+        TrapKind::Alarm => 10,
+        TrapKind::Dart => 15,
+        TrapKind::Fire => 15,
+        TrapKind::Net => 10,
+        TrapKind::Pit => 13,
+    };
+}
+
+// A trap that's been found shows on the map. Traps are only ever laid in
+// bare floor, which is what's left once one is disarmed.
+pub fn is_known_trap(map: &GameMap, x: u16, y: u16) -> bool {
+    return map.get_trap(x, y).is_some() && *map.get_tile(x, y) == Tile::TrapKnown;
+}
+
+pub fn reveal_trap(map: &mut GameMap, x: u16, y: u16) {
+    if map.get_trap(x, y).is_some() {
+        map.set_tile(x, y, Tile::TrapKnown);
+    }
+}
+
+// Hidden traps within range of a tile, nearest first
+fn find_hidden_traps(map: &GameMap, x: u16, y: u16, range: u32) -> Vec<(u16, u16)> {
+    let mut result: Vec<(u16, u16)> = (0..map.traps.len())
+        .filter(|index| map.traps[*index].is_some() && map.tiles[*index] != Tile::TrapKnown)
+        .map(|index| map.index_to_coordinates(index))
+        .filter(|(trap_x, trap_y)| pathfinding::get_distance(x, y, *trap_x, *trap_y) <= range)
+        .collect();
+    result.sort_by_key(|(trap_x, trap_y)| pathfinding::get_distance(x, y, *trap_x, *trap_y));
+    return result;
+}
+
+// Anyone keeping an eye out spots traps nearby that their passive Perception
+// is good enough for
+pub fn notice_traps(game: &mut Game, entity: EntityID) {
+    let maybe_position = game.components.get_position(entity);
+    if maybe_position.is_none() {
+        return;
+    }
+    let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+    let perception = action::get_passive_skill(game, entity, Skill::Perception) as i32;
+    for (trap_x, trap_y) in find_hidden_traps(&game.current_map, x, y, NOTICE_RANGE) {
+        let kind = game.current_map.get_trap(trap_x, trap_y).unwrap();
+        if game.current_map.visible[game.current_map.coordinates_to_index(trap_x, trap_y)] && perception >= get_detection_difficulty(kind) {
+            reveal_trap(&mut game.current_map, trap_x, trap_y);
+            report(game, entity, format!("You notice a {}.", get_trap_name(kind)));
+        }
+    }
+}
+
+// Looks over the tiles underfoot and next to the searcher, rolling
+// Investigation for each hidden trap. Returns how many were found.
+pub fn search(game: &mut Game, entity: EntityID) -> usize {
+    let maybe_position = game.components.get_position(entity);
+    if maybe_position.is_none() {
+        return 0;
+    }
+    let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+    let mut result = 0;
+    for (trap_x, trap_y) in find_hidden_traps(&game.current_map, x, y, 1) {
+        let kind = game.current_map.get_trap(trap_x, trap_y).unwrap();
+        if action::roll_skill_check(game, entity, Skill::Investigation) >= get_detection_difficulty(kind) {
+            reveal_trap(&mut game.current_map, trap_x, trap_y);
+            report(game, entity, format!("You find a {}.", get_trap_name(kind)));
+            result += 1;
+        }
+    }
+    return result;
+}
+
+// Everything with a mind of its own comes to see what the noise was
+fn sound_alarm(game: &mut Game, x: u16, y: u16) {
+    let map_id = game.current_map.id;
+    for creature in game.components.get_living_creatures() {
+        let on_map = game.components.get_map_index(creature).is_some_and(|index| index.map == map_id);
+        let in_range = game.components.get_position(creature).is_some_and(|position| pathfinding::get_distance(x, y, position.x, position.y) <= ALARM_RANGE);
+        if !on_map || !in_range {
+            continue;
+        }
+        if let Some(ai) = game.components.get_ai_mut(creature) {
+            ai.last_known = Some((x, y));
+        }
+    }
+}
+
+// A fall is hard on anything fragile being carried. One of each kind breaks
+// if the roll beats what it's made of.
+fn break_fragile_items(game: &mut Game, entity: EntityID) {
+    let maybe_inventory = game.components.get_inventory(entity);
+    if maybe_inventory.is_none() {
+        return;
+    }
+    let tag_cache = &game.data_tables.tag_cache;
+    let items = &maybe_inventory.unwrap().items;
+    let fragile: Vec<usize> = (0..items.len()).filter(|index| item::has_tag(&items[*index], Tag::Fragile, tag_cache)).collect();
+    for index in fragile.into_iter().rev() {
+        let roll = game.rng.range(RngStream::Combat, 1, 20);
+        let items = &mut game.components.get_inventory_mut(entity).unwrap().items;
+        if roll <= item::get_armor_class(&items[index]) as i32 {
+            continue;
+        }
+        let broken = items[index].split(1);
+        if items[index].quantity == 0 {
+            items.remove(index);
+        }
+        report(game, entity, format!("Your {} breaks!", item::get_display_name(&broken)));
+    }
+}
+
+// Sets off the trap on a tile, which is plain to see afterwards
+pub fn spring_trap(game: &mut Game, entity: EntityID, x: u16, y: u16) {
+    let maybe_kind = game.current_map.get_trap(x, y);
+    if maybe_kind.is_none() {
+        return;
+    }
+    reveal_trap(&mut game.current_map, x, y);
+    let round = scheduler::get_round_length(game, entity);
+    match maybe_kind.unwrap() {
+        TrapKind::Alarm => {
+            report(game, entity, String::from("A bell clangs loudly!"));
+            sound_alarm(game, x, y);
+        },
+        TrapKind::Dart => {
+            report(game, entity, String::from("A dart shoots out of the wall!"));
+            let damage = game.rng.roll(RngStream::Combat, Dice::new(1, 4, 0));
+            action::apply_damage(game, entity, damage, DamageType::Piercing);
+        },
+        TrapKind::Fire => {
+            report(game, entity, String::from("Flames burst up from the floor!"));
+            let damage = game.rng.roll(RngStream::Combat, Dice::new(2, 6, 0));
+            action::apply_damage(game, entity, damage, DamageType::Fire);
+        },
+        TrapKind::Net => {
+            // Getting free of the net takes a while, and it's left behind
+            report(game, entity, String::from("A net drops over you!"));
+            item::add_to_pile(game.current_map.get_items_mut(x, y), Item::new(ItemType::Net), &game.data_tables.item_tag_map);
+            scheduler::spend_time(game, entity, 2 * round);
+        },
+        TrapKind::Pit => {
+            report(game, entity, String::from("The floor gives way beneath you!"));
+            let damage = game.rng.roll(RngStream::Combat, Dice::new(1, 6, 0));
+            action::apply_damage(game, entity, damage, DamageType::Bludgeoning);
+            break_fragile_items(game, entity);
+            scheduler::spend_time(game, entity, round);
+        },
+    }
+}
+
+// Springs any trap on the tile someone has just stepped onto, and gives the
+// player a chance to notice what's around them
+pub fn enter_tile(game: &mut Game, entity: EntityID, x: u16, y: u16) {
+    if game.current_map.get_trap(x, y).is_some() {
+        spring_trap(game, entity, x, y);
+    }
+    if entity == game.special_entities.player {
+        notice_traps(game, entity);
+    }
+}
+
+// Tries to take apart a known trap with thieves' tools, which can set it off
+// if it goes badly enough. Returns whether the trap is still there.
+pub fn disarm(game: &mut Game, entity: EntityID, x: u16, y: u16) -> bool {
+    if !is_known_trap(&game.current_map, x, y) {
+        report(game, entity, String::from("There's no trap there that you know of."));
+        return true;
+    }
+    let has_tools = game.components.get_inventory(entity).is_some_and(|inventory| inventory.items.iter().any(|item| item.item_type == ItemType::ThievesTools));
+    if !has_tools {
+        report(game, entity, String::from("You need thieves' tools to disarm it."));
+        return true;
+    }
+
+    let kind = game.current_map.get_trap(x, y).unwrap();
+    let name = get_trap_name(kind);
+    let margin = action::roll_skill_check(game, entity, Skill::SleightOfHand) - get_disarm_difficulty(kind);
+    if margin >= 0 {
+        game.current_map.set_trap(x, y, None);
+        game.current_map.set_tile(x, y, Tile::Floor);
+        report(game, entity, format!("You disarm the {}.", name));
+        return false;
+    }
+    if margin <= -BOTCHED_BY {
+        report(game, entity, format!("You set off the {}!", name));
+        spring_trap(game, entity, x, y);
+        return true;
+    }
+    report(game, entity, format!("You fail to disarm the {}.", name));
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::{Action, ActionRequest, Go, Noun}, component::Position, new_action};
+
+    #[test]
+    fn test_traps_spring_and_get_found() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 5, 3));
+        for y in 0..3 {
+            for x in 0..5 {
+                game.current_map.set_tile(x, y, Tile::Floor);
+                let index = game.current_map.coordinates_to_index(x, y);
+                game.current_map.visible[index] = true;
+            }
+        }
+        let player = game.special_entities.player;
+        *game.components.get_position_mut(player).unwrap() = Position { x: 0, y: 1 };
+        game.components.get_health_mut(player).unwrap().current = 100;
+        game.current_map.set_trap(1, 1, Some(TrapKind::Pit));
+        game.current_map.set_trap(4, 1, Some(TrapKind::Alarm));
+        assert!(!is_known_trap(&game.current_map, 1, 1));
+
+        // Walking onto a hidden pit hurts and shows where it is
+        let east = game.special_entities.east;
+        action::execute_action(&mut game, ActionRequest { actor: player, action: new_action!(Go), noun: Noun::Entity(east), second: Noun::Nothing });
+        assert!(game.components.get_health(player).unwrap().current < 100);
+        assert!(is_known_trap(&game.current_map, 1, 1));
+        assert_eq!(*game.current_map.get_tile(1, 1), Tile::TrapKnown);
+
+        // A sharp-eyed player spots an easy trap a couple of tiles away
+        game.components.get_creature_mut(player).unwrap().stats.wisdom = 20;
+        *game.components.get_position_mut(player).unwrap() = Position { x: 2, y: 1 };
+        notice_traps(&mut game, player);
+        assert!(is_known_trap(&game.current_map, 4, 1));
+
+        // Disarming needs tools, and a deft enough hand always manages it
+        assert!(disarm(&mut game, player, 4, 1));
+        game.components.get_inventory_mut(player).unwrap().items.push(Item::new(ItemType::ThievesTools));
+        game.components.get_creature_mut(player).unwrap().stats.dexterity = 30;
+        assert!(!disarm(&mut game, player, 4, 1));
+        assert!(game.current_map.get_trap(4, 1).is_none());
+        assert_eq!(*game.current_map.get_tile(4, 1), Tile::Floor);
+    }
+
+    #[test]
+    fn test_searching_finds_traps_next_to_you() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 5, 5));
+        crate::gen::map_gen::fill(&mut game.current_map, Tile::Floor);
+        let player = game.special_entities.player;
+        *game.components.get_position_mut(player).unwrap() = Position { x: 1, y: 1 };
+        game.current_map.set_trap(2, 2, Some(TrapKind::Alarm));
+        game.current_map.set_trap(4, 4, Some(TrapKind::Alarm));
+
+        // Sharp enough wits can't miss an alarm, but only right next to it
+        game.components.get_creature_mut(player).unwrap().stats.intelligence = 30;
+        assert_eq!(search(&mut game, player), 1);
+        assert!(is_known_trap(&game.current_map, 2, 2));
+        assert!(!is_known_trap(&game.current_map, 4, 4));
+        assert_eq!(search(&mut game, player), 0);
+    }
+
+    #[test]
+    fn test_falls_break_glass() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 1, 1));
+        crate::gen::map_gen::fill(&mut game.current_map, Tile::Floor);
+        let player = game.special_entities.player;
+        *game.components.get_position_mut(player).unwrap() = Position { x: 0, y: 0 };
+        game.components.get_health_mut(player).unwrap().current = 1000;
+        let items = &mut game.components.get_inventory_mut(player).unwrap().items;
+        items.clear();
+        items.push(Item::with_quantity(ItemType::Vial, 20));
+        items.push(Item::new(ItemType::Dagger));
+
+        // Glass doesn't stand up to many falls, but steel shrugs them off
+        for _ in 0..20 {
+            game.current_map.set_trap(0, 0, Some(TrapKind::Pit));
+            spring_trap(&mut game, player, 0, 0);
+        }
+        let items = &game.components.get_inventory(player).unwrap().items;
+        let vials = items.iter().find(|item| item.item_type == ItemType::Vial).map_or(0, |item| item.quantity);
+        assert!(vials < 20);
+        assert!(items.iter().any(|item| item.item_type == ItemType::Dagger));
+    }
+}
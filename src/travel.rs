@@ -0,0 +1,141 @@
+use crate::{action::{self, Action, ActionRequest, Go, Noun}, dijkstra::{self, DijkstraMap}, entity::EntityID, game::Game, monster, new_action};
+
+// Ways the player can get around without being told every step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoMove {
+    // Heads for the nearest place the player hasn't seen yet
+    Explore,
+    // Heads for the tile the player picked
+    Travel,
+}
+
+// A monster in view is worth stopping for
+fn get_visible_monster(game: &Game) -> Option<EntityID> {
+    for creature in game.components.get_living_creatures() {
+        if monster::get_monster_kind(game, creature).is_none() {
+            continue;
+        }
+        let map_index = game.components.get_map_index(creature);
+        let position = game.components.get_position(creature);
+        if map_index.is_none() || map_index.unwrap().map != game.current_map.id || position.is_none() {
+            continue;
+        }
+        let position = position.unwrap();
+        if game.current_map.visible[game.current_map.coordinates_to_index(position.x, position.y)] {
+            return Some(creature);
+        }
+    }
+    return None;
+}
+
+fn stop(game: &mut Game, message: String) -> Option<ActionRequest> {
+    game.auto_move = None;
+    game.add_message(message);
+    return None;
+}
+
+// Starts heading for a tile, returning why not if it can't be reached
+pub fn start_travel(game: &mut Game, x: u16, y: u16) -> Result<(), String> {
+    let map = &game.current_map;
+    if !map.is_in_bounds(x, y) || !map.explored[map.coordinates_to_index(x, y)] {
+        return Err(String::from("You don't know the way there."));
+    }
+    let travel = DijkstraMap::new(map, &[(x, y, 0)]);
+    let player = game.components.get_position(game.special_entities.player);
+    if player.is_none() || travel.get_distance(map, player.unwrap().x, player.unwrap().y).is_none() {
+        return Err(String::from("You can't find a way there."));
+    }
+    game.distance_maps.travel = Some(travel);
+    game.auto_move = Some(AutoMove::Travel);
+    return Ok(());
+}
+
+// The player's next step while exploring or travelling, stopping once
+// there's nowhere left to go or something comes into view
+pub fn get_next_step(game: &mut Game) -> Option<ActionRequest> {
+    let auto_move = game.auto_move?;
+    let player = game.special_entities.player;
+
+    if let Some(visible) = get_visible_monster(game) {
+        let name = monster::get_creature_name(game, visible);
+        return stop(game, action::capitalize(format!("{} is in view.", name)));
+    }
+
+    dijkstra::update_distance_maps(game);
+    let maybe_position = game.components.get_position(player);
+    if maybe_position.is_none() {
+        game.auto_move = None;
+        return None;
+    }
+    let (x, y) = (maybe_position.unwrap().x, maybe_position.unwrap().y);
+    let map = &game.current_map;
+    let distances = match auto_move {
+        AutoMove::Explore => &game.distance_maps.to_unexplored,
+        AutoMove::Travel => game.distance_maps.travel.as_ref().unwrap(),
+    };
+
+    let maybe_distance = distances.get_distance(map, x, y);
+    if maybe_distance.is_none() || maybe_distance == Some(0) {
+        let message = match auto_move {
+            AutoMove::Explore => "There's nowhere left to explore.",
+            AutoMove::Travel => if maybe_distance.is_none() { "You can't find a way there." } else { "You arrive." },
+        };
+        return stop(game, String::from(message));
+    }
+
+    let maybe_step = distances.get_downhill_step(map, x, y, |next_x, next_y| game.components.get_creature_at(map.id, next_x, next_y).is_some());
+    if maybe_step.is_none() {
+        return stop(game, String::from("There's something in the way."));
+    }
+    let (next_x, next_y) = maybe_step.unwrap();
+    let direction = action::get_direction(game, next_x as i16 - x as i16, next_y as i16 - y as i16);
+    return Some(ActionRequest {
+        actor: player,
+        action: new_action!(Go),
+        noun: Noun::Entity(direction),
+        second: Noun::Nothing,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ringbuffer::RingBuffer;
+    use crate::{lighting, map::{GameMap, Tile}};
+
+    #[test]
+    fn test_explore_and_travel() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 9, 3));
+        for y in 0..3 {
+            for x in 0..9 {
+                game.current_map.set_tile(x, y, if y == 1 { Tile::Floor } else { Tile::Wall });
+            }
+        }
+        game.current_map.set_tile(4, 1, Tile::DoorClosed);
+        let player = game.special_entities.player;
+        game.components.get_position_mut(player).unwrap().x = 0;
+        game.components.get_position_mut(player).unwrap().y = 1;
+        lighting::update_visibility(&mut game);
+
+        // Exploring opens the door on the way, which shows the rest of the corridor
+        game.auto_move = Some(AutoMove::Explore);
+        for _ in 0..20 {
+            let maybe_step = get_next_step(&mut game);
+            if maybe_step.is_none() {
+                break;
+            }
+            action::execute_action(&mut game, maybe_step.unwrap());
+        }
+        assert!(game.auto_move.is_none());
+        assert!((0..9).all(|x| game.current_map.explored[game.current_map.coordinates_to_index(x, 1)]));
+        assert_eq!(game.components.get_position(player).unwrap().x, 3);
+
+        assert!(start_travel(&mut game, 1, 1).is_ok());
+        while let Some(step) = get_next_step(&mut game) {
+            action::execute_action(&mut game, step);
+        }
+        assert_eq!(game.components.get_position(player).unwrap().x, 1);
+        assert_eq!(game.messages.back().unwrap(), "You arrive.");
+    }
+}
@@ -1,6 +1,6 @@
 use strum::IntoEnumIterator;
 
-use crate::{constants, tabletop::{self, Alignment, Class, Race, Stats}};
+use crate::{action::ItemSelection, constants, tabletop::{self, Alignment, Class, Race, Stats}};
 
 use super::menu_focus::{new_character, test_window, FocusIndex};
 
@@ -56,25 +56,107 @@ pub enum MenuItem {
     TextField(TextField),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventoryAction {
+    Burn,
+    Drink,
+    Drop,
+    Eat,
+    Empty,
+    Fill,
+    Give,
+    Insert,
+    Open,
+    PutOn,
+    Smell,
+    SwitchOff,
+    Taste,
+    View,
+}
+
+pub struct InventoryMenu {
+    // The first item picked, for actions that need a second one
+    pub pending_item: Option<ItemSelection>,
+    pub quantity: TextField,
+    pub selected_item: Option<usize>,
+}
+
+impl InventoryMenu {
+    pub fn new() -> Self {
+        Self {
+            pending_item: None,
+            quantity: TextField::new(String::from("How many?"), QUANTITY_MAX_LENGTH),
+            selected_item: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pending_item = None;
+        self.quantity.editing = false;
+        self.quantity.value.clear();
+        self.selected_item = None;
+    }
+}
+
+const QUANTITY_MAX_LENGTH: u16 = 6;
+
+// The tile picked to travel to
+pub struct TravelMenu {
+    pub x: u16,
+    pub y: u16,
+}
+
+impl TravelMenu {
+    pub fn new() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+pub fn inventory_index_to_letter(index: usize) -> Option<char> {
+    return match index {
+        0..=25 => Some((b'a' + index as u8) as char),
+        26..=51 => Some((b'A' + (index - 26) as u8) as char),
+        _ => None,
+    };
+}
+
+pub fn inventory_letter_to_index(letter: char) -> Option<usize> {
+    return match letter {
+        'a'..='z' => Some(letter as usize - 'a' as usize),
+        'A'..='Z' => Some(letter as usize - 'A' as usize + 26),
+        _ => None,
+    };
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MenuType {
+    Bestiary,
     Character,
+    Inventory(InventoryAction),
     Main,
     NewCharacter,
     Pause,
     TestMenu,
+    Travel,
 }
 
 pub struct MenuData {
+    pub inventory: InventoryMenu,
     pub new_character: NewCharacter,
     pub test_menu: TestMenu,
+    pub travel: TravelMenu,
 }
 
 impl MenuData {
     pub fn new() -> Self {
         Self {
+            inventory: InventoryMenu::new(),
             new_character: NewCharacter::new(),
             test_menu: TestMenu::new(),
+            travel: TravelMenu::new(),
         }
     }
 }
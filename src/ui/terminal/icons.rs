@@ -1,6 +1,6 @@
 use crossterm::style::Color;
 
-use crate::{item::{Item, ItemType}, map::Tile, tabletop::Race};
+use crate::{item::{Item, ItemType}, map::Tile, monster::MonsterKind, npc::NpcRole, substance::Substance, tabletop::Race};
 
 pub fn creature_color(race: &Race) -> Color {
     match race {
@@ -30,6 +30,43 @@ pub fn creature_icon(race: &Race) -> char {
     }
 }
 
+pub fn npc_color(role: NpcRole) -> Color {
+    match role {
+        NpcRole::Merchant => Color::Yellow,
+        NpcRole::Townsfolk => Color::Grey,
+    }
+}
+
+pub fn monster_color(kind: MonsterKind) -> Color {
+    match kind {
+        MonsterKind::Bat => Color::DarkGrey,
+        MonsterKind::GiantRat => Color::DarkYellow,
+        MonsterKind::GiantSpider => Color::DarkMagenta,
+        MonsterKind::Goblin => Color::Green,
+        MonsterKind::Kobold => Color::DarkRed,
+        MonsterKind::Ogre => Color::DarkYellow,
+        MonsterKind::Orc => Color::DarkGreen,
+        MonsterKind::Skeleton => Color::White,
+        MonsterKind::Wolf => Color::Grey,
+        MonsterKind::Zombie => Color::DarkCyan,
+    }
+}
+
+pub fn monster_icon(kind: MonsterKind) -> char {
+    match kind {
+        MonsterKind::Bat => 'b',
+        MonsterKind::GiantRat => 'r',
+        MonsterKind::GiantSpider => 's',
+        MonsterKind::Goblin => 'g',
+        MonsterKind::Kobold => 'k',
+        MonsterKind::Ogre => 'O',
+        MonsterKind::Orc => 'o',
+        MonsterKind::Skeleton => 'S',
+        MonsterKind::Wolf => 'C',
+        MonsterKind::Zombie => 'Z',
+    }
+}
+
 pub fn item_icon(item: &Item) -> char {
     match item.item_type {
         ItemType::Abacus => ']',
@@ -96,6 +133,7 @@ pub fn item_icon(item: &Item) -> char {
         ItemType::HandCrossbow => '}',
         ItemType::HealersKit => ']',
         ItemType::HeavyCrossbow => '}',
+        ItemType::Herbs => '"',
         ItemType::HideArmor => '[',
         ItemType::Horn => '{',
         ItemType::Hourglass => ']',
@@ -171,6 +209,7 @@ pub fn item_icon(item: &Item) -> char {
         ItemType::Staff => '(',
         ItemType::StuddedLeatherArmor => '[',
         ItemType::Tent => ']',
+        ItemType::ThievesTools => ']',
         ItemType::Tinderbox => ']',
         ItemType::Torch => ']',
         ItemType::Totem => ']',
@@ -188,6 +227,30 @@ pub fn item_icon(item: &Item) -> char {
     }
 }
 
+pub const FIRE_ICON: char = '^';
+pub const SMOKE_COLOR: Color = Color::Grey;
+// Tiles seen before but out of sight now
+pub const REMEMBERED_COLOR: Color = Color::DarkBlue;
+
+// Fires flicker from yellow to red as they die down
+pub fn fire_color(fuel: u16) -> Color {
+    if fuel > 3 {
+        return Color::Yellow;
+    }
+    return Color::Red;
+}
+
+pub fn spill_color(substance: Substance) -> Color {
+    match substance {
+        Substance::Oil => Color::DarkYellow,
+        Substance::Poison => Color::DarkGreen,
+        Substance::PotionOfGreaterHealing => Color::Magenta,
+        Substance::PotionOfHealing => Color::Magenta,
+        Substance::Water => Color::Blue,
+        Substance::Wine => Color::DarkRed,
+    }
+}
+
 pub fn tile_color(tile: &Tile) -> Color {
     match tile {
         Tile::Air => Color::DarkGrey,
@@ -195,33 +258,34 @@ pub fn tile_color(tile: &Tile) -> Color {
         Tile::Building => Color::DarkGrey,
         Tile::DoorClosed => Color::DarkGrey,
         Tile::DoorOpen => Color::DarkGrey,
-        Tile::Entrance => Color::DarkGrey,
+        Tile::Entrance => Color::White,
         Tile::Floor => Color::DarkGrey,
-        Tile::Forest => Color::DarkGrey,
+        Tile::Forest => Color::DarkGreen,
         Tile::Forge => Color::DarkGrey,
         Tile::Gate => Color::DarkGrey,
         Tile::Graveyard => Color::DarkGrey,
-        Tile::Herbs => Color::DarkGrey,
-        Tile::Hills => Color::DarkGrey,
+        Tile::Herbs => Color::Green,
+        Tile::Hills => Color::DarkYellow,
         Tile::Hive => Color::DarkGrey,
         Tile::Hole => Color::DarkGrey,
+        Tile::Ice => Color::Cyan,
         Tile::Lever => Color::DarkGrey,
         Tile::Magma => Color::DarkGrey,
-        Tile::Mountain => Color::DarkGrey,
+        Tile::Mountain => Color::Grey,
         Tile::Passage => Color::DarkGrey,
-        Tile::Plains => Color::DarkGrey,
+        Tile::Plains => Color::Green,
         Tile::Pool => Color::DarkGrey,
-        Tile::Road => Color::DarkGrey,
+        Tile::Road => Color::DarkYellow,
         Tile::StairDown => Color::DarkGrey,
         Tile::StairUp => Color::DarkGrey,
         Tile::Statue => Color::DarkGrey,
-        Tile::Swamp => Color::DarkGrey,
+        Tile::Swamp => Color::DarkCyan,
         Tile::Tombstone => Color::DarkGrey,
         Tile::TrapKnown => Color::DarkGrey,
-        Tile::Tree => Color::DarkGrey,
+        Tile::Tree => Color::DarkGreen,
         Tile::Tunnel => Color::DarkGrey,
         Tile::Wall => Color::DarkGrey,
-        Tile::Water => Color::DarkGrey,
+        Tile::Water => Color::Blue,
         Tile::Web => Color::DarkGrey,
     }
 }
@@ -243,6 +307,7 @@ pub fn tile_icon(tile: &Tile) -> char {
         Tile::Hills => '~',
         Tile::Hive => '0',
         Tile::Hole => '*',
+        Tile::Ice => '=',
         Tile::Lever => '!',
         Tile::Magma => '=',
         Tile::Mountain => '^',
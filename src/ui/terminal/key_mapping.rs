@@ -1,6 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent};
 
-use crate::{action::{Action, ActionRequest, CloseMenu, Go, NavigateMenu, NewGame, Noun, OpenMenu, Quit}, entity::EntityID, game::{Game, GameState}, new_action, ui::menu::{MenuItem, MenuType}};
+use crate::{action::{Action, ActionRequest, Burn, CloseMenu, Disarm, Drink, Drop, Eat, Empty, Enter, Exit, Explore, Fill, Forage, Give, Go, Insert, ItemSelection, NavigateMenu, NewGame, Noun, Open, OpenMenu, Pull, PutOn, Quit, Search, Sleep, Smell, SwitchOff, Take, Taste, Travel, Wait}, entity::EntityID, game::{Game, GameState}, new_action, ui::menu::{self, InventoryAction, MenuItem, MenuType}};
 
 pub fn map_input(event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
     return match game.state {
@@ -12,6 +12,15 @@ pub fn map_input(event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
 }
 
 fn map_input_menu(menu: MenuType, event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    if let MenuType::Inventory(inventory_action) = menu {
+        return map_input_inventory(inventory_action, event, game);
+    }
+    if menu == MenuType::Bestiary {
+        return map_input_bestiary(event, game);
+    }
+    if menu == MenuType::Travel {
+        return map_input_travel(event, game);
+    }
     
     let direction = match event.code {
         KeyCode::Up => Some(game.special_entities.north),
@@ -34,14 +43,326 @@ fn map_input_menu(menu: MenuType, event: KeyEvent, game: &mut Game) -> Option<Ac
     }
 
     return match menu {
+        MenuType::Bestiary => None,
         MenuType::Character => None,
+        MenuType::Inventory(_) => None,
         MenuType::Main => map_input_main_menu(event, game),
         MenuType::NewCharacter => map_input_new_character(event, game),
         MenuType::Pause => None,
         MenuType::TestMenu => map_input_test_menu(event, game),
+        MenuType::Travel => None,
+    };
+}
+
+fn find_adjacent_creature(game: &Game) -> Option<EntityID> {
+    let player = game.special_entities.player;
+    let position = game.components.get_position(player)?;
+    let map = &game.current_map;
+
+    for offset_y in -1..=1 {
+        for offset_x in -1..=1 {
+            if offset_x == 0 && offset_y == 0 {
+                continue;
+            }
+            let x: i32 = position.x as i32 + offset_x;
+            let y: i32 = position.y as i32 + offset_y;
+            if x < 0 || y < 0 || x >= map.width as i32 || y >= map.height as i32 {
+                continue;
+            }
+
+            let creature = game.components.get_creature_at(map.id, x as u16, y as u16);
+            if creature.is_some() {
+                return creature;
+            }
+        }
+    }
+
+    return None;
+}
+
+fn asks_for_quantity(inventory_action: InventoryAction) -> bool {
+    return match inventory_action {
+        InventoryAction::Burn => false,
+        InventoryAction::Drink => false,
+        InventoryAction::Drop => true,
+        InventoryAction::Eat => false,
+        InventoryAction::Empty => false,
+        InventoryAction::Fill => false,
+        InventoryAction::Give => true,
+        InventoryAction::Insert => true,
+        InventoryAction::Open => false,
+        InventoryAction::PutOn => true,
+        InventoryAction::Smell => false,
+        InventoryAction::SwitchOff => false,
+        InventoryAction::Taste => false,
+        InventoryAction::View => false,
     };
 }
 
+// Actions that can be used on what's underfoot or nearby instead of a held item
+fn works_without_item(inventory_action: InventoryAction) -> bool {
+    return matches!(inventory_action, InventoryAction::Burn | InventoryAction::Drink | InventoryAction::Smell | InventoryAction::Taste);
+}
+
+fn finish_inventory_selection(inventory_action: InventoryAction, selection: Noun, game: &mut Game) -> Option<ActionRequest> {
+    let player = game.special_entities.player;
+
+    let request = match inventory_action {
+        InventoryAction::Burn => ActionRequest {
+            actor: player,
+            action: new_action!(Burn),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Drink => ActionRequest {
+            actor: player,
+            action: new_action!(Drink),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Drop => ActionRequest {
+            actor: player,
+            action: new_action!(Drop),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Eat => ActionRequest {
+            actor: player,
+            action: new_action!(Eat),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Empty => ActionRequest {
+            actor: player,
+            action: new_action!(Empty),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Fill => ActionRequest {
+            actor: player,
+            action: new_action!(Fill),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Give => {
+            let recipient = match find_adjacent_creature(game) {
+                Some(creature) => Noun::Entity(creature),
+                None => Noun::Nothing,
+            };
+            ActionRequest {
+                actor: player,
+                action: new_action!(Give),
+                noun: selection,
+                second: recipient
+            }
+        },
+        InventoryAction::Insert => {
+            // The first pick is the item, the second is the container
+            let pending_item = game.menu_data.inventory.pending_item;
+            if pending_item.is_none() {
+                if let Noun::Item(item_selection) = selection {
+                    game.menu_data.inventory.pending_item = Some(item_selection);
+                }
+                return None;
+            }
+            ActionRequest {
+                actor: player,
+                action: new_action!(Insert),
+                noun: Noun::Item(pending_item.unwrap()),
+                second: selection
+            }
+        },
+        InventoryAction::Open => ActionRequest {
+            actor: player,
+            action: new_action!(Open),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::PutOn => ActionRequest {
+            actor: player,
+            action: new_action!(PutOn),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Smell => ActionRequest {
+            actor: player,
+            action: new_action!(Smell),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::SwitchOff => ActionRequest {
+            actor: player,
+            action: new_action!(SwitchOff),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::Taste => ActionRequest {
+            actor: player,
+            action: new_action!(Taste),
+            noun: selection,
+            second: Noun::Nothing
+        },
+        InventoryAction::View => return None,
+    };
+
+    game.action_queue.push_back(ActionRequest {
+        actor: player,
+        action: new_action!(CloseMenu),
+        noun: Noun::Nothing,
+        second: Noun::Nothing
+    });
+
+    return Some(request);
+}
+
+fn map_input_bestiary(event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    if event.code == KeyCode::Esc {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(CloseMenu),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+    return None;
+}
+
+// Moves the cursor around the map until a tile is picked to travel to
+fn map_input_travel(event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    let player = game.special_entities.player;
+    if event.code == KeyCode::Esc {
+        let request = ActionRequest {
+            actor: player,
+            action: new_action!(CloseMenu),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Enter || event.code == KeyCode::Char('.') {
+        let cursor = &game.menu_data.travel;
+        let index = game.current_map.coordinates_to_index(cursor.x, cursor.y);
+        game.action_queue.push_back(ActionRequest {
+            actor: player,
+            action: new_action!(CloseMenu),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        });
+        let request = ActionRequest {
+            actor: player,
+            action: new_action!(Travel),
+            noun: Noun::Number(index as i64),
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    let offset: Option<(i32, i32)> = match event.code {
+        KeyCode::Up | KeyCode::Char('8') => Some((0, -1)),
+        KeyCode::Right | KeyCode::Char('6') => Some((1, 0)),
+        KeyCode::Down | KeyCode::Char('2') => Some((0, 1)),
+        KeyCode::Left | KeyCode::Char('4') => Some((-1, 0)),
+        KeyCode::Char('7') => Some((-1, -1)),
+        KeyCode::Char('9') => Some((1, -1)),
+        KeyCode::Char('1') => Some((-1, 1)),
+        KeyCode::Char('3') => Some((1, 1)),
+        _ => None,
+    };
+    if let Some((offset_x, offset_y)) = offset {
+        let map = &game.current_map;
+        let cursor = &mut game.menu_data.travel;
+        cursor.x = (cursor.x as i32 + offset_x).clamp(0, map.width as i32 - 1) as u16;
+        cursor.y = (cursor.y as i32 + offset_y).clamp(0, map.height as i32 - 1) as u16;
+    }
+    return None;
+}
+
+fn map_input_inventory(inventory_action: InventoryAction, event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    if game.menu_data.inventory.selected_item.is_some() {
+        return map_input_quantity(inventory_action, event, game);
+    }
+
+    if event.code == KeyCode::Esc {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(CloseMenu),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if inventory_action == InventoryAction::View {
+        return None;
+    }
+
+    if event.code == KeyCode::Char('-') && works_without_item(inventory_action) {
+        return finish_inventory_selection(inventory_action, Noun::Nothing, game);
+    }
+
+    let index: usize = match event.code {
+        KeyCode::Char(character) => menu::inventory_letter_to_index(character)?,
+        _ => return None,
+    };
+
+    let quantity = game.components.get_inventory(game.special_entities.player)?.items.get(index)?.quantity;
+
+    let picking_item = game.menu_data.inventory.pending_item.is_none();
+    if quantity > 1 && picking_item && asks_for_quantity(inventory_action) {
+        let inventory_menu = &mut game.menu_data.inventory;
+        inventory_menu.selected_item = Some(index);
+        inventory_menu.quantity.value.clear();
+        inventory_menu.quantity.editing = true;
+        return None;
+    }
+
+    return finish_inventory_selection(inventory_action, Noun::Item(ItemSelection { index, quantity: 1 }), game);
+}
+
+fn map_input_quantity(inventory_action: InventoryAction, event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    let inventory_menu = &mut game.menu_data.inventory;
+    let index = inventory_menu.selected_item.unwrap();
+
+    match event.code {
+        KeyCode::Esc => inventory_menu.reset(),
+        KeyCode::Backspace => {
+            inventory_menu.quantity.value.pop();
+        },
+        KeyCode::Char(character) => {
+            let text_field = &mut inventory_menu.quantity;
+            if character.is_ascii_digit() && text_field.value.len() < text_field.max_length as usize {
+                text_field.value.push(character);
+            }
+        },
+        KeyCode::Enter => {
+            let maybe_stack = game.components.get_inventory(game.special_entities.player)
+                .and_then(|inventory| inventory.items.get(index));
+            if maybe_stack.is_none() {
+                game.menu_data.inventory.reset();
+                return None;
+            }
+            let available = maybe_stack.unwrap().quantity;
+
+            // An empty prompt means the whole stack
+            let quantity: u32 = match game.menu_data.inventory.quantity.value.parse::<u32>() {
+                Ok(value) => u32::min(value, available),
+                Err(_) => available,
+            };
+            game.menu_data.inventory.reset();
+
+            if quantity == 0 {
+                return None;
+            }
+            return finish_inventory_selection(inventory_action, Noun::Item(ItemSelection { index, quantity }), game);
+        },
+        _ => (),
+    };
+
+    return None;
+}
+
 fn map_input_main_menu(event: KeyEvent, game: &Game) -> Option<ActionRequest> {
     if event.code == KeyCode::Char('p') || event.code == KeyCode::Char('P') {
         let request = ActionRequest {
@@ -101,7 +422,13 @@ fn map_input_paused(_event: KeyEvent, _game: &Game) -> Option<ActionRequest> {
     return None;
 }
 
-fn map_input_ingame(event: KeyEvent, game: &Game) -> Option<ActionRequest> {
+fn map_input_ingame(event: KeyEvent, game: &mut Game) -> Option<ActionRequest> {
+    // Any key stops the player exploring or travelling
+    if game.auto_move.is_some() {
+        game.auto_move = None;
+        return None;
+    }
+
     if event.code == KeyCode::Esc {
         let request = ActionRequest {
             actor: game.special_entities.player,
@@ -133,6 +460,158 @@ fn map_input_ingame(event: KeyEvent, game: &Game) -> Option<ActionRequest> {
         };
         return Some(request);
     }
+
+    if event.code == KeyCode::Char('5') || event.code == KeyCode::Char('.') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Wait),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('Z') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Sleep),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('u') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Pull),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('g') || event.code == KeyCode::Char(',') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Take),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('X') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Explore),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('f') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Forage),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('s') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Search),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('D') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Disarm),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('t') {
+        if let Some(position) = game.components.get_position(game.special_entities.player) {
+            game.menu_data.travel.x = position.x;
+            game.menu_data.travel.y = position.y;
+        }
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(OpenMenu),
+            noun: Noun::Menu(MenuType::Travel),
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('>') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Enter),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('<') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(Exit),
+            noun: Noun::Nothing,
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    if event.code == KeyCode::Char('B') {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(OpenMenu),
+            noun: Noun::Menu(MenuType::Bestiary),
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
+
+    let inventory_action: Option<InventoryAction> = match event.code {
+        KeyCode::Char('b') => Some(InventoryAction::Burn),
+        KeyCode::Char('d') => Some(InventoryAction::Drop),
+        KeyCode::Char('e') => Some(InventoryAction::Empty),
+        KeyCode::Char('E') => Some(InventoryAction::Eat),
+        KeyCode::Char('F') => Some(InventoryAction::Fill),
+        KeyCode::Char('G') => Some(InventoryAction::Give),
+        KeyCode::Char('i') => Some(InventoryAction::View),
+        KeyCode::Char('o') => Some(InventoryAction::Open),
+        KeyCode::Char('p') => Some(InventoryAction::Insert),
+        KeyCode::Char('P') => Some(InventoryAction::PutOn),
+        KeyCode::Char('q') => Some(InventoryAction::Drink),
+        KeyCode::Char('S') => Some(InventoryAction::Smell),
+        KeyCode::Char('T') => Some(InventoryAction::Taste),
+        KeyCode::Char('x') => Some(InventoryAction::SwitchOff),
+        _ => None,
+    };
+
+    if let Some(inventory_action) = inventory_action {
+        let request = ActionRequest {
+            actor: game.special_entities.player,
+            action: new_action!(OpenMenu),
+            noun: Noun::Menu(MenuType::Inventory(inventory_action)),
+            second: Noun::Nothing
+        };
+        return Some(request);
+    }
     
     return None;
 }
@@ -56,10 +56,13 @@ pub mod test_window {
 
 pub fn get_offset(menu_type: MenuType, index: usize) -> Offset {
     match menu_type {
+        MenuType::Bestiary => UNKNOWN_OFFSET,
         MenuType::Character => UNKNOWN_OFFSET,
+        MenuType::Inventory(_) => UNKNOWN_OFFSET,
         MenuType::Main => UNKNOWN_OFFSET,
         MenuType::NewCharacter => new_character::get_offset(index),
         MenuType::Pause => UNKNOWN_OFFSET,
         MenuType::TestMenu => test_window::get_offset(index),
+        MenuType::Travel => UNKNOWN_OFFSET,
     }
 }
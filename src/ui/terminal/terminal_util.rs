@@ -3,14 +3,22 @@ use ringbuffer::RingBuffer;
 use std::{error::Error, fmt, io::{self, Write}, time::Duration};
 
 use crossterm::style;
+use strum::IntoEnumIterator;
 
-use crate::{action::ActionRequest, component::Position, game::{DebugInfo, Game, GameState}, item::Item, map::Tile, tabletop::{self, Race}, ui::menu::{Dropdown, Menu, MenuItem, MenuType, NewCharacter, PointBuy, TestMenu, TextField}, FRAMES_PER_SECOND};
+use crate::{action::{self, ActionRequest}, component::Position, game::{DebugInfo, Game, GameState}, lighting::{self, LightLevel}, item::{self, CoinType, CurrencyAmount, Item, Weight, WEIGHT_PER_POUND}, map::Tile, monster::{self, MonsterKind}, substance, tabletop::{self, DamageType, Dice, Race}, time::{self, Seconds, SECONDS_PER_HOUR}, ui::menu::{self, Dropdown, InventoryAction, Menu, MenuItem, MenuType, NewCharacter, PointBuy, TestMenu, TextField}, weather, FRAMES_PER_SECOND};
 
 use super::{icons, key_mapping, menu_offsets::{self, test_window, Offset}};
 
 pub const MIN_WIDTH: u16 = 80;
 pub const MIN_HEIGHT: u16 = 24;
 
+// Rows at the bottom of the screen that the map does not draw over
+pub const HUD_HEIGHT: u16 = 2;
+
+// How little food or water is left before the HUD starts to warn about it
+const HUNGRY_THRESHOLD: Seconds = 8 * SECONDS_PER_HOUR;
+const THIRSTY_THRESHOLD: Seconds = 6 * SECONDS_PER_HOUR;
+
 const DEFAULT_BACKGROUND: Color = Color::Black;
 const DEFAULT_FOREGROUND: Color = Color::White;
 
@@ -188,12 +196,106 @@ fn draw_dropdown_line(render_state: &mut RenderState, x: u16, y: u16, foreground
     }
 }
 
+fn draw_hud(render_state: &mut RenderState, game: &Game) {
+    let top: u16 = render_state.screen.height - HUD_HEIGHT;
+    let width: usize = render_state.screen.width as usize;
+
+    for y in top..render_state.screen.height {
+        draw_text(render_state, &" ".repeat(width), DEFAULT_FOREGROUND, 0, y);
+    }
+
+    if let Some(last_message) = game.messages.back() {
+        let mut message: String = last_message.clone();
+        message.truncate(width);
+        draw_text(render_state, &message, DEFAULT_FOREGROUND, 0, top);
+    }
+
+    if let Some(health) = game.components.get_health(game.special_entities.player) {
+        let color = if health.current * 4 <= health.maximum { Color::Red } else { DEFAULT_FOREGROUND };
+        draw_text(render_state, &format!("HP {}/{}", health.current, health.maximum), color, 0, top + 1);
+    }
+
+    let status = get_status_text(game);
+    if !status.is_empty() {
+        draw_text(render_state, &status, Color::Yellow, 12, top + 1);
+    }
+
+    let clock = &game.clock;
+    let date = clock.get_date();
+    let date = format!("{} {} ({}, {}) {}", time::format_date(&date), time::format_clock_time(clock), time::get_time_of_day_name(clock.get_time_of_day()), time::get_season_name(time::get_season(date.month)), weather::get_weather_name(game.weather));
+    let date_x = (width as u16).saturating_sub(date.len() as u16);
+    draw_text(render_state, &date, DEFAULT_FOREGROUND, date_x, top + 1);
+}
+
+fn get_status_text(game: &Game) -> String {
+    let player = game.special_entities.player;
+    let mut statuses: Vec<String> = Vec::new();
+
+    if let Some(nutrition) = game.components.get_nutrition(player) {
+        if nutrition.food == 0 {
+            statuses.push(String::from("Starving"));
+        } else if nutrition.food < HUNGRY_THRESHOLD {
+            statuses.push(String::from("Hungry"));
+        }
+        if nutrition.water == 0 {
+            statuses.push(String::from("Parched"));
+        } else if nutrition.water < THIRSTY_THRESHOLD {
+            statuses.push(String::from("Thirsty"));
+        }
+    }
+
+    let exhaustion = game.components.get_creature(player).map_or(0, |creature| creature.exhaustion);
+    if exhaustion > 0 {
+        statuses.push(format!("Exhausted {}", exhaustion));
+    }
+
+    let position = game.components.get_position(player).map(|position| (position.x, position.y));
+    if let Some((x, y)) = position.filter(|(x, y)| game.current_map.is_in_bounds(*x, *y)) {
+        let light = lighting::get_perceived_light(game, player, x, y, lighting::get_light_level(game, x, y));
+        match light {
+            LightLevel::Dark => statuses.push(String::from("Dark")),
+            LightLevel::Dim => statuses.push(String::from("Dim")),
+            LightLevel::Bright => (),
+        }
+    }
+
+    return statuses.join(" ");
+}
+
+fn is_visible(game: &Game, pos: &Position) -> bool {
+    if !game.current_map.is_in_bounds(pos.x, pos.y) {
+        return false;
+    }
+    return game.current_map.visible[game.current_map.coordinates_to_index(pos.x, pos.y)];
+}
+
 fn draw_ingame(render_state: &mut RenderState, game: &Game) {
-    for y in 0..render_state.screen.height {
-        for x in 0..render_state.screen.width {
+    for y in 0..game.current_map.height {
+        for x in 0..game.current_map.width {
+            let index = game.current_map.coordinates_to_index(x, y);
             let tile: &Tile = game.current_map.as_ref().get_tile(x, y);
-            render_state.current_frame.set_color(x, y, icons::tile_color(tile));
+
+            // Out of sight, only what was seen before is drawn
+            if !game.current_map.visible[index] {
+                let icon = if game.current_map.explored[index] { icons::tile_icon(tile) } else { ' ' };
+                render_state.current_frame.set_color(x, y, icons::REMEMBERED_COLOR);
+                render_state.current_frame.set_icon(x, y, icon);
+                continue;
+            }
+
+            let color = match game.current_map.get_spill(x, y) {
+                Some(substance) => icons::spill_color(substance),
+                None => icons::tile_color(tile),
+            };
+            render_state.current_frame.set_color(x, y, color);
             render_state.current_frame.set_icon(x, y, icons::tile_icon(tile));
+
+            if game.current_map.is_burning(x, y) {
+                render_state.current_frame.set_color(x, y, icons::fire_color(game.current_map.fires[index]));
+                render_state.current_frame.set_icon(x, y, icons::FIRE_ICON);
+            } else if game.current_map.smoke[index] > 0 {
+                render_state.current_frame.set_color(x, y, icons::SMOKE_COLOR);
+            }
         }
     }
 
@@ -205,7 +307,7 @@ fn draw_ingame(render_state: &mut RenderState, game: &Game) {
         let (x, y) = game.current_map.index_to_coordinates(i);
 
         let items: &Vec<Item> = game.current_map.get_items(x, y);
-        if items.is_empty() {
+        if items.is_empty() || !game.current_map.visible[i] {
             continue;
         }
 
@@ -220,9 +322,16 @@ fn draw_ingame(render_state: &mut RenderState, game: &Game) {
             continue;
         }
         let pos: &Position = &characters.position[i];
+        if !is_visible(game, pos) {
+            continue;
+        }
         let race: &Race = &characters.creature[i].race;
+        let color = match characters.npc[i].role {
+            Some(role) => icons::npc_color(role),
+            None => icons::creature_color(race),
+        };
 
-        render_state.current_frame.set_color(pos.x, pos.y, icons::creature_color(race));
+        render_state.current_frame.set_color(pos.x, pos.y, color);
         render_state.current_frame.set_icon(pos.x, pos.y,  icons::creature_icon(race));
     }
 
@@ -231,22 +340,180 @@ fn draw_ingame(render_state: &mut RenderState, game: &Game) {
             continue;
         }
         let pos: &Position = &monsters.position[i];
-        let race: &Race = &characters.creature[i].race;
+        if !is_visible(game, pos) {
+            continue;
+        }
+        let kind = monsters.monster[i].kind;
 
-        render_state.current_frame.set_color(pos.x, pos.y, icons::creature_color(race));
-        render_state.current_frame.set_icon(pos.x, pos.y,  icons::creature_icon(race));
+        render_state.current_frame.set_color(pos.x, pos.y, icons::monster_color(kind));
+        render_state.current_frame.set_icon(pos.x, pos.y, icons::monster_icon(kind));
     }
 
+    draw_hud(render_state, game);
+}
+
+fn draw_inventory_menu(render_state: &mut RenderState, game: &Game, inventory_action: InventoryAction) {
+    let title = match inventory_action {
+        InventoryAction::Burn => "Light what? (- around you)",
+        InventoryAction::Drink => "Drink what? (- from the ground)",
+        InventoryAction::Drop => "Drop what?",
+        InventoryAction::Eat => "Eat what?",
+        InventoryAction::Empty => "Empty what?",
+        InventoryAction::Fill => "Fill what?",
+        InventoryAction::Give => "Give what?",
+        InventoryAction::Insert => {
+            if game.menu_data.inventory.pending_item.is_some() { "Put it into what?" } else { "Put what?" }
+        },
+        InventoryAction::Open => "Open what?",
+        InventoryAction::PutOn => "Put what on it?",
+        InventoryAction::Smell => "Smell what? (- around you)",
+        InventoryAction::SwitchOff => "Put out what?",
+        InventoryAction::Taste => "Taste what? (- from the ground)",
+        InventoryAction::View => "Inventory",
+    };
+    draw_text(render_state, title, DEFAULT_FOREGROUND, 2, 1);
+
+    let player = game.special_entities.player;
+    let maybe_inventory = game.components.get_inventory(player);
+    if maybe_inventory.is_none() {
+        return;
+    }
+    let items: &Vec<Item> = &maybe_inventory.unwrap().items;
+    let inventory_menu = &game.menu_data.inventory;
+
+    if items.is_empty() {
+        draw_text(render_state, "You are not carrying anything.", DEFAULT_FOREGROUND, 2, 3);
+    }
+
+    let max_y: u16 = render_state.screen.height - 4;
+    let mut y: u16 = 3;
+    for (index, item) in items.iter().enumerate() {
+        let maybe_letter = menu::inventory_index_to_letter(index);
+        if maybe_letter.is_none() || y > max_y {
+            break;
+        }
+        let selected = inventory_menu.selected_item == Some(index)
+            || inventory_menu.pending_item.is_some_and(|pending| pending.index == index);
+        let color = if selected { Color::Yellow } else { DEFAULT_FOREGROUND };
+
+        draw_text(render_state, &maybe_letter.unwrap().to_string(), Color::Yellow, 2, y);
+        y = draw_inventory_item(render_state, game, item, color, 4, y, max_y);
+    }
+
+    let footer_y: u16 = render_state.screen.height - 2;
+    if let Some(creature) = game.components.get_creature(player) {
+        let capacity = tabletop::carrying_capacity(creature.stats.strength, creature.size);
+        let carried = format!("Carrying {} of {} lb", format_weight(item::get_pile_weight(items)), capacity);
+        draw_text(render_state, &carried, DEFAULT_FOREGROUND, 2, footer_y);
+    }
+
+    if inventory_menu.selected_item.is_some() {
+        draw_text_field(render_state, &inventory_menu.quantity, Offset::new(2, footer_y - 1));
+    }
+}
+
+// Draws an item and, indented below it, whatever it contains. Returns the next free row
+fn draw_inventory_item(render_state: &mut RenderState, game: &Game, item: &Item, color: Color, x: u16, y: u16, max_y: u16) -> u16 {
+    let mut name = item::get_display_name(item);
+    if let Some(liquid) = &item.liquid {
+        name = format!("{} ({} oz of {})", name, liquid.volume, substance::get_apparent_name(liquid.substance, &game.data_tables.substance_tag_map));
+    }
+    draw_text(render_state, &name, color, x, y);
+    draw_text(render_state, &format!("{:>8} lb", format_weight(item::get_total_weight(item))), DEFAULT_FOREGROUND, 44, y);
+    draw_text(render_state, &format!("{:>8}", format_price(item::get_price(item))), DEFAULT_FOREGROUND, 58, y);
+
+    let mut next_y = y + 1;
+    for content in &item.contents {
+        if next_y > max_y {
+            break;
+        }
+        next_y = draw_inventory_item(render_state, game, content, DEFAULT_FOREGROUND, x + 2, next_y, max_y);
+    }
+    return next_y;
+}
+
+fn format_dice(dice: &Dice) -> String {
+    if dice.count == 0 {
+        return dice.bonus.to_string();
+    }
+    if dice.bonus == 0 {
+        return format!("{}d{}", dice.count, dice.sides);
+    }
+    return format!("{}d{}{:+}", dice.count, dice.sides, dice.bonus);
+}
+
+fn format_damage_types(damage_types: &[DamageType]) -> String {
+    return damage_types.iter().map(|damage_type| tabletop::get_damage_type_name(*damage_type)).collect::<Vec<&str>>().join(", ");
+}
+
+// Everything known about each kind of monster the player has come across
+fn draw_bestiary_menu(render_state: &mut RenderState, game: &Game) {
+    draw_text(render_state, "Bestiary", DEFAULT_FOREGROUND, 2, 1);
+
+    let max_y: u16 = render_state.screen.height - 2;
+    let mut y: u16 = 3;
+    for kind in MonsterKind::iter() {
+        if !game.bestiary[kind] {
+            continue;
+        }
+        if y + 3 > max_y {
+            break;
+        }
+        let definition = monster::get_monster_definition(kind);
+        render_state.current_frame.set_color(2, y, icons::monster_color(kind));
+        render_state.current_frame.set_icon(2, y, icons::monster_icon(kind));
+        let summary = format!("{} ({:?}, CR {}) AC {}, HP {}, speed {} ft", definition.name, definition.size, monster::get_challenge_name(definition.challenge),
+            definition.armor_class, format_dice(&definition.hit_dice), definition.speed);
+        draw_text(render_state, &summary, DEFAULT_FOREGROUND, 4, y);
+        y += 1;
+
+        let attacks: Vec<String> = definition.attacks.iter()
+            .map(|attack| format!("{} {:+} ({} {})", attack.name, attack.to_hit, format_dice(&attack.damage), tabletop::get_damage_type_name(attack.damage_type)))
+            .collect();
+        draw_text(render_state, &format!("Attacks: {}", attacks.join(", ")), DEFAULT_FOREGROUND, 6, y);
+        y += 1;
+
+        let mut defenses: Vec<String> = Vec::new();
+        if !definition.resistances.is_empty() {
+            defenses.push(format!("resists {}", format_damage_types(definition.resistances)));
+        }
+        if !definition.immunities.is_empty() {
+            defenses.push(format!("immune to {}", format_damage_types(definition.immunities)));
+        }
+        if !definition.vulnerabilities.is_empty() {
+            defenses.push(format!("vulnerable to {}", format_damage_types(definition.vulnerabilities)));
+        }
+        if !defenses.is_empty() {
+            draw_text(render_state, &action::capitalize(defenses.join("; ")), DEFAULT_FOREGROUND, 6, y);
+            y += 1;
+        }
+        y += 1;
+    }
+
+    if y == 3 {
+        draw_text(render_state, "You haven't met any monsters yet.", DEFAULT_FOREGROUND, 2, y);
+    }
+}
+
+// The map as usual, with the tile to travel to picked out
+fn draw_travel_menu(render_state: &mut RenderState, game: &Game) {
+    draw_ingame(render_state, game);
+    let cursor = &game.menu_data.travel;
+    render_state.current_frame.set_background(cursor.x, cursor.y, Color::DarkYellow);
+    draw_text(render_state, "Travel where? (Enter to go, Esc to cancel)", DEFAULT_FOREGROUND, 0, 0);
 }
 
 fn draw_menu(menu_type: MenuType, render_state: &mut RenderState, game: &Game) {
     clear_frame(render_state);
     match menu_type {
+        MenuType::Bestiary => draw_bestiary_menu(render_state, game),
         MenuType::Character => (),
+        MenuType::Inventory(inventory_action) => draw_inventory_menu(render_state, game, inventory_action),
         MenuType::Main => draw_main_menu(render_state, game),
         MenuType::NewCharacter => draw_new_character_menu(render_state, game),
         MenuType::Pause => (),
         MenuType::TestMenu => draw_test_menu(render_state, game),
+        MenuType::Travel => draw_travel_menu(render_state, game),
     };
 }
 
@@ -289,7 +556,7 @@ fn draw_menu_items(render_state: &mut RenderState, menu: &dyn Menu) {
     }
 }
 
-fn draw_main_menu(render_state: &mut RenderState, _game: &Game) {
+fn draw_main_menu(render_state: &mut RenderState, game: &Game) {
     draw_text(render_state, "P", Color::Yellow, 3, 1);
     draw_text(render_state, "Play game", Color::White, 5, 1);
 
@@ -301,6 +568,8 @@ fn draw_main_menu(render_state: &mut RenderState, _game: &Game) {
 
     draw_text(render_state, "T", Color::Yellow, 3, 4);
     draw_text(render_state, "Test Menu", Color::White, 5, 4);
+
+    draw_text(render_state, &format!("Seed {}", game.rng.seed), Color::DarkGrey, 3, 6);
 }
 
 fn draw_new_character_menu(render_state: &mut RenderState, game: &Game) {
@@ -455,6 +724,21 @@ pub fn game_drawing_end() {
     );
 }
 
+fn format_price(price: CurrencyAmount) -> String {
+    let coin = match price.coin_type {
+        CoinType::Copper => "cp",
+        CoinType::Silver => "sp",
+        CoinType::Electrum => "ep",
+        CoinType::Gold => "gp",
+        CoinType::Platinum => "pp",
+    };
+    return format!("{} {}", price.amount, coin);
+}
+
+fn format_weight(weight: Weight) -> String {
+    return format!("{}.{:02}", weight / WEIGHT_PER_POUND, (weight % WEIGHT_PER_POUND) / 10);
+}
+
 fn get_average_fps(debug_info: &DebugInfo) -> u32 {
     let mut sum: f64 = 0.0;
 
@@ -0,0 +1,283 @@
+use enum_map::{enum_map, Enum, EnumMap};
+
+use crate::{action, electricity, game::Game, lighting::LightLevel, map, rng::RngStream, tabletop::Dice, temperature::Temperature, time::{self, Season, TimeOfDay}};
+
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+}
+
+pub const fn get_weather_name(weather: Weather) -> &'static str {
+    match weather {
+        Weather::Clear => "Clear",
+        Weather::Cloudy => "Cloudy",
+        Weather::Fog => "Fog",
+        Weather::Rain => "Rain",
+        Weather::Snow => "Snow",
+    }
+}
+
+// The percent chance of each kind of weather on any given day
+pub fn get_weather_chances(season: Season) -> EnumMap<Weather, u32> {
+    return match season {
+        Season::Spring => enum_map! {
+            Weather::Clear => 40,
+            Weather::Cloudy => 25,
+            Weather::Fog => 10,
+            Weather::Rain => 25,
+            Weather::Snow => 0,
+        },
+        Season::Summer => enum_map! {
+            Weather::Clear => 65,
+            Weather::Cloudy => 15,
+            Weather::Fog => 5,
+            Weather::Rain => 15,
+            Weather::Snow => 0,
+        },
+        Season::Autumn => enum_map! {
+            Weather::Clear => 30,
+            Weather::Cloudy => 30,
+            Weather::Fog => 15,
+            Weather::Rain => 25,
+            Weather::Snow => 0,
+        },
+        Season::Winter => enum_map! {
+            Weather::Clear => 30,
+            Weather::Cloudy => 30,
+            Weather::Fog => 10,
+            Weather::Rain => 5,
+            Weather::Snow => 25,
+        },
+    };
+}
+
+// Picks the weather for a day from a roll between 0 and 99
+pub fn pick_weather(season: Season, roll: u32) -> Weather {
+    let mut total: u32 = 0;
+    for (weather, chance) in get_weather_chances(season) {
+        total += chance;
+        if roll < total {
+            return weather;
+        }
+    }
+    return Weather::Clear;
+}
+
+// Dawn and dusk are dim, and clouds keep the day from ever getting bright
+pub fn get_daylight(time_of_day: TimeOfDay, weather: Weather) -> LightLevel {
+    let result = match time_of_day {
+        TimeOfDay::Night => LightLevel::Dark,
+        TimeOfDay::Dawn => LightLevel::Dim,
+        TimeOfDay::Morning => LightLevel::Bright,
+        TimeOfDay::Afternoon => LightLevel::Bright,
+        TimeOfDay::Dusk => LightLevel::Dim,
+        TimeOfDay::Evening => LightLevel::Dark,
+    };
+    if weather == Weather::Clear {
+        return result;
+    }
+    return LightLevel::min(result, LightLevel::Dim);
+}
+
+pub fn get_season_temperature(season: Season) -> Temperature {
+    return match season {
+        Season::Spring => 12,
+        Season::Summer => 24,
+        Season::Autumn => 10,
+        Season::Winter => -4,
+    };
+}
+
+// How much warmer or colder than the season's average it is outside
+pub fn get_time_of_day_temperature(time_of_day: TimeOfDay) -> Temperature {
+    return match time_of_day {
+        TimeOfDay::Night => -6,
+        TimeOfDay::Dawn => -4,
+        TimeOfDay::Morning => 0,
+        TimeOfDay::Afternoon => 4,
+        TimeOfDay::Dusk => 0,
+        TimeOfDay::Evening => -3,
+    };
+}
+
+pub fn get_weather_temperature(weather: Weather) -> Temperature {
+    return match weather {
+        Weather::Clear => 0,
+        Weather::Cloudy => -1,
+        Weather::Fog => -2,
+        Weather::Rain => -3,
+        Weather::Snow => -5,
+    };
+}
+
+pub fn get_outdoor_temperature(season: Season, time_of_day: TimeOfDay, weather: Weather) -> Temperature {
+    return get_season_temperature(season) + get_time_of_day_temperature(time_of_day) + get_weather_temperature(weather);
+}
+
+// The percent chance a patch of herbs has anything worth foraging
+pub fn get_forage_chance(season: Season) -> u32 {
+    return match season {
+        Season::Spring => 60,
+        Season::Summer => 80,
+        Season::Autumn => 50,
+        Season::Winter => 5,
+    };
+}
+
+// How many monsters turn up compared to the daytime, as a percentage
+pub fn get_spawn_rate(time_of_day: TimeOfDay) -> u32 {
+    return match time_of_day {
+        TimeOfDay::Night => 200,
+        TimeOfDay::Dawn => 125,
+        TimeOfDay::Morning => 100,
+        TimeOfDay::Afternoon => 100,
+        TimeOfDay::Dusk => 125,
+        TimeOfDay::Evening => 150,
+    };
+}
+
+// Settles what the weather will be like for the day
+pub fn roll_weather(game: &mut Game) {
+    let season = time::get_season(game.clock.get_date().month);
+    let roll = game.rng.range(RngStream::Weather, 0, 99);
+    game.weather = pick_weather(season, roll as u32);
+}
+
+// Brings the light and temperature outdoors in line with the clock
+pub fn update_outdoors(game: &mut Game) {
+    let season = time::get_season(game.clock.get_date().month);
+    let time_of_day = game.clock.get_time_of_day();
+    let map = game.current_map.as_mut();
+    map.daylight = get_daylight(time_of_day, game.weather);
+    map.outdoor_temperature = get_outdoor_temperature(season, time_of_day, game.weather);
+}
+
+// Percent chance, each minute it rains, of lightning striking near the player
+const LIGHTNING_CHANCE: u32 = 2;
+// How far from the player it can come down, in tiles
+const LIGHTNING_RANGE: i32 = 8;
+const LIGHTNING_DAMAGE: Dice = Dice::new(2, 6, 0);
+
+// Storms now and then bring lightning down on open ground, which arcs on
+// through anything wet or metal nearby
+pub fn strike_lightning(game: &mut Game) {
+    if game.weather != Weather::Rain || !game.rng.chance(RngStream::Weather, LIGHTNING_CHANCE) {
+        return;
+    }
+    let player = game.special_entities.player;
+    let maybe_position = game.components.get_position(player);
+    if maybe_position.is_none() || !game.current_map.is_in_bounds(maybe_position.unwrap().x, maybe_position.unwrap().y) {
+        return;
+    }
+    let (player_x, player_y) = (maybe_position.unwrap().x as i32, maybe_position.unwrap().y as i32);
+    let (width, height) = (game.current_map.width as i32, game.current_map.height as i32);
+    let x = game.rng.range(RngStream::Weather, player_x - LIGHTNING_RANGE, player_x + LIGHTNING_RANGE).clamp(0, width - 1) as u16;
+    let y = game.rng.range(RngStream::Weather, player_y - LIGHTNING_RANGE, player_y + LIGHTNING_RANGE).clamp(0, height - 1) as u16;
+    if !map::is_outdoors(game.current_map.get_tile(x, y)) {
+        return;
+    }
+
+    action::report(game, player, String::from("Lightning strikes!"));
+    let damage = game.rng.roll(RngStream::Weather, LIGHTNING_DAMAGE);
+    electricity::discharge(game, x, y, damage);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lighting, map::{GameMap, Tile}, time::SECONDS_PER_HOUR};
+
+    #[test]
+    fn test_daylight_outdoors() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 2, 1));
+        game.current_map.set_tile(0, 0, Tile::Plains);
+        game.current_map.set_tile(1, 0, Tile::Floor);
+        game.current_map.ambient_light = LightLevel::Dark;
+
+        update_outdoors(&mut game);
+        assert_eq!(lighting::get_light_level(&game, 0, 0), LightLevel::Bright);
+        assert_eq!(lighting::get_light_level(&game, 1, 0), LightLevel::Dark);
+
+        // Dusk
+        game.clock.advance(9 * SECONDS_PER_HOUR);
+        update_outdoors(&mut game);
+        assert_eq!(lighting::get_light_level(&game, 0, 0), LightLevel::Dim);
+
+        game.clock.advance(4 * SECONDS_PER_HOUR);
+        update_outdoors(&mut game);
+        assert_eq!(lighting::get_light_level(&game, 0, 0), LightLevel::Dark);
+        assert!(game.current_map.get_ambient_temperature(0, 0) < game.current_map.get_ambient_temperature(1, 0));
+    }
+
+    #[test]
+    fn test_seasonal_weather() {
+        assert_eq!(pick_weather(Season::Summer, 0), Weather::Clear);
+        assert_eq!(pick_weather(Season::Winter, 99), Weather::Snow);
+        assert_eq!(pick_weather(Season::Summer, 99), Weather::Rain);
+        for season in [Season::Spring, Season::Summer, Season::Autumn, Season::Winter] {
+            assert_eq!(get_weather_chances(season).values().sum::<u32>(), 100);
+        }
+        assert!(get_outdoor_temperature(Season::Winter, TimeOfDay::Night, Weather::Snow) < 0);
+    }
+
+    #[test]
+    fn test_lightning_in_storms() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 5, 5));
+        for y in 0..5 {
+            for x in 0..5 {
+                game.current_map.set_tile(x, y, Tile::Plains);
+                game.current_map.set_spill(x, y, Some(crate::substance::Substance::Water));
+            }
+        }
+        let player = game.special_entities.player;
+        *game.components.get_position_mut(player).unwrap() = crate::component::Position { x: 2, y: 2 };
+        game.components.get_health_mut(player).unwrap().current = 10000;
+
+        // Clear skies never strike, but a sodden field carries a strike
+        // anywhere in it to the player sooner or later
+        game.weather = Weather::Clear;
+        for _ in 0..500 {
+            strike_lightning(&mut game);
+        }
+        assert_eq!(game.components.get_health(player).unwrap().current, 10000);
+        game.weather = Weather::Rain;
+        for _ in 0..500 {
+            strike_lightning(&mut game);
+        }
+        assert!(game.components.get_health(player).unwrap().current < 10000);
+    }
+
+    // How many patches of herbs out of twenty turn up anything
+    fn forage_patches(game: &mut Game) -> u32 {
+        use crate::action::{ActionRoutine, Forage, Noun};
+        let player = game.special_entities.player;
+        game.components.get_inventory_mut(player).unwrap().items.clear();
+        for _ in 0..20 {
+            game.current_map.set_tile(0, 0, Tile::Herbs);
+            Forage::execute(game, player, Noun::Nothing, Noun::Nothing);
+            assert_eq!(*game.current_map.get_tile(0, 0), Tile::Plains);
+        }
+        return game.components.get_inventory(player).unwrap().items.iter().map(|item| item.quantity).sum();
+    }
+
+    #[test]
+    fn test_foraging_follows_the_seasons() {
+        let mut game = Game::new();
+        game.current_map = Box::new(GameMap::new(0, 1, 1));
+        *game.components.get_position_mut(game.special_entities.player).unwrap() = crate::component::Position { x: 0, y: 0 };
+
+        game.clock.time = 0;
+        assert_eq!(time::get_season(game.clock.get_date().month), Season::Winter);
+        let winter = forage_patches(&mut game);
+        game.clock.time = 6 * time::DAYS_IN_A_MONTH as u64 * time::SECONDS_PER_DAY;
+        assert_eq!(time::get_season(game.clock.get_date().month), Season::Summer);
+        let summer = forage_patches(&mut game);
+        assert!(winter <= 3 && summer >= 10, "{} in winter and {} in summer", winter, summer);
+    }
+}